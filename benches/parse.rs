@@ -0,0 +1,54 @@
+//! 比较按需定位读取文件与内存映射两种方式解析节表和读取节内容的耗时
+//!
+//! 默认解析基准测试程序本身, 可以用EASYREADELF_BENCH_FILE指定更大的文件, 如带调试信息的二进制或core文件
+
+use std::fs::File;
+use std::hint::black_box;
+use std::path::PathBuf;
+use criterion::{criterion_group, criterion_main, Criterion};
+use easyreadelf::elf::mapped::MappedFile;
+use easyreadelf::elf::section::{parse_sections, read_section_data, SHF_COMPRESSED};
+
+fn bench_file() -> PathBuf {
+    std::env::var_os("EASYREADELF_BENCH_FILE").map(PathBuf::from).unwrap_or_else(|| std::env::current_exe().unwrap())
+}
+
+fn sections(c:&mut Criterion) {
+    let path = bench_file();
+    let mut group = c.benchmark_group("sections");
+    group.bench_function("file", |b| b.iter(|| {
+        let mut file = File::open(&path).unwrap();
+        black_box(parse_sections(&mut file).unwrap().len())
+    }));
+    group.bench_function("mmap", |b| b.iter(|| {
+        let file = MappedFile::open(&path).unwrap();
+        black_box(file.elf().unwrap().sections().unwrap().len())
+    }));
+    group.finish();
+}
+
+/// 读取所有节的内容并计算字节和, 模拟转储整个文件
+fn section_data(c:&mut Criterion) {
+    let path = bench_file();
+    let mut group = c.benchmark_group("section_data");
+    group.bench_function("file", |b| b.iter(|| {
+        let mut file = File::open(&path).unwrap();
+        let mut sum = 0u64;
+        for section in parse_sections(&mut file).unwrap().iter().filter(|section| section.sh_flags & SHF_COMPRESSED == 0) {
+            sum += read_section_data(&mut file, section).unwrap().iter().map(|byte| *byte as u64).sum::<u64>();
+        }
+        black_box(sum)
+    }));
+    group.bench_function("mmap", |b| b.iter(|| {
+        let file = MappedFile::open(&path).unwrap();
+        let mut sum = 0u64;
+        for section in file.elf().unwrap().sections().unwrap().iter().filter(|section| section.sh_flags & SHF_COMPRESSED == 0) {
+            sum += section.data.iter().map(|byte| *byte as u64).sum::<u64>();
+        }
+        black_box(sum)
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, sections, section_data);
+criterion_main!(benches);
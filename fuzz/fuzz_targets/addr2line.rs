@@ -0,0 +1,13 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::addr2line::Symbolizer;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(symbolizer) = Symbolizer::load(&mut Cursor::new(data)) {
+        for address in data.chunks_exact(8).take(16) {
+            let _ = symbolizer.symbolize(u64::from_le_bytes(address.try_into().unwrap()));
+        }
+    }
+});
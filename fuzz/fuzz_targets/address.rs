@@ -0,0 +1,15 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::address::AddressMap;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(map) = AddressMap::load(&mut Cursor::new(data)) {
+        for value in data.chunks_exact(8).take(16) {
+            let value = u64::from_le_bytes(value.try_into().unwrap());
+            let _ = map.locate_vaddr(value);
+            let _ = map.locate_offset(value);
+        }
+    }
+});
@@ -0,0 +1,18 @@
+#![no_main]
+
+use std::io::Cursor;
+use std::path::Path;
+use easyreadelf::elf::archive::parse_archive;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut file = Cursor::new(data);
+    // thin静态库的成员是外部文件, 不读取
+    if let Ok(archive) = parse_archive(&mut file) {
+        if !archive.thin {
+            for member in &archive.members {
+                let _ = archive.read_member(&mut file, Path::new(""), member);
+            }
+        }
+    }
+});
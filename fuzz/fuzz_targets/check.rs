@@ -0,0 +1,9 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::check::check_file;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = check_file(&mut Cursor::new(data));
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::elf::core::parse_core;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_core(&mut Cursor::new(data));
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::dwarf::info::parse_debug_info;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_debug_info(&mut Cursor::new(data));
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::dwarf::line::parse_debug_line;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_debug_line(&mut Cursor::new(data));
+});
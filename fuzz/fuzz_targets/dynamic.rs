@@ -0,0 +1,9 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::elf::dynamic::parse_dynamic;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_dynamic(&mut Cursor::new(data));
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::dwarf::frame::{parse_eh_frame, parse_eh_frame_hdr};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut elf = Cursor::new(data);
+    if let Ok(Some(eh_frame)) = parse_eh_frame(&mut elf) {
+        let _ = eh_frame.to_string();
+    }
+    let _ = parse_eh_frame_hdr(&mut elf);
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use easyreadelf::elf::file::ElfFile;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(elf) = ElfFile::from_bytes(data.to_vec()) else { return };
+    let _ = elf.build_id();
+    let _ = elf.symbols();
+    let _ = elf.dynamic_symbols();
+    let _ = elf.units();
+    let _ = elf.line_programs();
+    let _ = elf.read_vaddr(elf.header().e_entry, 256);
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use std::io::Cursor;
+use std::io;
+use easyreadelf::gnu::{write_file_header, write_program_headers, write_section_headers, GnuOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut elf = Cursor::new(data);
+    let options = GnuOptions { wide:true, after_header:false };
+    let _ = write_file_header(&mut elf, &mut io::sink());
+    let _ = write_section_headers(&mut elf, &mut io::sink(), &options);
+    let _ = write_program_headers(&mut elf, &mut io::sink(), &options);
+});
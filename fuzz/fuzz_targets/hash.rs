@@ -0,0 +1,13 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::elf::hash::parse_hash_tables;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(tables) = parse_hash_tables(&mut Cursor::new(data)) {
+        let _ = tables.lookup("main");
+        let _ = tables.histograms().iter().map(ToString::to_string).count();
+        let _ = tables.problems();
+    }
+});
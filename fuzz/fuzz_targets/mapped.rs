@@ -0,0 +1,15 @@
+#![no_main]
+
+use easyreadelf::elf::mapped::MappedElf;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(elf) = MappedElf::parse(data) else { return };
+    let _ = elf.sections();
+    let _ = elf.section_headers();
+    if let Ok(segments) = elf.segments() {
+        for segment in &segments {
+            let _ = elf.segment_data(segment);
+        }
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::elf::note::{parse_note_sections, parse_note_segments};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut elf = Cursor::new(data);
+    let _ = parse_note_segments(&mut elf);
+    let _ = parse_note_sections(&mut elf);
+});
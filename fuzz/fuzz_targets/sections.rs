@@ -0,0 +1,14 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::elf::section::{parse_sections, read_section_data};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut elf = Cursor::new(data);
+    if let Ok(sections) = parse_sections(&mut elf) {
+        for section in &sections {
+            let _ = read_section_data(&mut elf, section);
+        }
+    }
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::elf::segment::{parse_segments, read_segment_data, read_vaddr};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut elf = Cursor::new(data);
+    if let Ok(segments) = parse_segments(&mut elf) {
+        for segment in &segments {
+            let _ = read_segment_data(&mut elf, segment);
+            // 跨越段的末尾, 覆盖零填充和未映射的地址
+            let _ = read_vaddr(&mut elf, &segments, segment.p_vaddr.saturating_add(segment.p_filesz).saturating_sub(8), 64);
+        }
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::size::{size_report, DataSource};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut elf = Cursor::new(data);
+    for source in [DataSource::SEGMENTS, DataSource::SECTIONS, DataSource::SYMBOLS, DataSource::COMPILE_UNITS] {
+        let _ = size_report(&mut elf, &source);
+    }
+});
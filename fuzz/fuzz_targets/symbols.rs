@@ -0,0 +1,11 @@
+#![no_main]
+
+use std::io::Cursor;
+use easyreadelf::elf::symbol::{parse_dynamic_symbols, parse_symbols};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut elf = Cursor::new(data);
+    let _ = parse_symbols(&mut elf);
+    let _ = parse_dynamic_symbols(&mut elf);
+});
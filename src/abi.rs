@@ -0,0 +1,192 @@
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::elf::dynamic::{dynamic_strings, parse_dynamic, DT_NEEDED, DT_SONAME};
+use crate::elf::header::parse_header;
+use crate::elf::symbol::{parse_dynamic_symbols, type_to_string, STT_OBJECT, STT_TLS};
+use crate::elf::version::{parse_versions, versioned_name, SymbolVersion, VER_FLG_BASE};
+use crate::json::JsonValue;
+
+pub enum Severity {
+    /// 旧版本编译的程序仍然可以使用新库, 如新增符号
+    COMPATIBLE,
+    /// 旧版本编译的程序可能无法加载或行为错误
+    BREAKING,
+}
+
+impl Severity {
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        match self {
+            Severity::COMPATIBLE => String::from("COMPATIBLE"),
+            Severity::BREAKING => String::from("BREAKING"),
+        }
+    }
+}
+
+pub struct AbiChange {
+    pub severity:Severity,
+    pub description:String,
+}
+
+pub struct AbiReport {
+    pub soname:Option<String>,
+    pub changes:Vec<AbiChange>,
+}
+
+/// 库的ABI中与兼容性有关的部分
+struct Abi {
+    class:u8,
+    machine:String,
+    soname:Option<String>,
+    needed:Vec<String>,
+    /// 版本定义的名字, 不包括以库名命名的基础版本
+    versions:Vec<String>,
+    exports:Vec<Export>,
+}
+
+struct Export {
+    name:String,
+    version:Option<SymbolVersion>,
+    symbol_type:u8,
+    size:u64,
+}
+
+impl Export {
+    fn display_name(&self) -> String {
+        versioned_name(&self.name, self.version.as_ref())
+    }
+
+    fn version_name(&self) -> Option<&str> {
+        self.version.as_ref().map(|version| version.name.as_str())
+    }
+
+    fn hidden(&self) -> bool {
+        self.version.as_ref().is_some_and(|version| version.hidden)
+    }
+
+    /// 复制重定位按旧的大小复制数据对象, 大小变化会破坏ABI
+    fn is_data(&self) -> bool {
+        matches!(self.symbol_type, STT_OBJECT | STT_TLS)
+    }
+}
+
+fn read_abi<R:Read + Seek>(elf:&mut R) -> io::Result<Abi> {
+    let header = parse_header(elf)?;
+    let dynamic = parse_dynamic(elf)?;
+    let symbols = parse_dynamic_symbols(elf)?;
+    let info = parse_versions(elf)?;
+    let exports = symbols.iter()
+        .filter(|symbol| symbol.is_exported())
+        .map(|symbol| Export {
+            name:symbol.name.clone(),
+            version:info.symbol_version(symbol.index),
+            symbol_type:symbol.symbol_type(),
+            size:symbol.st_size,
+        })
+        // 链接器为每个版本生成的同名ABS符号已经体现在版本定义中
+        .filter(|export| !(export.symbol_type == STT_OBJECT && export.size == 0 && export.version_name() == Some(export.name.as_str())))
+        .collect();
+    Ok(Abi {
+        class:header.e_ident[4],
+        machine:header.e_machine.to_string(),
+        soname:dynamic_strings(&dynamic, DT_SONAME).first().map(|soname| soname.to_string()),
+        needed:dynamic_strings(&dynamic, DT_NEEDED).iter().map(|name| name.to_string()).collect(),
+        versions:info.definitions.iter()
+            .filter(|definition| definition.flags & VER_FLG_BASE == 0)
+            .filter_map(|definition| definition.names.first().cloned())
+            .collect(),
+        exports,
+    })
+}
+
+/// 旧符号在新库中的对应: 带版本的引用需要同名同版本的定义(默认或非默认均可), 不带版本的引用绑定默认版本
+fn find_export<'a>(exports:&'a [Export], old:&Export) -> Option<&'a Export> {
+    exports.iter().find(|export| {
+        export.name == old.name && match old.version_name() {
+            Some(version) => export.version_name() == Some(version),
+            None => !export.hidden(),
+        }
+    })
+}
+
+/// 比较同一个共享库的新旧版本, 把导出符号, 符号版本, 数据对象大小, SONAME和依赖的变化分为兼容和破坏性两类
+pub fn check_abi<R:Read + Seek>(old:&mut R, new:&mut R) -> io::Result<AbiReport> {
+    let old = read_abi(old)?;
+    let new = read_abi(new)?;
+    let mut changes = Vec::new();
+    let mut push = |severity:Severity, description:String| changes.push(AbiChange { severity, description });
+
+    if old.class != new.class || old.machine != new.machine {
+        push(Severity::BREAKING, format!("architecture changed: {} -> {}",old.machine,new.machine));
+    }
+    match (&old.soname, &new.soname) {
+        (Some(old), Some(new)) if old != new => push(Severity::BREAKING, format!("SONAME changed: {} -> {}",old,new)),
+        (Some(old), None) => push(Severity::BREAKING, format!("SONAME removed: {}",old)),
+        (None, Some(new)) => push(Severity::COMPATIBLE, format!("SONAME added: {}",new)),
+        _ => {}
+    }
+    for name in old.needed.iter().filter(|name| !new.needed.contains(name)) {
+        push(Severity::BREAKING, format!("needed library removed: {}",name));
+    }
+    for name in new.needed.iter().filter(|name| !old.needed.contains(name)) {
+        push(Severity::COMPATIBLE, format!("needed library added: {}",name));
+    }
+    for version in old.versions.iter().filter(|version| !new.versions.contains(version)) {
+        push(Severity::BREAKING, format!("version definition removed: {}",version));
+    }
+    for version in new.versions.iter().filter(|version| !old.versions.contains(version)) {
+        push(Severity::COMPATIBLE, format!("version definition added: {}",version));
+    }
+    for export in &old.exports {
+        let Some(other) = find_export(&new.exports, export) else {
+            push(Severity::BREAKING, format!("symbol removed: {} ({})",export.display_name(),type_to_string(export.symbol_type)));
+            continue;
+        };
+        if export.symbol_type != other.symbol_type {
+            push(Severity::BREAKING, format!("symbol type changed: {} {} -> {}",export.display_name(),type_to_string(export.symbol_type),type_to_string(other.symbol_type)));
+        } else if export.is_data() && export.size != other.size {
+            push(Severity::BREAKING, format!("data object size changed: {} {} -> {} bytes",export.display_name(),export.size,other.size));
+        }
+        if !export.hidden() && other.hidden() {
+            push(Severity::COMPATIBLE, format!("symbol is no longer the default version: {}",other.display_name()));
+        }
+    }
+    for export in &new.exports {
+        let added = !old.exports.iter().any(|other| other.name == export.name && other.version_name() == export.version_name());
+        if added {
+            push(Severity::COMPATIBLE, format!("symbol added: {} ({})",export.display_name(),type_to_string(export.symbol_type)));
+        }
+    }
+    Ok(AbiReport { soname:new.soname, changes })
+}
+
+impl AbiReport {
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|change| matches!(change.severity, Severity::BREAKING))
+    }
+
+    fn count(&self, breaking:bool) -> usize {
+        self.changes.iter().filter(|change| matches!(change.severity, Severity::BREAKING) == breaking).count()
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::object([
+            ("soname", self.soname.clone().into()),
+            ("breaking", self.is_breaking().into()),
+            ("changes", JsonValue::ARRAY(self.changes.iter().map(|change| JsonValue::object([
+                ("severity", change.severity.to_string().into()),
+                ("description", change.description.clone().into()),
+            ])).collect())),
+        ])
+    }
+}
+
+impl std::fmt::Display for AbiReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            writeln!(f,"{:<11}{}",change.severity.to_string(),change.description)?;
+        }
+        writeln!(f,"{} breaking, {} compatible changes",self.count(true),self.count(false))
+    }
+}
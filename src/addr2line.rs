@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use std::path::Path;
+use crate::dwarf::constants::*;
+use crate::dwarf::info::{parse_units, CompilationUnit};
+use crate::dwarf::line::{parse_line_programs, LineProgram};
+use crate::dwarf::ranges::{die_ranges, Range};
+use crate::dwarf::sections::DwarfSections;
+use crate::elf::section::parse_sections;
+use crate::elf::segment::{parse_segments, SegmentType};
+use crate::elf::symbol::{parse_dynamic_symbols, parse_symbols, ElfSymbol};
+
+/// 调用链中的一帧, 第一帧是地址所在的(可能被内联的)函数
+pub struct Frame {
+    pub function:Option<String>,
+    pub file:Option<String>,
+    pub line:u64,
+    pub column:u64,
+}
+
+/// 一个地址的符号化结果
+pub struct Symbolized {
+    pub address:u64,
+    /// 所在函数符号及地址相对于符号的偏移
+    pub symbol:Option<(String, u64)>,
+    pub frames:Vec<Frame>,
+}
+
+/// 用于追溯函数名的DIE信息, origin为DW_AT_abstract_origin或DW_AT_specification
+struct DieName {
+    name:Option<String>,
+    origin:Option<u64>,
+}
+
+/// 函数或内联函数覆盖的地址范围
+struct Scope {
+    ranges:Vec<Range>,
+    depth:usize,
+    die:u64,
+    inlined:bool,
+    call_file:u64,
+    call_line:u64,
+    call_column:u64,
+}
+
+struct UnitScopes {
+    comp_dir:Option<String>,
+    ranges:Vec<Range>,
+    program:Option<usize>,
+    scopes:Vec<Scope>,
+}
+
+/// 基于符号表和DWARF的地址到源码位置的转换
+pub struct Symbolizer {
+    functions:Vec<ElfSymbol>,
+    programs:Vec<LineProgram>,
+    units:Vec<UnitScopes>,
+    names:HashMap<u64, DieName>,
+    /// 每个节的地址范围, 按节索引排列
+    section_ranges:Vec<Range>,
+    load_base:u64,
+}
+
+fn in_ranges(ranges:&[Range], address:u64) -> bool {
+    ranges.iter().any(|&(begin, end)| begin <= address && address < end)
+}
+
+fn collect_unit(unit:&CompilationUnit, sections:&DwarfSections, programs:&[LineProgram], names:&mut HashMap<u64, DieName>) -> UnitScopes {
+    let mut scopes = Vec::new();
+    let Some(root) = unit.root.as_ref() else {
+        return UnitScopes { comp_dir:None, ranges:Vec::new(), program:None, scopes };
+    };
+    for die in root.walk() {
+        let name = die.attr_str(DW_AT_NAME)
+            .or_else(|| die.attr_str(DW_AT_LINKAGE_NAME))
+            .or_else(|| die.attr_str(DW_AT_MIPS_LINKAGE_NAME));
+        let origin = die.attr_u64(DW_AT_ABSTRACT_ORIGIN).or_else(|| die.attr_u64(DW_AT_SPECIFICATION));
+        if name.is_some() || origin.is_some() {
+            names.insert(die.offset, DieName { name:name.map(|name| name.to_string()), origin });
+        }
+        if die.tag != DW_TAG_SUBPROGRAM && die.tag != DW_TAG_INLINED_SUBROUTINE {
+            continue;
+        }
+        let ranges = die_ranges(unit, die, sections).unwrap_or_default();
+        if ranges.is_empty() {
+            continue;
+        }
+        scopes.push(Scope {
+            ranges,
+            depth:die.depth,
+            die:die.offset,
+            inlined:die.tag == DW_TAG_INLINED_SUBROUTINE,
+            call_file:die.attr_u64(DW_AT_CALL_FILE).unwrap_or(0),
+            call_line:die.attr_u64(DW_AT_CALL_LINE).unwrap_or(0),
+            call_column:die.attr_u64(DW_AT_CALL_COLUMN).unwrap_or(0),
+        });
+    }
+    let mut ranges = die_ranges(unit, root, sections).unwrap_or_default();
+    if ranges.is_empty() {
+        ranges = scopes.iter().flat_map(|scope| scope.ranges.iter().copied()).collect();
+    }
+    let program = root.attr_u64(DW_AT_STMT_LIST)
+        .and_then(|offset| programs.iter().position(|program| program.header.offset == offset));
+    UnitScopes { comp_dir:unit.comp_dir().map(|dir| dir.to_string()), ranges, program, scopes }
+}
+
+impl Symbolizer {
+    pub fn load<R:Read + Seek>(elf:&mut R) -> io::Result<Self> {
+        let mut functions:Vec<ElfSymbol> = parse_symbols(elf)?;
+        functions.extend(parse_dynamic_symbols(elf)?);
+        functions.retain(|symbol| symbol.is_function() && !symbol.is_undefined() && symbol.st_value != 0);
+        functions.sort_by_key(|symbol| symbol.st_value);
+        let load_base = parse_segments(elf)?.iter()
+            .filter(|segment| matches!(segment.p_type, SegmentType::LOAD))
+            .map(|segment| segment.p_vaddr - segment.p_vaddr % segment.p_align.max(1))
+            .min()
+            .unwrap_or(0);
+        let section_ranges = parse_sections(elf)?.iter()
+            .map(|section| (section.sh_addr, section.sh_addr.wrapping_add(section.sh_size)))
+            .collect();
+        let sections = DwarfSections::load(elf)?;
+        let programs = parse_line_programs(&sections.debug_line, &sections.debug_line_str, &sections.debug_str)?;
+        let mut names = HashMap::new();
+        let units = parse_units(&sections)?.iter()
+            .map(|unit| collect_unit(unit, &sections, &programs, &mut names))
+            .collect();
+        Ok(Self { functions, programs, units, names, section_ranges, load_base })
+    }
+
+    /// 第一个PT_LOAD段按页对齐后的虚拟地址, 模块内偏移相对于它计算
+    pub fn load_base(&self) -> u64 {
+        self.load_base
+    }
+
+    /// 函数符号的地址, 用于解析`symbol+0x..`
+    pub fn symbol_address(&self, name:&str) -> Option<u64> {
+        self.functions.iter().find(|symbol| symbol.name == name).map(|symbol| symbol.st_value)
+    }
+
+    /// 包含address的函数符号
+    pub fn find_symbol(&self, address:u64) -> Option<&ElfSymbol> {
+        let end = self.functions.partition_point(|symbol| symbol.st_value <= address);
+        let candidates = &self.functions[..end];
+        // 大小为0的符号(如汇编函数)只在与地址同一个节时才作为最近的符号
+        candidates.iter().rev().find(|symbol| symbol.contains(address))
+            .or_else(|| candidates.last().filter(|symbol| {
+                symbol.st_size == 0 && self.section_ranges.get(symbol.st_shndx as usize)
+                    .is_some_and(|&(begin, end)| begin <= address && address < end)
+            }))
+    }
+
+    fn function_name(&self, mut offset:u64) -> Option<String> {
+        // 沿abstract_origin/specification追溯, 限制次数以防引用成环
+        for _ in 0..16 {
+            let entry = self.names.get(&offset)?;
+            if let Some(name) = &entry.name {
+                return Some(name.clone());
+            }
+            offset = entry.origin?;
+        }
+        None
+    }
+
+    /// 符号化一个虚拟地址
+    pub fn symbolize(&self, address:u64) -> Symbolized {
+        let symbol = self.find_symbol(address).map(|symbol| (symbol.name.clone(), address - symbol.st_value));
+        let unit = self.units.iter().find(|unit| in_ranges(&unit.ranges, address));
+        let program = match unit.and_then(|unit| unit.program) {
+            Some(index) => self.programs.get(index),
+            None => self.programs.iter().find(|program| program.find_row(address).is_some()),
+        };
+        let row = program.and_then(|program| program.find_row(address));
+        // DWARF 5之前行号表中的相对路径是相对于编译目录的
+        let file_path = |file:u64| -> Option<String> {
+            let path = program?.header.file_path(file)?;
+            match unit.and_then(|unit| unit.comp_dir.as_ref()) {
+                Some(dir) if !path.starts_with('/') => Some(format!("{}/{}", dir.trim_end_matches('/'), path)),
+                _ => Some(path),
+            }
+        };
+        let mut file = row.and_then(|row| file_path(row.file));
+        let mut line = row.map(|row| row.line).unwrap_or(0);
+        let mut column = row.map(|row| row.column).unwrap_or(0);
+        let mut scopes:Vec<&Scope> = unit.map(|unit| {
+            unit.scopes.iter().filter(|scope| in_ranges(&scope.ranges, address)).collect()
+        }).unwrap_or_default();
+        scopes.sort_by_key(|scope| std::cmp::Reverse(scope.depth));
+        let mut frames = Vec::new();
+        for scope in scopes {
+            frames.push(Frame { function:self.function_name(scope.die), file:file.clone(), line, column });
+            if !scope.inlined {
+                break;
+            }
+            // 外层一帧的位置是内联函数的调用点
+            file = file_path(scope.call_file);
+            line = scope.call_line;
+            column = scope.call_column;
+        }
+        if frames.is_empty() {
+            frames.push(Frame { function:symbol.as_ref().map(|(name, _)| name.clone()), file, line, column });
+        }
+        Symbolized { address, symbol, frames }
+    }
+
+    /// 符号化一个相对于模块加载基址的偏移, 用于PIE和共享库
+    pub fn symbolize_relative(&self, offset:u64) -> Symbolized {
+        self.symbolize(self.load_base.wrapping_add(offset))
+    }
+}
+
+impl std::fmt::Display for Frame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f,"{} at {}:",self.function.as_deref().unwrap_or("??"),self.file.as_deref().unwrap_or("??"))?;
+        if self.column != 0 {
+            write!(f,"{}:{}",self.line,self.column)
+        } else {
+            write!(f,"{}",self.line)
+        }
+    }
+}
+
+impl std::fmt::Display for Symbolized {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.symbol {
+            Some((name, 0)) => writeln!(f,"{:#x}: {}",self.address,name)?,
+            Some((name, offset)) => writeln!(f,"{:#x}: {}+{:#x}",self.address,name,offset)?,
+            None => writeln!(f,"{:#x}: ??",self.address)?,
+        }
+        for (i, frame) in self.frames.iter().enumerate() {
+            if i == 0 {
+                writeln!(f,"    {}",frame)?;
+            } else {
+                writeln!(f,"    (inlined by) {}",frame)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 从文本中解析出的地址
+#[derive(Debug, PartialEq)]
+pub enum AddressRef {
+    /// 虚拟地址
+    ABSOLUTE(u64),
+    /// 相对于模块加载基址的偏移, 如`libfoo.so+0x1234`或`./prog(+0x1d)`
+    RELATIVE(u64),
+    /// 相对于符号的偏移, 如backtrace_symbols输出的`./prog(main+0x1d)`
+    SYMBOL(String, u64),
+}
+
+/// 被符号化的文件在日志中可能出现的名字: 命令行给出的路径, 文件名和SONAME
+pub struct ModuleNames {
+    path:String,
+    names:Vec<String>,
+}
+
+impl ModuleNames {
+    pub fn new(path:&str, soname:Option<&str>) -> Self {
+        let mut names:Vec<String> = Path::new(path).file_name().map(|name| name.to_string_lossy().into_owned()).into_iter().collect();
+        names.extend(soname.map(String::from));
+        Self { path:path.to_string(), names }
+    }
+
+    /// module是否是这个文件: 与路径相同, 或者去掉目录后与文件名或SONAME相同
+    pub fn matches(&self, module:&str) -> bool {
+        let name = Path::new(module).file_name().map(|name| name.to_string_lossy());
+        module == self.path || name.is_some_and(|name| self.names.iter().any(|candidate| *candidate == name))
+    }
+}
+
+/// `+`之前的部分是否像另一个模块(路径或共享库)而不是符号名
+fn is_other_module(prefix:&str) -> bool {
+    prefix.contains('/') || prefix.contains(".so")
+}
+
+fn parse_hex(text:&str) -> Option<u64> {
+    let digits = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")).unwrap_or(text);
+    u64::from_str_radix(digits, 16).ok()
+}
+
+/// 解析backtrace_symbols格式的一帧: `module(symbol+0x..) [0x..]`, `module(+0x..) [0x..]`或`module() [0x..]`;
+/// 不是这种格式时返回None, 是其他模块的帧时返回Some(None)
+fn parse_backtrace_frame(line:&str, module:&ModuleNames) -> Option<Option<AddressRef>> {
+    let open = line.find('(')?;
+    let close = open + line[open..].find(')')?;
+    let name = line[..open].rsplit(char::is_whitespace).next()?;
+    if name.is_empty() {
+        return None;
+    }
+    let location = &line[open + 1..close];
+    let runtime = line[close + 1..].trim_start().strip_prefix("[0x")?.split(']').next()?;
+    if !module.matches(name) {
+        return Some(None);
+    }
+    Some(match location.rsplit_once('+') {
+        Some(("", offset)) => parse_hex(offset).map(AddressRef::RELATIVE),
+        Some((symbol, offset)) => parse_hex(offset).map(|offset| AddressRef::SYMBOL(symbol.to_string(), offset)),
+        // 没有符号和偏移时(如非PIE程序)运行时地址就是链接时的地址
+        None => parse_hex(runtime).map(AddressRef::ABSOLUTE),
+    })
+}
+
+/// 从崩溃日志等文本中提取地址, 每行(一帧)最多一个
+///
+/// backtrace_symbols格式的帧只在模块是被符号化的文件时使用, 优先使用括号中的符号或模块内偏移,
+/// 方括号中的运行时地址只在没有偏移时使用; 其他行依次尝试`module+0x..`, `symbol+0x..`和`0x..`,
+/// 其他模块的偏移和方括号中的运行时地址被忽略, 不带0x前缀的单词不作为地址
+pub fn extract_addresses(text:&str, module:&ModuleNames) -> Vec<AddressRef> {
+    let mut addresses = Vec::new();
+    for line in text.lines() {
+        if let Some(frame) = parse_backtrace_frame(line, module) {
+            addresses.extend(frame);
+            continue;
+        }
+        // gdb的`#1 0x.. in f () from /lib/libc.so.6`
+        if line.rsplit_once(" from ").is_some_and(|(_, library)| !module.matches(library.trim())) {
+            continue;
+        }
+        let (mut absolute, mut offset) = (None, None);
+        let (mut depth, mut start) = (0i32, 0);
+        for (pos, c) in line.char_indices().chain([(line.len(), ' ')]) {
+            if !c.is_whitespace() && !"[](),;<>{}".contains(c) {
+                continue;
+            }
+            let token = &line[start..pos];
+            if token.starts_with("0x") && depth <= 0 {
+                absolute = absolute.or_else(|| parse_hex(token).map(AddressRef::ABSOLUTE));
+            } else if token.contains("+0x") {
+                offset = offset.or_else(|| parse_address(token, module));
+            }
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                _ => {}
+            }
+            start = pos + c.len_utf8();
+        }
+        addresses.extend(offset.or(absolute));
+    }
+    addresses
+}
+
+/// 解析单个地址参数, 支持`0x1234`, `1234`(十六进制), `module+0x1234`和`symbol+0x1234`;
+/// 其他模块(路径或共享库)的偏移返回None
+pub fn parse_address(token:&str, module:&ModuleNames) -> Option<AddressRef> {
+    match token.rsplit_once('+') {
+        None => parse_hex(token).map(AddressRef::ABSOLUTE),
+        Some((prefix, value)) => {
+            let value = parse_hex(value)?;
+            if prefix.is_empty() || module.matches(prefix) {
+                Some(AddressRef::RELATIVE(value))
+            } else if is_other_module(prefix) {
+                None
+            } else {
+                Some(AddressRef::SYMBOL(prefix.to_string(), value))
+            }
+        }
+    }
+}
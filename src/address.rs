@@ -0,0 +1,115 @@
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::elf::header::{parse_header, HeaderType};
+use crate::elf::section::{parse_sections, section_at_offset, section_at_vaddr, ElfSection};
+use crate::elf::segment::{load_segment_at_offset, load_segment_at_vaddr, parse_segments, ElfSegment};
+use crate::elf::symbol::{parse_dynamic_symbols, parse_symbols, ElfSymbol, STT_FILE, STT_SECTION, STT_TLS};
+
+/// 一个虚拟地址或文件偏移所在的段, 节和符号, 以及换算得到的另一个坐标
+pub struct Location {
+    /// 运行时的虚拟地址, 偏移不在任何PT_LOAD段中(如.symtab)时为None
+    pub vaddr:Option<u64>,
+    /// 文件偏移, 地址位于段的p_filesz之后(如.bss)时为None
+    pub offset:Option<u64>,
+    /// 所在PT_LOAD段在段头表中的索引
+    pub segment:Option<usize>,
+    /// 所在节的索引, 名字及在节内的偏移
+    pub section:Option<(u16, String, u64)>,
+    /// 包含该位置的符号及相对于符号的偏移
+    pub symbol:Option<(String, u64)>,
+    /// 地址在PT_LOAD段中, 但在文件中没有内容(p_filesz < p_memsz的部分)
+    pub bss:bool,
+}
+
+/// 在虚拟地址, 文件偏移, 段, 节和符号之间换算
+pub struct AddressMap {
+    relocatable:bool,
+    segments:Vec<ElfSegment>,
+    sections:Vec<ElfSection>,
+    /// 有地址的已定义符号, 按st_value排序
+    symbols:Vec<ElfSymbol>,
+}
+
+impl AddressMap {
+    pub fn load<R:Read + Seek>(elf:&mut R) -> io::Result<Self> {
+        let header = parse_header(elf)?;
+        let mut symbols = parse_symbols(elf)?;
+        symbols.extend(parse_dynamic_symbols(elf)?);
+        // TLS符号的值是TLS块内的偏移, 不是地址
+        symbols.retain(|symbol| !symbol.is_undefined() && symbol.index != 0 && !matches!(symbol.symbol_type(), STT_SECTION | STT_FILE | STT_TLS));
+        symbols.sort_by_key(|symbol| symbol.st_value);
+        Ok(Self {
+            relocatable:matches!(header.e_type, HeaderType::REL),
+            segments:parse_segments(elf)?,
+            sections:parse_sections(elf)?,
+            symbols,
+        })
+    }
+
+    /// 包含value的符号, 大小为0的符号只匹配起始地址; 可重定位文件的符号值是节内偏移, 只在同一个节中查找
+    fn find_symbol(&self, value:u64, section:Option<&ElfSection>) -> Option<(String, u64)> {
+        let end = self.symbols.partition_point(|symbol| symbol.st_value <= value);
+        self.symbols[..end].iter().rev()
+            .filter(|symbol| !self.relocatable || section.is_some_and(|section| section.index == symbol.st_shndx))
+            .find(|symbol| symbol.contains(value))
+            .map(|symbol| (symbol.name.clone(), value - symbol.st_value))
+    }
+
+    /// section为所在的节及在节内的偏移
+    fn locate(&self, vaddr:Option<u64>, offset:Option<u64>, segment:Option<usize>, section:Option<(&ElfSection, u64)>, bss:bool) -> Location {
+        let symbol = match (self.relocatable, vaddr, section) {
+            (false, Some(vaddr), _) => self.find_symbol(vaddr, None),
+            (true, _, Some((section, within))) => self.find_symbol(within, Some(section)),
+            _ => None,
+        };
+        let section = section.map(|(section, within)| (section.index, section.name.clone(), within));
+        Location { vaddr, offset, segment, section, symbol, bss }
+    }
+
+    /// 虚拟地址所在的位置, 地址在.bss等只在内存中的部分时没有文件偏移
+    pub fn locate_vaddr(&self, vaddr:u64) -> Location {
+        let load = load_segment_at_vaddr(&self.segments, vaddr);
+        let offset = load
+            .filter(|(_, segment)| vaddr - segment.p_vaddr < segment.p_filesz)
+            .and_then(|(_, segment)| segment.p_offset.checked_add(vaddr - segment.p_vaddr));
+        let bss = load.is_some() && offset.is_none();
+        let section = match self.relocatable {
+            true => None,
+            false => section_at_vaddr(&self.sections, vaddr).map(|section| (section, vaddr - section.sh_addr)),
+        };
+        self.locate(Some(vaddr), offset, load.map(|(index, _)| index), section, bss)
+    }
+
+    /// 文件偏移所在的位置, 偏移不在PT_LOAD段中(如.symtab或可重定位文件)时没有虚拟地址
+    pub fn locate_offset(&self, offset:u64) -> Location {
+        let load = load_segment_at_offset(&self.segments, offset);
+        let vaddr = load.and_then(|(_, segment)| segment.p_vaddr.checked_add(offset - segment.p_offset));
+        let section = section_at_offset(&self.sections, offset).map(|section| (section, offset - section.sh_offset));
+        self.locate(vaddr, Some(offset), load.map(|(index, _)| index), section, false)
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.vaddr {
+            Some(vaddr) => writeln!(f,"    vaddr    {:#x}",vaddr)?,
+            None => writeln!(f,"    vaddr    none (not in a LOAD segment)")?,
+        }
+        match self.offset {
+            Some(offset) => writeln!(f,"    offset   {:#x}",offset)?,
+            None if self.bss => writeln!(f,"    offset   none (past p_filesz, zero-filled at run time)")?,
+            None => writeln!(f,"    offset   none (not in a LOAD segment)")?,
+        }
+        if let Some(segment) = self.segment {
+            writeln!(f,"    segment  [{}] LOAD",segment)?;
+        }
+        if let Some((index, name, within)) = &self.section {
+            writeln!(f,"    section  [{}] {}+{:#x}",index,name,within)?;
+        }
+        if let Some((name, within)) = &self.symbol {
+            writeln!(f,"    symbol   {}+{:#x}",name,within)?;
+        }
+        Ok(())
+    }
+}
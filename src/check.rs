@@ -0,0 +1,247 @@
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::elf::bounds::{file_len, overlaps, EHDR_SIZE};
+use crate::elf::dynamic::SHT_DYNAMIC;
+use crate::elf::hash::{parse_hash_tables, SHT_GNU_HASH, SHT_HASH};
+use crate::elf::header::{parse_header, ElfHeader};
+use crate::elf::section::{parse_sections, ElfSection, SHDR_SIZE, SHF_ALLOC, SHT_GROUP, SHT_NOBITS, SHT_REL, SHT_RELA, SHT_STRTAB};
+use crate::elf::segment::{parse_segments, ElfSegment, SegmentType, PHDR_SIZE};
+use crate::elf::symbol::{SHT_DYNSYM, SHT_SYMTAB};
+use crate::elf::version::{SHT_GNU_VERDEF, SHT_GNU_VERNEED, SHT_GNU_VERSYM};
+use crate::json::impl_to_json;
+
+const SHF_INFO_LINK:u64 = 0x40;
+const SHF_LINK_ORDER:u64 = 0x80;
+/// e_shstrndx为SHN_UNDEF时文件没有节名字符串表, 为SHN_XINDEX时真正的索引保存在0号节头的sh_link中
+const SHN_UNDEF:u16 = 0;
+const SHN_XINDEX:u16 = 0xffff;
+const SYMBOL_SIZE:u64 = 24;
+
+/// 一个结构问题, kind是稳定的机器可读标识, 如`section-overlap`
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Problem {
+    pub kind:&'static str,
+    pub detail:String,
+}
+
+impl_to_json!(Problem { kind, detail });
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CheckReport {
+    pub problems:Vec<Problem>,
+}
+
+impl_to_json!(CheckReport { problems });
+
+impl CheckReport {
+    /// 没有发现任何问题
+    pub fn passed(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    fn push(&mut self, kind:&'static str, detail:impl Into<String>) {
+        self.problems.push(Problem { kind, detail:detail.into() });
+    }
+}
+
+impl std::fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.problems.is_empty() {
+            return writeln!(f,"No problems found");
+        }
+        for problem in &self.problems {
+            writeln!(f,"{:<16} {}",problem.kind,problem.detail)?;
+        }
+        writeln!(f,"{} problems found",self.problems.len())
+    }
+}
+
+fn section_label(section:&ElfSection) -> String {
+    format!("section [{}] {}",section.index,section.name)
+}
+
+fn segment_label(index:usize, segment:&ElfSegment) -> String {
+    format!("program header [{}] {}",index,segment.p_type.to_string())
+}
+
+/// 对齐值必须是0, 1或2的幂
+fn valid_align(align:u64) -> bool {
+    align <= 1 || align.is_power_of_two()
+}
+
+fn check_header(header:&ElfHeader, report:&mut CheckReport) {
+    if header.e_ehsize as u64 != EHDR_SIZE {
+        report.push("ehsize", format!("e_ehsize is {}, expected {}",header.e_ehsize,EHDR_SIZE));
+    }
+    if header.e_phnum > 0 && header.e_phentsize as u64 != PHDR_SIZE {
+        report.push("phentsize", format!("e_phentsize is {}, expected {}",header.e_phentsize,PHDR_SIZE));
+    }
+    if header.e_shnum > 0 && header.e_shentsize as u64 != SHDR_SIZE {
+        report.push("shentsize", format!("e_shentsize is {}, expected {}",header.e_shentsize,SHDR_SIZE));
+    }
+}
+
+/// sh_link引用的节必须存在, expected不为空时还必须是其中的类型之一
+fn check_link(sections:&[ElfSection], section:&ElfSection, expected:&[u32], report:&mut CheckReport) {
+    match sections.get(section.sh_link as usize) {
+        Some(linked) if section.sh_link != 0 && !expected.is_empty() && !expected.contains(&linked.sh_type) => {
+            report.push("section-link", format!("{} links to {} of type {:#x}",section_label(section),section_label(linked),linked.sh_type));
+        }
+        Some(_) => {}
+        None => report.push("section-link", format!("{} links to section {}, which does not exist",section_label(section),section.sh_link)),
+    }
+}
+
+fn check_sections(header:&ElfHeader, sections:&[ElfSection], len:u64, report:&mut CheckReport) {
+    if header.e_shstrndx != SHN_UNDEF && header.e_shstrndx != SHN_XINDEX {
+        match sections.get(header.e_shstrndx as usize) {
+            Some(strtab) if strtab.sh_type != SHT_STRTAB => {
+                report.push("section-link", format!("e_shstrndx refers to {}, which is not a string table",section_label(strtab)));
+            }
+            Some(_) => {}
+            None => report.push("section-link", format!("e_shstrndx {} does not refer to a section",header.e_shstrndx)),
+        }
+    }
+    let names = sections.get(header.e_shstrndx as usize).filter(|_| header.e_shstrndx != SHN_UNDEF);
+    for section in sections.iter().skip(1) {
+        let label = section_label(section);
+        if names.is_some_and(|names| section.sh_name as u64 >= names.sh_size) {
+            report.push("section-name", format!("{} has sh_name {:#x} outside the section name string table",label,section.sh_name));
+        }
+        if section.sh_type != SHT_NOBITS && section.sh_offset.checked_add(section.sh_size).is_none_or(|end| end > len) {
+            report.push("section-eof", format!("{} at {:#x} with size {:#x} extends past the end of the file ({:#x} bytes)",label,section.sh_offset,section.sh_size,len));
+        }
+        if !valid_align(section.sh_addralign) {
+            report.push("section-align", format!("{} has sh_addralign {:#x}, which is not a power of two",label,section.sh_addralign));
+        } else if section.sh_flags & SHF_ALLOC != 0 && section.sh_addralign > 1 && section.sh_addr % section.sh_addralign != 0 {
+            report.push("section-align", format!("{} at address {:#x} is not aligned to {:#x}",label,section.sh_addr,section.sh_addralign));
+        }
+        match section.sh_type {
+            SHT_SYMTAB | SHT_DYNSYM => {
+                check_link(sections, section, &[SHT_STRTAB], report);
+                // sh_info是最后一个局部符号的索引加一
+                let count = section.sh_size / SYMBOL_SIZE;
+                if section.sh_info as u64 > count {
+                    report.push("section-info", format!("{} has sh_info {}, but only {} symbols",label,section.sh_info,count));
+                }
+            }
+            SHT_DYNAMIC | SHT_GNU_VERDEF | SHT_GNU_VERNEED => check_link(sections, section, &[SHT_STRTAB], report),
+            SHT_HASH | SHT_GNU_HASH | SHT_GNU_VERSYM => check_link(sections, section, &[SHT_DYNSYM, SHT_SYMTAB], report),
+            SHT_GROUP => check_link(sections, section, &[SHT_SYMTAB], report),
+            SHT_REL | SHT_RELA => {
+                check_link(sections, section, &[SHT_DYNSYM, SHT_SYMTAB], report);
+                if section.sh_info != 0 && section.sh_info as usize >= sections.len() {
+                    report.push("section-info", format!("{} applies to section {}, which does not exist",label,section.sh_info));
+                }
+            }
+            _ => {
+                if section.sh_flags & SHF_LINK_ORDER != 0 {
+                    check_link(sections, section, &[], report);
+                }
+                if section.sh_flags & SHF_INFO_LINK != 0 && section.sh_info as usize >= sections.len() {
+                    report.push("section-info", format!("{} refers to section {}, which does not exist",label,section.sh_info));
+                }
+            }
+        }
+    }
+    // 按偏移排序后, 与之前结束得最晚的节比较就能找出所有重叠
+    let mut placed:Vec<&ElfSection> = sections.iter()
+        .skip(1)
+        .filter(|section| section.sh_type != SHT_NOBITS && section.sh_size > 0)
+        .collect();
+    placed.sort_by_key(|section| (section.sh_offset, section.index));
+    let table = header.e_shoff..header.e_shoff.saturating_add(header.e_shnum as u64 * header.e_shentsize as u64);
+    let mut last:Option<&ElfSection> = None;
+    for section in placed {
+        let end = section.sh_offset.saturating_add(section.sh_size);
+        if section.sh_offset < EHDR_SIZE {
+            report.push("section-overlap", format!("{} at {:#x} overlaps the ELF header",section_label(section),section.sh_offset));
+        }
+        if overlaps(&(section.sh_offset..end), &table) {
+            report.push("section-overlap", format!("{} at {:#x} overlaps the section header table at {:#x}",section_label(section),section.sh_offset,header.e_shoff));
+        }
+        match last {
+            Some(previous) if section.sh_offset < previous.sh_offset.saturating_add(previous.sh_size) => {
+                report.push("section-overlap", format!("{} at {:#x} overlaps {} at {:#x} with size {:#x}",section_label(section),section.sh_offset,section_label(previous),previous.sh_offset,previous.sh_size));
+                if end > previous.sh_offset.saturating_add(previous.sh_size) {
+                    last = Some(section);
+                }
+            }
+            _ => last = Some(section),
+        }
+    }
+}
+
+fn check_segments(header:&ElfHeader, segments:&[ElfSegment], len:u64, report:&mut CheckReport) {
+    let is_load = |segment:&&ElfSegment| matches!(segment.p_type, SegmentType::LOAD);
+    for (index, segment) in segments.iter().enumerate() {
+        let label = segment_label(index, segment);
+        if segment.p_offset.checked_add(segment.p_filesz).is_none_or(|end| end > len) {
+            report.push("segment-eof", format!("{} at {:#x} with size {:#x} extends past the end of the file ({:#x} bytes)",label,segment.p_offset,segment.p_filesz,len));
+        }
+        if !valid_align(segment.p_align) {
+            report.push("segment-align", format!("{} has p_align {:#x}, which is not a power of two",label,segment.p_align));
+        } else if is_load(&segment) && segment.p_align > 1 && segment.p_vaddr % segment.p_align != segment.p_offset % segment.p_align {
+            report.push("load-align", format!("{} has p_vaddr {:#x} and p_offset {:#x}, which are not congruent modulo p_align {:#x}",label,segment.p_vaddr,segment.p_offset,segment.p_align));
+        }
+        if is_load(&segment) && segment.p_filesz > segment.p_memsz {
+            report.push("segment-size", format!("{} has p_filesz {:#x} larger than p_memsz {:#x}",label,segment.p_filesz,segment.p_memsz));
+        }
+        if segment.p_vaddr.checked_add(segment.p_memsz).is_none() {
+            report.push("segment-size", format!("{} at {:#x} with p_memsz {:#x} wraps around the address space",label,segment.p_vaddr,segment.p_memsz));
+        }
+    }
+    let loads:Vec<(usize, &ElfSegment)> = segments.iter().enumerate().filter(|(_, segment)| is_load(segment)).collect();
+    for pair in loads.windows(2) {
+        let ((_, previous), (index, segment)) = (pair[0], pair[1]);
+        if segment.p_vaddr < previous.p_vaddr {
+            report.push("load-order", format!("{} at {:#x} follows a LOAD segment at {:#x}, PT_LOAD entries must be sorted by p_vaddr",segment_label(index, segment),segment.p_vaddr,previous.p_vaddr));
+        } else if segment.p_vaddr < previous.p_vaddr.saturating_add(previous.p_memsz) {
+            report.push("load-overlap", format!("{} at {:#x} overlaps the previous LOAD segment at {:#x} with p_memsz {:#x}",segment_label(index, segment),segment.p_vaddr,previous.p_vaddr,previous.p_memsz));
+        }
+    }
+    for (index, phdr) in segments.iter().enumerate().filter(|(_, segment)| matches!(segment.p_type, SegmentType::PHDR)) {
+        let label = segment_label(index, phdr);
+        let table_size = header.e_phnum as u64 * header.e_phentsize as u64;
+        if phdr.p_offset != header.e_phoff || phdr.p_filesz != table_size {
+            report.push("phdr", format!("{} at {:#x} with size {:#x} does not describe the program header table at {:#x} with size {:#x}",label,phdr.p_offset,phdr.p_filesz,header.e_phoff,table_size));
+        }
+        if loads.first().is_some_and(|(first, _)| *first < index) {
+            report.push("phdr", format!("{} must precede every LOAD segment",label));
+        }
+        let covered = loads.iter().any(|(_, load)| {
+            let within = |start:u64, size:u64, base:u64, limit:u64| start >= base && start - base <= limit && size <= limit - (start - base);
+            within(phdr.p_offset, phdr.p_filesz, load.p_offset, load.p_filesz) && within(phdr.p_vaddr, phdr.p_memsz, load.p_vaddr, load.p_memsz)
+        });
+        if !covered {
+            report.push("phdr", format!("{} at {:#x} is not covered by a LOAD segment",label,phdr.p_vaddr));
+        }
+    }
+}
+
+/// 检查文件结构: 文件头中的表项大小, 超出文件或相互重叠的节, 节的对齐和sh_link/sh_info引用,
+/// PT_LOAD段的对齐和顺序, PT_PHDR是否被LOAD段覆盖, .hash/.gnu.hash能否找到每个动态符号
+///
+/// 节头表或段头表本身无法解析时作为一个问题报告, 只有文件头无法读取时返回错误
+pub fn check_file<R:Read + Seek>(elf:&mut R) -> io::Result<CheckReport> {
+    let header = parse_header(elf)?;
+    let len = file_len(elf)?;
+    let mut report = CheckReport { problems:Vec::new() };
+    check_header(&header, &mut report);
+    match parse_sections(elf) {
+        Ok(sections) => {
+            check_sections(&header, &sections, len, &mut report);
+            match parse_hash_tables(elf) {
+                Ok(tables) => tables.problems().into_iter().for_each(|problem| report.push("hash", problem)),
+                Err(e) => report.push("hash", e.to_string()),
+            }
+        }
+        Err(e) => report.push("section-table", e.to_string()),
+    }
+    match parse_segments(elf) {
+        Ok(segments) => check_segments(&header, &segments, len, &mut report),
+        Err(e) => report.push("segment-table", e.to_string()),
+    }
+    Ok(report)
+}
@@ -0,0 +1,435 @@
+use std::collections::HashSet;
+use std::fmt::Formatter;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Seek};
+use std::path::{Component, Path, PathBuf};
+use crate::elf::dynamic::*;
+use crate::elf::header::{parse_header, MachineType};
+use crate::elf::segment::{parse_segments, read_segment_data, SegmentType};
+
+const ELFCLASS32:u8 = 1;
+const ELFCLASS64:u8 = 2;
+
+/// 查找共享库时使用的配置, 所有绝对路径都相对于sysroot
+pub struct SearchConfig {
+    pub sysroot:Option<PathBuf>,
+    /// LD_LIBRARY_PATH中的目录
+    pub library_path:Vec<String>,
+    /// /etc/ld.so.conf及其include的文件中的目录
+    pub config_dirs:Vec<String>,
+}
+
+/// 依赖的查找结果
+#[allow(non_camel_case_types)]
+pub enum Resolution {
+    FOUND(PathBuf),
+    NOT_FOUND,
+    /// 只找到了ELF类别或机器类型不匹配的文件
+    MISMATCH(PathBuf, String),
+}
+
+pub struct Dependency {
+    pub name:String,
+    pub resolution:Resolution,
+    /// 找到该库的搜索路径来源, 如RUNPATH, ld.so.conf
+    pub source:&'static str,
+    /// 已经在树中出现过, 不再展开
+    pub repeated:bool,
+    pub children:Vec<Dependency>,
+}
+
+pub struct DependencyTree {
+    pub path:PathBuf,
+    pub interpreter:Option<(String, Resolution)>,
+    pub needed:Vec<Dependency>,
+}
+
+/// 根文件的ELF类别和机器类型, 依赖必须与之相同
+struct Target {
+    class:u8,
+    machine:MachineType,
+}
+
+/// 一个已加载对象中与查找有关的信息
+struct ObjectInfo {
+    needed:Vec<String>,
+    rpath:Vec<String>,
+    runpath:Vec<String>,
+    origin:PathBuf,
+}
+
+fn machine_platform(machine:&MachineType, class:u8) -> &'static str {
+    match machine {
+        MachineType::AMDX86_64 => "x86_64",
+        MachineType::Intel386 => "i686",
+        MachineType::AArch64 => "aarch64",
+        MachineType::RISCV if class == ELFCLASS32 => "riscv32",
+        MachineType::RISCV => "riscv64",
+        _ => "",
+    }
+}
+
+/// 简单的通配符匹配, 支持`*`和`?`
+fn wildcard_match(pattern:&[u8], name:&[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => wildcard_match(&pattern[1..], name) || (!name.is_empty() && wildcard_match(pattern, &name[1..])),
+        (Some(b'?'), Some(_)) => wildcard_match(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => wildcard_match(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+impl SearchConfig {
+    /// 读取sysroot下的/etc/ld.so.conf, library_path为LD_LIBRARY_PATH的值
+    pub fn new(sysroot:Option<PathBuf>, library_path:Option<&str>) -> Self {
+        let mut config = Self {
+            sysroot,
+            library_path:library_path.unwrap_or("").split([':', ';']).filter(|dir| !dir.is_empty()).map(String::from).collect(),
+            config_dirs:Vec::new(),
+        };
+        let mut visited = HashSet::new();
+        let mut dirs = Vec::new();
+        config.read_ld_so_conf(Path::new("/etc/ld.so.conf"), &mut visited, &mut dirs);
+        config.config_dirs = dirs;
+        config
+    }
+
+    /// 把绝对路径放到sysroot下
+    pub fn host_path(&self, path:&Path) -> PathBuf {
+        match &self.sysroot {
+            Some(sysroot) if path.is_absolute() && !path.starts_with(sysroot) => {
+                sysroot.join(path.strip_prefix("/").unwrap_or(path))
+            }
+            _ => path.to_path_buf(),
+        }
+    }
+
+    fn read_ld_so_conf(&self, path:&Path, visited:&mut HashSet<PathBuf>, dirs:&mut Vec<String>) {
+        // include可能成环
+        if !visited.insert(path.to_path_buf()) {
+            return;
+        }
+        let Ok(text) = std::fs::read_to_string(self.host_path(path)) else { return };
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() || line.starts_with("hwcap ") {
+                continue;
+            }
+            if let Some(pattern) = line.strip_prefix("include").filter(|rest| rest.starts_with([' ', '\t'])) {
+                for pattern in pattern.split_whitespace() {
+                    let pattern = match Path::new(pattern).is_absolute() {
+                        true => PathBuf::from(pattern),
+                        false => path.parent().unwrap_or(Path::new("/")).join(pattern),
+                    };
+                    for include in self.expand_glob(&pattern) {
+                        self.read_ld_so_conf(&include, visited, dirs);
+                    }
+                }
+                continue;
+            }
+            // 旧格式中目录后面可以跟`=类型`
+            for dir in line.split([' ', '\t', ',', ':']).filter(|dir| !dir.is_empty()) {
+                let dir = dir.split('=').next().unwrap_or(dir).to_string();
+                if !dirs.contains(&dir) {
+                    dirs.push(dir);
+                }
+            }
+        }
+    }
+
+    /// 展开文件名部分带通配符的路径, 结果按文件名排序
+    fn expand_glob(&self, pattern:&Path) -> Vec<PathBuf> {
+        let Some(name) = pattern.file_name().and_then(|name| name.to_str()) else { return Vec::new() };
+        if !name.contains(['*', '?']) {
+            return vec![pattern.to_path_buf()];
+        }
+        let dir = pattern.parent().unwrap_or(Path::new("/"));
+        let Ok(entries) = std::fs::read_dir(self.host_path(dir)) else { return Vec::new() };
+        let mut names:Vec<String> = entries.filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|entry| wildcard_match(name.as_bytes(), entry.as_bytes()))
+            .collect();
+        names.sort();
+        names.into_iter().map(|entry| dir.join(entry)).collect()
+    }
+
+    /// 编译进动态链接器的默认目录
+    fn default_dirs(&self, target:&Target) -> Vec<String> {
+        let dirs:&[&str] = if target.class == ELFCLASS64 { &["/lib64", "/usr/lib64", "/lib", "/usr/lib"] } else { &["/lib", "/usr/lib"] };
+        dirs.iter().map(|dir| dir.to_string()).collect()
+    }
+}
+
+/// 展开RPATH/RUNPATH中的$ORIGIN, $LIB和$PLATFORM(也支持${...}形式)
+pub fn expand_dst(path:&str, origin:&Path, class:u8, machine:&MachineType) -> String {
+    let lib = if class == ELFCLASS64 { "lib64" } else { "lib" };
+    let mut result = path.to_string();
+    for (name, value) in [("ORIGIN", origin.to_string_lossy().into_owned()), ("LIB", lib.to_string()), ("PLATFORM", machine_platform(machine, class).to_string())] {
+        result = result.replace(&format!("${{{}}}",name), &value).replace(&format!("${}",name), &value);
+    }
+    result
+}
+
+/// 规范化路径中的`.`和`..`, 不访问文件系统
+fn normalize(path:&Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => { result.pop(); }
+            component => result.push(component),
+        }
+    }
+    result
+}
+
+fn read_target<R:Read + Seek>(elf:&mut R) -> io::Result<Target> {
+    let header = parse_header(elf)?;
+    Ok(Target { class:header.e_ident[4], machine:header.e_machine })
+}
+
+fn class_name(class:u8) -> &'static str {
+    match class {
+        ELFCLASS32 => "ELF32",
+        ELFCLASS64 => "ELF64",
+        _ => "unknown class",
+    }
+}
+
+/// 检查候选文件是否是与根文件匹配的ELF, 不匹配时返回原因
+fn check_candidate(path:&Path, target:&Target) -> Option<Result<(), String>> {
+    let mut file = File::open(path).ok()?;
+    if !file.metadata().ok()?.is_file() {
+        return None;
+    }
+    let mut magic = [0u8;4];
+    if file.read_exact(&mut magic).is_err() || &magic != b"\x7fELF" {
+        return Some(Err(String::from("not an ELF file")));
+    }
+    let candidate = match read_target(&mut file) {
+        Ok(candidate) => candidate,
+        Err(e) => return Some(Err(e.to_string())),
+    };
+    if candidate.class != target.class {
+        return Some(Err(format!("wrong ELF class: {}",class_name(candidate.class))));
+    }
+    if candidate.machine != target.machine {
+        return Some(Err(format!("wrong machine: {}",candidate.machine.to_string())));
+    }
+    Some(Ok(()))
+}
+
+fn read_object(path:&Path) -> io::Result<ObjectInfo> {
+    let mut file = File::open(path)?;
+    let dynamic = parse_dynamic(&mut file)?;
+    let split = |tag:u64| -> Vec<String> {
+        dynamic_strings(&dynamic, tag).iter()
+            .flat_map(|paths| paths.split(':'))
+            .map(String::from)
+            .collect()
+    };
+    Ok(ObjectInfo {
+        needed:dynamic_strings(&dynamic, DT_NEEDED).iter().map(|name| name.to_string()).collect(),
+        rpath:split(DT_RPATH),
+        runpath:split(DT_RUNPATH),
+        origin:normalize(path).parent().map(Path::to_path_buf).unwrap_or_default(),
+    })
+}
+
+fn read_interpreter<R:Read + Seek>(elf:&mut R) -> io::Result<Option<String>> {
+    let segments = parse_segments(elf)?;
+    let Some(segment) = segments.iter().find(|segment| matches!(segment.p_type, SegmentType::INTERP)) else { return Ok(None) };
+    let data = read_segment_data(elf, segment)?;
+    let len = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    Ok(Some(String::from_utf8_lossy(&data[..len]).into_owned()))
+}
+
+struct Resolver<'a> {
+    config:&'a SearchConfig,
+    target:Target,
+    visited:HashSet<PathBuf>,
+}
+
+impl Resolver<'_> {
+    /// 按动态链接器的顺序查找: RPATH(对象没有RUNPATH时, 包括加载链上的), LD_LIBRARY_PATH, RUNPATH, ld.so.conf, 默认目录
+    fn find(&self, name:&str, object:&ObjectInfo, ancestors:&[(Vec<String>, PathBuf)]) -> (Resolution, &'static str) {
+        let mut mismatch = None;
+        let try_path = |path:PathBuf, mismatch:&mut Option<(PathBuf, String)>| -> Option<PathBuf> {
+            match check_candidate(&path, &self.target)? {
+                Ok(()) => Some(path),
+                Err(reason) => {
+                    mismatch.get_or_insert((path, reason));
+                    None
+                }
+            }
+        };
+        if name.contains('/') {
+            return match try_path(self.config.host_path(Path::new(name)), &mut mismatch) {
+                Some(path) => (Resolution::FOUND(path), "path"),
+                None => match mismatch {
+                    Some((path, reason)) => (Resolution::MISMATCH(path, reason), "path"),
+                    None => (Resolution::NOT_FOUND, ""),
+                },
+            };
+        }
+        let expand = |dirs:&[String], origin:&Path| -> Vec<PathBuf> {
+            dirs.iter().map(|dir| {
+                // $ORIGIN展开后已经是主机上的路径, 不再加sysroot
+                let uses_origin = dir.contains("$ORIGIN") || dir.contains("${ORIGIN}");
+                let dir = expand_dst(dir, origin, self.target.class, &self.target.machine);
+                if uses_origin {
+                    PathBuf::from(dir)
+                } else {
+                    self.config.host_path(Path::new(&dir))
+                }
+            }).collect()
+        };
+        let mut searches:Vec<(&'static str, Vec<PathBuf>)> = Vec::new();
+        if object.runpath.is_empty() {
+            searches.push(("RPATH", expand(&object.rpath, &object.origin)));
+            for (rpath, origin) in ancestors.iter().rev() {
+                searches.push(("RPATH", expand(rpath, origin)));
+            }
+        }
+        searches.push(("LD_LIBRARY_PATH", expand(&self.config.library_path, &object.origin)));
+        searches.push(("RUNPATH", expand(&object.runpath, &object.origin)));
+        searches.push(("ld.so.conf", expand(&self.config.config_dirs, &object.origin)));
+        searches.push(("default", expand(&self.config.default_dirs(&self.target), &object.origin)));
+        for (source, dirs) in searches {
+            for dir in dirs {
+                if let Some(path) = try_path(normalize(&dir.join(name)), &mut mismatch) {
+                    return (Resolution::FOUND(path), source);
+                }
+            }
+        }
+        match mismatch {
+            Some((path, reason)) => (Resolution::MISMATCH(path, reason), ""),
+            None => (Resolution::NOT_FOUND, ""),
+        }
+    }
+
+    fn resolve(&mut self, object:&ObjectInfo, ancestors:&mut Vec<(Vec<String>, PathBuf)>) -> Vec<Dependency> {
+        let mut dependencies = Vec::new();
+        for name in &object.needed {
+            let (resolution, source) = self.find(name, object, ancestors);
+            let mut dependency = Dependency { name:name.clone(), resolution, source, repeated:false, children:Vec::new() };
+            if let Resolution::FOUND(path) = &dependency.resolution {
+                let key = std::fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+                if !self.visited.insert(key) {
+                    dependency.repeated = true;
+                } else if let Ok(child) = read_object(path) {
+                    ancestors.push((object.rpath.clone(), object.origin.clone()));
+                    dependency.children = self.resolve(&child, ancestors);
+                    ancestors.pop();
+                }
+            }
+            dependencies.push(dependency);
+        }
+        dependencies
+    }
+}
+
+fn resolver_interpreter(interpreter:&Option<(String, Resolution)>) -> Option<PathBuf> {
+    match interpreter {
+        Some((_, Resolution::FOUND(path))) => Some(path.clone()),
+        _ => None,
+    }
+}
+
+/// 不执行程序, 按动态链接器的规则解析path的依赖树
+pub fn resolve_dependencies(path:&Path, config:&SearchConfig) -> io::Result<DependencyTree> {
+    let mut file = File::open(path)?;
+    let target = read_target(&mut file)?;
+    let interpreter = read_interpreter(&mut file)?.map(|interpreter| {
+        let host = config.host_path(Path::new(&interpreter));
+        let resolution = match check_candidate(&host, &target) {
+            Some(Ok(())) => Resolution::FOUND(host),
+            Some(Err(reason)) => Resolution::MISMATCH(host, reason),
+            None => Resolution::NOT_FOUND,
+        };
+        (interpreter, resolution)
+    });
+    let object = read_object(path)?;
+    let mut resolver = Resolver { config, target, visited:HashSet::new() };
+    let loaded = std::iter::once(path.to_path_buf())
+        .chain(resolver_interpreter(&interpreter));
+    for loaded in loaded {
+        if let Ok(canonical) = std::fs::canonicalize(loaded) {
+            resolver.visited.insert(canonical);
+        }
+    }
+    let needed = resolver.resolve(&object, &mut Vec::new());
+    Ok(DependencyTree { path:path.to_path_buf(), interpreter, needed })
+}
+
+impl DependencyTree {
+    /// 找不到或类型不匹配的依赖
+    pub fn problems(&self) -> Vec<&Dependency> {
+        fn collect<'a>(dependencies:&'a [Dependency], problems:&mut Vec<&'a Dependency>) {
+            for dependency in dependencies {
+                if !matches!(dependency.resolution, Resolution::FOUND(_)) {
+                    problems.push(dependency);
+                }
+                collect(&dependency.children, problems);
+            }
+        }
+        let mut problems = Vec::new();
+        collect(&self.needed, &mut problems);
+        problems
+    }
+
+    /// 找到的所有共享库, 按动态链接器的加载顺序(广度优先)排列, 每个库只出现一次
+    pub fn libraries(&self) -> Vec<&Path> {
+        let mut libraries = Vec::new();
+        let mut level:Vec<&Dependency> = self.needed.iter().collect();
+        while !level.is_empty() {
+            let mut next = Vec::new();
+            for dependency in level {
+                if let (Resolution::FOUND(path), false) = (&dependency.resolution, dependency.repeated) {
+                    libraries.push(path.as_path());
+                }
+                next.extend(dependency.children.iter());
+            }
+            level = next;
+        }
+        libraries
+    }
+}
+
+fn write_resolution(f:&mut Formatter<'_>, resolution:&Resolution) -> std::fmt::Result {
+    match resolution {
+        Resolution::FOUND(path) => write!(f,"{}",path.display()),
+        Resolution::NOT_FOUND => write!(f,"not found"),
+        Resolution::MISMATCH(path, reason) => write!(f,"not found ({}: {})",path.display(),reason),
+    }
+}
+
+fn write_dependencies(f:&mut Formatter<'_>, dependencies:&[Dependency], depth:usize) -> std::fmt::Result {
+    for dependency in dependencies {
+        write!(f,"{:indent$}{} => ","",dependency.name,indent = depth * 4)?;
+        write_resolution(f, &dependency.resolution)?;
+        if !dependency.source.is_empty() {
+            write!(f," [{}]",dependency.source)?;
+        }
+        if dependency.repeated {
+            write!(f," (already listed)")?;
+        }
+        writeln!(f)?;
+        write_dependencies(f, &dependency.children, depth + 1)?;
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for DependencyTree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f,"{}",self.path.display())?;
+        if let Some((interpreter, resolution)) = &self.interpreter {
+            write!(f,"    {} (interpreter) => ",interpreter)?;
+            write_resolution(f, resolution)?;
+            writeln!(f)?;
+        }
+        write_dependencies(f, &self.needed, 1)
+    }
+}
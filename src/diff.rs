@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::elf::dynamic::{has_string, parse_dynamic, tag_name};
+use crate::elf::header::{parse_header, BitType, Encoding};
+use crate::elf::note::{parse_note_sections, parse_note_segments, ElfNote};
+use crate::elf::section::{parse_sections, section_flags_to_string, section_type_name};
+use crate::elf::segment::parse_segments;
+use crate::elf::symbol::{bind_to_string, parse_dynamic_symbols, parse_symbols, type_to_string};
+use crate::elf::version::{parse_versions, versioned_name};
+use crate::json::JsonValue;
+
+pub enum ChangeKind {
+    ADDED,
+    REMOVED,
+    CHANGED,
+}
+
+impl ChangeKind {
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        match self {
+            ChangeKind::ADDED => String::from("added"),
+            ChangeKind::REMOVED => String::from("removed"),
+            ChangeKind::CHANGED => String::from("changed"),
+        }
+    }
+
+    fn marker(&self) -> char {
+        match self {
+            ChangeKind::ADDED => '+',
+            ChangeKind::REMOVED => '-',
+            ChangeKind::CHANGED => '~',
+        }
+    }
+}
+
+/// 一个字段的新旧值, 新增的项没有旧值, 删除的项没有新值
+pub struct FieldChange {
+    pub field:&'static str,
+    pub old:Option<String>,
+    pub new:Option<String>,
+}
+
+pub struct Change {
+    pub kind:ChangeKind,
+    /// 项的名字, 如节名, 段类型, 符号名@版本
+    pub name:String,
+    pub fields:Vec<FieldChange>,
+}
+
+/// 一类结构(节表, 段表等)中的所有变化
+pub struct DiffGroup {
+    pub title:&'static str,
+    pub changes:Vec<Change>,
+}
+
+pub struct ElfDiff {
+    pub groups:Vec<DiffGroup>,
+}
+
+/// 参与比较的一项, 同一类中名字唯一
+struct Item {
+    name:String,
+    fields:Vec<(&'static str, String)>,
+}
+
+/// 名字重复的项(如多个LOAD段)按出现顺序加上序号
+fn number_duplicates(items:&mut [Item]) {
+    let mut totals:HashMap<String, usize> = HashMap::new();
+    for item in items.iter() {
+        *totals.entry(item.name.clone()).or_default() += 1;
+    }
+    let mut seen:HashMap<String, usize> = HashMap::new();
+    for item in items.iter_mut() {
+        if totals[&item.name] > 1 {
+            let count = seen.entry(item.name.clone()).or_default();
+            *count += 1;
+            item.name = format!("{}[{}]",item.name,count);
+        }
+    }
+}
+
+/// 按名字匹配两边的项: 只在旧文件中的为删除, 只在新文件中的为新增, 两边都有但字段不同的为修改
+fn diff_items(title:&'static str, mut old:Vec<Item>, mut new:Vec<Item>) -> DiffGroup {
+    number_duplicates(&mut old);
+    number_duplicates(&mut new);
+    // 编号后同一类中的名字唯一, 按名字建立索引
+    let old_items:HashMap<&str, &Item> = old.iter().map(|item| (item.name.as_str(), item)).collect();
+    let new_items:HashMap<&str, &Item> = new.iter().map(|item| (item.name.as_str(), item)).collect();
+    let mut changes = Vec::new();
+    for item in &old {
+        let Some(other) = new_items.get(item.name.as_str()) else {
+            changes.push(Change {
+                kind:ChangeKind::REMOVED,
+                name:item.name.clone(),
+                fields:item.fields.iter().map(|(field, value)| FieldChange { field, old:Some(value.clone()), new:None }).collect(),
+            });
+            continue;
+        };
+        let fields:Vec<FieldChange> = item.fields.iter().zip(&other.fields)
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((field, old), (_, new))| FieldChange { field, old:Some(old.clone()), new:Some(new.clone()) })
+            .collect();
+        if !fields.is_empty() {
+            changes.push(Change { kind:ChangeKind::CHANGED, name:item.name.clone(), fields });
+        }
+    }
+    for item in &new {
+        if !old_items.contains_key(item.name.as_str()) {
+            changes.push(Change {
+                kind:ChangeKind::ADDED,
+                name:item.name.clone(),
+                fields:item.fields.iter().map(|(field, value)| FieldChange { field, old:None, new:Some(value.clone()) }).collect(),
+            });
+        }
+    }
+    DiffGroup { title, changes }
+}
+
+fn header_items<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<Item>> {
+    let header = parse_header(elf)?;
+    Ok(vec![Item {
+        name:String::from("ELF header"),
+        fields:vec![
+            ("class", BitType::from(header.e_ident[4]).to_string()),
+            ("data", Encoding::from(header.e_ident[5]).to_string()),
+            ("osabi", header.e_ident[7].to_string()),
+            ("type", header.e_type.to_string()),
+            ("machine", header.e_machine.to_string()),
+            ("entry", format!("{:#x}",header.e_entry)),
+            ("flags", format!("{:#x}",header.e_flags)),
+            ("phnum", header.e_phnum.to_string()),
+            ("shnum", header.e_shnum.to_string()),
+        ],
+    }])
+}
+
+/// 文件偏移随布局变化, 不参与节的比较
+fn section_items<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<Item>> {
+    Ok(parse_sections(elf).unwrap_or_default().into_iter()
+        .filter(|section| section.index != 0)
+        .map(|section| Item {
+            name:section.name.clone(),
+            fields:vec![
+                ("type", section_type_name(section.sh_type)),
+                ("flags", section_flags_to_string(section.sh_flags)),
+                ("address", format!("{:#x}",section.sh_addr)),
+                ("size", format!("{:#x}",section.sh_size)),
+                ("align", section.sh_addralign.to_string()),
+                ("entsize", format!("{:#x}",section.sh_entsize)),
+            ],
+        })
+        .collect())
+}
+
+fn segment_items<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<Item>> {
+    Ok(parse_segments(elf)?.into_iter()
+        .map(|segment| Item {
+            name:segment.p_type.to_string(),
+            fields:vec![
+                ("flags", segment.p_flags.to_string()),
+                ("offset", format!("{:#x}",segment.p_offset)),
+                ("vaddr", format!("{:#x}",segment.p_vaddr)),
+                ("filesz", format!("{:#x}",segment.p_filesz)),
+                ("memsz", format!("{:#x}",segment.p_memsz)),
+                ("align", format!("{:#x}",segment.p_align)),
+            ],
+        })
+        .collect())
+}
+
+/// NEEDED等字符串表项以`类型 字符串`作为名字, 如`NEEDED libc.so.6`, 这样顺序变化或增删一个库只影响对应的项
+fn dynamic_items<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<Item>> {
+    Ok(parse_dynamic(elf)?.into_iter()
+        .map(|entry| match has_string(entry.d_tag) {
+            true => Item { name:format!("{} {}",tag_name(entry.d_tag),entry.string.as_deref().unwrap_or("")), fields:Vec::new() },
+            false => Item { name:tag_name(entry.d_tag), fields:vec![("value", entry.value())] },
+        })
+        .collect())
+}
+
+/// 导出的符号, 优先使用.dynsym, 没有时(如静态链接的文件)使用.symtab
+fn symbol_items<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<Item>> {
+    let mut symbols = parse_dynamic_symbols(elf).unwrap_or_default();
+    let mut versions = parse_versions(elf).ok();
+    if symbols.is_empty() {
+        symbols = parse_symbols(elf).unwrap_or_default();
+        versions = None;
+    }
+    Ok(symbols.iter()
+        .filter(|symbol| symbol.is_exported())
+        .map(|symbol| {
+            let version = versions.as_ref().and_then(|versions| versions.symbol_version(symbol.index));
+            Item {
+                name:versioned_name(&symbol.name, version.as_ref()),
+                fields:vec![
+                    ("type", type_to_string(symbol.symbol_type())),
+                    ("bind", bind_to_string(symbol.bind())),
+                    ("size", symbol.st_size.to_string()),
+                ],
+            }
+        })
+        .collect())
+}
+
+fn note_items<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<Item>> {
+    let mut notes:Vec<ElfNote> = parse_note_sections(elf).unwrap_or_default().into_iter()
+        .flat_map(|(_, notes)| notes)
+        .collect();
+    if notes.is_empty() {
+        notes = parse_note_segments(elf)?;
+    }
+    Ok(notes.iter()
+        .map(|note| Item {
+            name:format!("{} {}",note.name,note.type_name()),
+            fields:vec![
+                ("size", note.desc.len().to_string()),
+                ("desc", note.desc.iter().map(|b| format!("{:02x}",b)).collect()),
+            ],
+        })
+        .collect())
+}
+
+/// 比较两个ELF文件的文件头, 节表, 段表, 动态段, 导出符号和注释
+pub fn diff_elf<R:Read + Seek>(old:&mut R, new:&mut R) -> io::Result<ElfDiff> {
+    Ok(ElfDiff {
+        groups:vec![
+            diff_items("ELF header", header_items(old)?, header_items(new)?),
+            diff_items("Sections", section_items(old)?, section_items(new)?),
+            diff_items("Program headers", segment_items(old)?, segment_items(new)?),
+            diff_items("Dynamic section", dynamic_items(old)?, dynamic_items(new)?),
+            diff_items("Exported symbols", symbol_items(old)?, symbol_items(new)?),
+            diff_items("Notes", note_items(old)?, note_items(new)?),
+        ],
+    })
+}
+
+impl ElfDiff {
+    pub fn is_empty(&self) -> bool {
+        self.groups.iter().all(|group| group.changes.is_empty())
+    }
+
+    /// 每一类以小写下划线形式的标题(如program_headers)为键
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::OBJECT(self.groups.iter().map(|group| {
+            let changes = group.changes.iter().map(|change| JsonValue::object([
+                ("kind", change.kind.to_string().into()),
+                ("name", change.name.clone().into()),
+                ("fields", JsonValue::object(change.fields.iter().map(|field| {
+                    (field.field, JsonValue::object([("old", field.old.clone().into()), ("new", field.new.clone().into())]))
+                }))),
+            ])).collect();
+            (group.title.to_lowercase().replace(' ', "_"), JsonValue::ARRAY(changes))
+        }).collect())
+    }
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f,"{} {}",self.kind.marker(),self.name)?;
+        let fields:Vec<String> = self.fields.iter().map(|field| match (&field.old, &field.new) {
+            (Some(old), Some(new)) => format!("{}: {} -> {}",field.field,old,new),
+            (Some(value), None) | (None, Some(value)) => format!("{}: {}",field.field,value),
+            (None, None) => String::from(field.field),
+        }).collect();
+        if !fields.is_empty() {
+            write!(f," ({})",fields.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ElfDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f,"No structural differences.");
+        }
+        for group in self.groups.iter().filter(|group| !group.changes.is_empty()) {
+            writeln!(f,"{}:",group.title)?;
+            for change in &group.changes {
+                writeln!(f,"  {}",change)?;
+            }
+        }
+        Ok(())
+    }
+}
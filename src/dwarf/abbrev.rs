@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use std::io;
+use crate::dwarf::constants::DW_FORM_IMPLICIT_CONST;
+use crate::dwarf::reader::DwarfReader;
+
+/// 缩写表中一个属性的声明
+pub struct AttributeSpec {
+    pub name:u64,
+    pub form:u64,
+    /// DW_FORM_implicit_const的值直接保存在缩写表中
+    pub implicit_const:Option<i64>,
+}
+
+/// .debug_abbrev中的一条缩写, 描述了DIE的tag和属性布局
+pub struct Abbreviation {
+    pub code:u64,
+    pub tag:u64,
+    pub has_children:bool,
+    pub attributes:Vec<AttributeSpec>,
+}
+
+/// 解析从offset开始的一张缩写表, 以缩写编号为键
+pub fn parse_abbrevs(debug_abbrev:&[u8], offset:u64) -> io::Result<HashMap<u64, Abbreviation>> {
+    let mut reader = DwarfReader::new(debug_abbrev);
+    reader.seek(usize::try_from(offset).unwrap_or(usize::MAX))?;
+    let mut abbrevs = HashMap::new();
+    loop {
+        let code = reader.uleb128()?;
+        if code == 0 {
+            break;
+        }
+        let tag = reader.uleb128()?;
+        let has_children = reader.u8()? != 0;
+        let mut attributes = Vec::new();
+        loop {
+            let name = reader.uleb128()?;
+            let form = reader.uleb128()?;
+            if name == 0 && form == 0 {
+                break;
+            }
+            let implicit_const = if form == DW_FORM_IMPLICIT_CONST {
+                Some(reader.sleb128()?)
+            } else {
+                None
+            };
+            attributes.push(AttributeSpec { name, form, implicit_const });
+        }
+        abbrevs.insert(code, Abbreviation { code, tag, has_children, attributes });
+    }
+    Ok(abbrevs)
+}
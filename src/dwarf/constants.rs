@@ -0,0 +1,451 @@
+// DWARF常量及其名字, 取值见DWARF 5标准第7章
+
+pub const DW_TAG_COMPILE_UNIT:u64 = 0x11;
+pub const DW_TAG_INLINED_SUBROUTINE:u64 = 0x1d;
+pub const DW_TAG_SUBPROGRAM:u64 = 0x2e;
+pub const DW_TAG_PARTIAL_UNIT:u64 = 0x3c;
+pub const DW_TAG_SKELETON_UNIT:u64 = 0x4a;
+
+pub const DW_AT_LOCATION:u64 = 0x02;
+pub const DW_AT_NAME:u64 = 0x03;
+pub const DW_AT_STMT_LIST:u64 = 0x10;
+pub const DW_AT_LOW_PC:u64 = 0x11;
+pub const DW_AT_HIGH_PC:u64 = 0x12;
+pub const DW_AT_LANGUAGE:u64 = 0x13;
+pub const DW_AT_COMP_DIR:u64 = 0x1b;
+pub const DW_AT_PRODUCER:u64 = 0x25;
+pub const DW_AT_ABSTRACT_ORIGIN:u64 = 0x31;
+pub const DW_AT_ENCODING:u64 = 0x3e;
+pub const DW_AT_FRAME_BASE:u64 = 0x40;
+pub const DW_AT_SPECIFICATION:u64 = 0x47;
+pub const DW_AT_RANGES:u64 = 0x55;
+pub const DW_AT_CALL_COLUMN:u64 = 0x57;
+pub const DW_AT_CALL_FILE:u64 = 0x58;
+pub const DW_AT_CALL_LINE:u64 = 0x59;
+pub const DW_AT_LINKAGE_NAME:u64 = 0x6e;
+pub const DW_AT_STR_OFFSETS_BASE:u64 = 0x72;
+pub const DW_AT_ADDR_BASE:u64 = 0x73;
+pub const DW_AT_RNGLISTS_BASE:u64 = 0x74;
+pub const DW_AT_LOCLISTS_BASE:u64 = 0x8c;
+pub const DW_AT_MIPS_LINKAGE_NAME:u64 = 0x2007;
+pub const DW_AT_GNU_ADDR_BASE:u64 = 0x2133;
+
+pub const DW_FORM_ADDR:u64 = 0x01;
+pub const DW_FORM_BLOCK2:u64 = 0x03;
+pub const DW_FORM_BLOCK4:u64 = 0x04;
+pub const DW_FORM_DATA2:u64 = 0x05;
+pub const DW_FORM_DATA4:u64 = 0x06;
+pub const DW_FORM_DATA8:u64 = 0x07;
+pub const DW_FORM_STRING:u64 = 0x08;
+pub const DW_FORM_BLOCK:u64 = 0x09;
+pub const DW_FORM_BLOCK1:u64 = 0x0a;
+pub const DW_FORM_DATA1:u64 = 0x0b;
+pub const DW_FORM_FLAG:u64 = 0x0c;
+pub const DW_FORM_SDATA:u64 = 0x0d;
+pub const DW_FORM_STRP:u64 = 0x0e;
+pub const DW_FORM_UDATA:u64 = 0x0f;
+pub const DW_FORM_REF_ADDR:u64 = 0x10;
+pub const DW_FORM_REF1:u64 = 0x11;
+pub const DW_FORM_REF2:u64 = 0x12;
+pub const DW_FORM_REF4:u64 = 0x13;
+pub const DW_FORM_REF8:u64 = 0x14;
+pub const DW_FORM_REF_UDATA:u64 = 0x15;
+pub const DW_FORM_INDIRECT:u64 = 0x16;
+pub const DW_FORM_SEC_OFFSET:u64 = 0x17;
+pub const DW_FORM_EXPRLOC:u64 = 0x18;
+pub const DW_FORM_FLAG_PRESENT:u64 = 0x19;
+pub const DW_FORM_STRX:u64 = 0x1a;
+pub const DW_FORM_ADDRX:u64 = 0x1b;
+pub const DW_FORM_REF_SUP4:u64 = 0x1c;
+pub const DW_FORM_STRP_SUP:u64 = 0x1d;
+pub const DW_FORM_DATA16:u64 = 0x1e;
+pub const DW_FORM_LINE_STRP:u64 = 0x1f;
+pub const DW_FORM_REF_SIG8:u64 = 0x20;
+pub const DW_FORM_IMPLICIT_CONST:u64 = 0x21;
+pub const DW_FORM_LOCLISTX:u64 = 0x22;
+pub const DW_FORM_RNGLISTX:u64 = 0x23;
+pub const DW_FORM_REF_SUP8:u64 = 0x24;
+pub const DW_FORM_STRX1:u64 = 0x25;
+pub const DW_FORM_STRX2:u64 = 0x26;
+pub const DW_FORM_STRX3:u64 = 0x27;
+pub const DW_FORM_STRX4:u64 = 0x28;
+pub const DW_FORM_ADDRX1:u64 = 0x29;
+pub const DW_FORM_ADDRX2:u64 = 0x2a;
+pub const DW_FORM_ADDRX3:u64 = 0x2b;
+pub const DW_FORM_ADDRX4:u64 = 0x2c;
+pub const DW_FORM_GNU_ADDR_INDEX:u64 = 0x1f01;
+pub const DW_FORM_GNU_STR_INDEX:u64 = 0x1f02;
+pub const DW_FORM_GNU_REF_ALT:u64 = 0x1f20;
+pub const DW_FORM_GNU_STRP_ALT:u64 = 0x1f21;
+
+pub const DW_UT_COMPILE:u8 = 0x01;
+pub const DW_UT_TYPE:u8 = 0x02;
+pub const DW_UT_PARTIAL:u8 = 0x03;
+pub const DW_UT_SKELETON:u8 = 0x04;
+pub const DW_UT_SPLIT_COMPILE:u8 = 0x05;
+pub const DW_UT_SPLIT_TYPE:u8 = 0x06;
+
+pub fn tag_name(tag:u64) -> String {
+    let name = match tag {
+        0x01 => "DW_TAG_array_type",
+        0x02 => "DW_TAG_class_type",
+        0x03 => "DW_TAG_entry_point",
+        0x04 => "DW_TAG_enumeration_type",
+        0x05 => "DW_TAG_formal_parameter",
+        0x08 => "DW_TAG_imported_declaration",
+        0x0a => "DW_TAG_label",
+        0x0b => "DW_TAG_lexical_block",
+        0x0d => "DW_TAG_member",
+        0x0f => "DW_TAG_pointer_type",
+        0x10 => "DW_TAG_reference_type",
+        0x11 => "DW_TAG_compile_unit",
+        0x12 => "DW_TAG_string_type",
+        0x13 => "DW_TAG_structure_type",
+        0x15 => "DW_TAG_subroutine_type",
+        0x16 => "DW_TAG_typedef",
+        0x17 => "DW_TAG_union_type",
+        0x18 => "DW_TAG_unspecified_parameters",
+        0x19 => "DW_TAG_variant",
+        0x1a => "DW_TAG_common_block",
+        0x1b => "DW_TAG_common_inclusion",
+        0x1c => "DW_TAG_inheritance",
+        0x1d => "DW_TAG_inlined_subroutine",
+        0x1e => "DW_TAG_module",
+        0x1f => "DW_TAG_ptr_to_member_type",
+        0x20 => "DW_TAG_set_type",
+        0x21 => "DW_TAG_subrange_type",
+        0x22 => "DW_TAG_with_stmt",
+        0x23 => "DW_TAG_access_declaration",
+        0x24 => "DW_TAG_base_type",
+        0x25 => "DW_TAG_catch_block",
+        0x26 => "DW_TAG_const_type",
+        0x27 => "DW_TAG_constant",
+        0x28 => "DW_TAG_enumerator",
+        0x29 => "DW_TAG_file_type",
+        0x2a => "DW_TAG_friend",
+        0x2b => "DW_TAG_namelist",
+        0x2c => "DW_TAG_namelist_item",
+        0x2d => "DW_TAG_packed_type",
+        0x2e => "DW_TAG_subprogram",
+        0x2f => "DW_TAG_template_type_param",
+        0x30 => "DW_TAG_template_value_param",
+        0x31 => "DW_TAG_thrown_type",
+        0x32 => "DW_TAG_try_block",
+        0x33 => "DW_TAG_variant_part",
+        0x34 => "DW_TAG_variable",
+        0x35 => "DW_TAG_volatile_type",
+        0x36 => "DW_TAG_dwarf_procedure",
+        0x37 => "DW_TAG_restrict_type",
+        0x38 => "DW_TAG_interface_type",
+        0x39 => "DW_TAG_namespace",
+        0x3a => "DW_TAG_imported_module",
+        0x3b => "DW_TAG_unspecified_type",
+        0x3c => "DW_TAG_partial_unit",
+        0x3d => "DW_TAG_imported_unit",
+        0x3f => "DW_TAG_condition",
+        0x40 => "DW_TAG_shared_type",
+        0x41 => "DW_TAG_type_unit",
+        0x42 => "DW_TAG_rvalue_reference_type",
+        0x43 => "DW_TAG_template_alias",
+        0x44 => "DW_TAG_coarray_type",
+        0x45 => "DW_TAG_generic_subrange",
+        0x46 => "DW_TAG_dynamic_type",
+        0x47 => "DW_TAG_atomic_type",
+        0x48 => "DW_TAG_call_site",
+        0x49 => "DW_TAG_call_site_parameter",
+        0x4a => "DW_TAG_skeleton_unit",
+        0x4b => "DW_TAG_immutable_type",
+        0x4106 => "DW_TAG_GNU_template_template_param",
+        0x4107 => "DW_TAG_GNU_template_parameter_pack",
+        0x4108 => "DW_TAG_GNU_formal_parameter_pack",
+        0x4109 => "DW_TAG_GNU_call_site",
+        0x410a => "DW_TAG_GNU_call_site_parameter",
+        _ => return format!("DW_TAG_unknown_{:#x}", tag),
+    };
+    String::from(name)
+}
+
+pub fn attr_name(attr:u64) -> String {
+    let name = match attr {
+        0x01 => "DW_AT_sibling",
+        0x02 => "DW_AT_location",
+        0x03 => "DW_AT_name",
+        0x09 => "DW_AT_ordering",
+        0x0b => "DW_AT_byte_size",
+        0x0c => "DW_AT_bit_offset",
+        0x0d => "DW_AT_bit_size",
+        0x10 => "DW_AT_stmt_list",
+        0x11 => "DW_AT_low_pc",
+        0x12 => "DW_AT_high_pc",
+        0x13 => "DW_AT_language",
+        0x15 => "DW_AT_discr",
+        0x16 => "DW_AT_discr_value",
+        0x17 => "DW_AT_visibility",
+        0x18 => "DW_AT_import",
+        0x19 => "DW_AT_string_length",
+        0x1a => "DW_AT_common_reference",
+        0x1b => "DW_AT_comp_dir",
+        0x1c => "DW_AT_const_value",
+        0x1d => "DW_AT_containing_type",
+        0x1e => "DW_AT_default_value",
+        0x20 => "DW_AT_inline",
+        0x21 => "DW_AT_is_optional",
+        0x22 => "DW_AT_lower_bound",
+        0x25 => "DW_AT_producer",
+        0x27 => "DW_AT_prototyped",
+        0x2a => "DW_AT_return_addr",
+        0x2c => "DW_AT_start_scope",
+        0x2e => "DW_AT_bit_stride",
+        0x2f => "DW_AT_upper_bound",
+        0x31 => "DW_AT_abstract_origin",
+        0x32 => "DW_AT_accessibility",
+        0x33 => "DW_AT_address_class",
+        0x34 => "DW_AT_artificial",
+        0x35 => "DW_AT_base_types",
+        0x36 => "DW_AT_calling_convention",
+        0x37 => "DW_AT_count",
+        0x38 => "DW_AT_data_member_location",
+        0x39 => "DW_AT_decl_column",
+        0x3a => "DW_AT_decl_file",
+        0x3b => "DW_AT_decl_line",
+        0x3c => "DW_AT_declaration",
+        0x3d => "DW_AT_discr_list",
+        0x3e => "DW_AT_encoding",
+        0x3f => "DW_AT_external",
+        0x40 => "DW_AT_frame_base",
+        0x41 => "DW_AT_friend",
+        0x42 => "DW_AT_identifier_case",
+        0x43 => "DW_AT_macro_info",
+        0x44 => "DW_AT_namelist_item",
+        0x45 => "DW_AT_priority",
+        0x46 => "DW_AT_segment",
+        0x47 => "DW_AT_specification",
+        0x48 => "DW_AT_static_link",
+        0x49 => "DW_AT_type",
+        0x4a => "DW_AT_use_location",
+        0x4b => "DW_AT_variable_parameter",
+        0x4c => "DW_AT_virtuality",
+        0x4d => "DW_AT_vtable_elem_location",
+        0x4e => "DW_AT_allocated",
+        0x4f => "DW_AT_associated",
+        0x50 => "DW_AT_data_location",
+        0x51 => "DW_AT_byte_stride",
+        0x52 => "DW_AT_entry_pc",
+        0x53 => "DW_AT_use_UTF8",
+        0x54 => "DW_AT_extension",
+        0x55 => "DW_AT_ranges",
+        0x56 => "DW_AT_trampoline",
+        0x57 => "DW_AT_call_column",
+        0x58 => "DW_AT_call_file",
+        0x59 => "DW_AT_call_line",
+        0x5a => "DW_AT_description",
+        0x5b => "DW_AT_binary_scale",
+        0x5c => "DW_AT_decimal_scale",
+        0x5d => "DW_AT_small",
+        0x5e => "DW_AT_decimal_sign",
+        0x5f => "DW_AT_digit_count",
+        0x60 => "DW_AT_picture_string",
+        0x61 => "DW_AT_mutable",
+        0x62 => "DW_AT_threads_scaled",
+        0x63 => "DW_AT_explicit",
+        0x64 => "DW_AT_object_pointer",
+        0x65 => "DW_AT_endianity",
+        0x66 => "DW_AT_elemental",
+        0x67 => "DW_AT_pure",
+        0x68 => "DW_AT_recursive",
+        0x69 => "DW_AT_signature",
+        0x6a => "DW_AT_main_subprogram",
+        0x6b => "DW_AT_data_bit_offset",
+        0x6c => "DW_AT_const_expr",
+        0x6d => "DW_AT_enum_class",
+        0x6e => "DW_AT_linkage_name",
+        0x6f => "DW_AT_string_length_bit_size",
+        0x70 => "DW_AT_string_length_byte_size",
+        0x71 => "DW_AT_rank",
+        0x72 => "DW_AT_str_offsets_base",
+        0x73 => "DW_AT_addr_base",
+        0x74 => "DW_AT_rnglists_base",
+        0x76 => "DW_AT_dwo_name",
+        0x77 => "DW_AT_reference",
+        0x78 => "DW_AT_rvalue_reference",
+        0x79 => "DW_AT_macros",
+        0x7a => "DW_AT_call_all_calls",
+        0x7b => "DW_AT_call_all_source_calls",
+        0x7c => "DW_AT_call_all_tail_calls",
+        0x7d => "DW_AT_call_return_pc",
+        0x7e => "DW_AT_call_value",
+        0x7f => "DW_AT_call_origin",
+        0x80 => "DW_AT_call_parameter",
+        0x81 => "DW_AT_call_pc",
+        0x82 => "DW_AT_call_tail_call",
+        0x83 => "DW_AT_call_target",
+        0x84 => "DW_AT_call_target_clobbered",
+        0x85 => "DW_AT_call_data_location",
+        0x86 => "DW_AT_call_data_value",
+        0x87 => "DW_AT_noreturn",
+        0x88 => "DW_AT_alignment",
+        0x89 => "DW_AT_export_symbols",
+        0x8a => "DW_AT_deleted",
+        0x8b => "DW_AT_defaulted",
+        0x8c => "DW_AT_loclists_base",
+        0x2007 => "DW_AT_MIPS_linkage_name",
+        0x2101 => "DW_AT_sf_names",
+        0x2105 => "DW_AT_body_begin",
+        0x2106 => "DW_AT_body_end",
+        0x2107 => "DW_AT_GNU_vector",
+        0x2111 => "DW_AT_GNU_call_site_value",
+        0x2113 => "DW_AT_GNU_call_site_target",
+        0x2115 => "DW_AT_GNU_tail_call",
+        0x2116 => "DW_AT_GNU_all_tail_call_sites",
+        0x2117 => "DW_AT_GNU_all_call_sites",
+        0x2119 => "DW_AT_GNU_macros",
+        0x211a => "DW_AT_GNU_deleted",
+        0x2130 => "DW_AT_GNU_dwo_name",
+        0x2131 => "DW_AT_GNU_dwo_id",
+        0x2132 => "DW_AT_GNU_ranges_base",
+        0x2133 => "DW_AT_GNU_addr_base",
+        0x2134 => "DW_AT_GNU_pubnames",
+        0x2135 => "DW_AT_GNU_pubtypes",
+        0x2137 => "DW_AT_GNU_locviews",
+        0x2138 => "DW_AT_GNU_entry_view",
+        _ => return format!("DW_AT_unknown_{:#x}", attr),
+    };
+    String::from(name)
+}
+
+pub fn form_name(form:u64) -> String {
+    let name = match form {
+        DW_FORM_ADDR => "DW_FORM_addr",
+        DW_FORM_BLOCK2 => "DW_FORM_block2",
+        DW_FORM_BLOCK4 => "DW_FORM_block4",
+        DW_FORM_DATA2 => "DW_FORM_data2",
+        DW_FORM_DATA4 => "DW_FORM_data4",
+        DW_FORM_DATA8 => "DW_FORM_data8",
+        DW_FORM_STRING => "DW_FORM_string",
+        DW_FORM_BLOCK => "DW_FORM_block",
+        DW_FORM_BLOCK1 => "DW_FORM_block1",
+        DW_FORM_DATA1 => "DW_FORM_data1",
+        DW_FORM_FLAG => "DW_FORM_flag",
+        DW_FORM_SDATA => "DW_FORM_sdata",
+        DW_FORM_STRP => "DW_FORM_strp",
+        DW_FORM_UDATA => "DW_FORM_udata",
+        DW_FORM_REF_ADDR => "DW_FORM_ref_addr",
+        DW_FORM_REF1 => "DW_FORM_ref1",
+        DW_FORM_REF2 => "DW_FORM_ref2",
+        DW_FORM_REF4 => "DW_FORM_ref4",
+        DW_FORM_REF8 => "DW_FORM_ref8",
+        DW_FORM_REF_UDATA => "DW_FORM_ref_udata",
+        DW_FORM_INDIRECT => "DW_FORM_indirect",
+        DW_FORM_SEC_OFFSET => "DW_FORM_sec_offset",
+        DW_FORM_EXPRLOC => "DW_FORM_exprloc",
+        DW_FORM_FLAG_PRESENT => "DW_FORM_flag_present",
+        DW_FORM_STRX => "DW_FORM_strx",
+        DW_FORM_ADDRX => "DW_FORM_addrx",
+        DW_FORM_REF_SUP4 => "DW_FORM_ref_sup4",
+        DW_FORM_STRP_SUP => "DW_FORM_strp_sup",
+        DW_FORM_DATA16 => "DW_FORM_data16",
+        DW_FORM_LINE_STRP => "DW_FORM_line_strp",
+        DW_FORM_REF_SIG8 => "DW_FORM_ref_sig8",
+        DW_FORM_IMPLICIT_CONST => "DW_FORM_implicit_const",
+        DW_FORM_LOCLISTX => "DW_FORM_loclistx",
+        DW_FORM_RNGLISTX => "DW_FORM_rnglistx",
+        DW_FORM_REF_SUP8 => "DW_FORM_ref_sup8",
+        DW_FORM_STRX1 => "DW_FORM_strx1",
+        DW_FORM_STRX2 => "DW_FORM_strx2",
+        DW_FORM_STRX3 => "DW_FORM_strx3",
+        DW_FORM_STRX4 => "DW_FORM_strx4",
+        DW_FORM_ADDRX1 => "DW_FORM_addrx1",
+        DW_FORM_ADDRX2 => "DW_FORM_addrx2",
+        DW_FORM_ADDRX3 => "DW_FORM_addrx3",
+        DW_FORM_ADDRX4 => "DW_FORM_addrx4",
+        DW_FORM_GNU_ADDR_INDEX => "DW_FORM_GNU_addr_index",
+        DW_FORM_GNU_STR_INDEX => "DW_FORM_GNU_str_index",
+        DW_FORM_GNU_REF_ALT => "DW_FORM_GNU_ref_alt",
+        DW_FORM_GNU_STRP_ALT => "DW_FORM_GNU_strp_alt",
+        _ => return format!("DW_FORM_unknown_{:#x}", form),
+    };
+    String::from(name)
+}
+
+pub fn unit_type_name(unit_type:u8) -> String {
+    let name = match unit_type {
+        DW_UT_COMPILE => "DW_UT_compile",
+        DW_UT_TYPE => "DW_UT_type",
+        DW_UT_PARTIAL => "DW_UT_partial",
+        DW_UT_SKELETON => "DW_UT_skeleton",
+        DW_UT_SPLIT_COMPILE => "DW_UT_split_compile",
+        DW_UT_SPLIT_TYPE => "DW_UT_split_type",
+        _ => return format!("DW_UT_unknown_{:#x}", unit_type),
+    };
+    String::from(name)
+}
+
+/// DW_AT_language的取值
+pub fn language_name(lang:u64) -> Option<&'static str> {
+    Some(match lang {
+        0x01 => "ANSI C",
+        0x02 => "K&R C",
+        0x03 => "Ada 83",
+        0x04 => "C++",
+        0x05 => "Cobol 74",
+        0x06 => "Cobol 85",
+        0x07 => "FORTRAN 77",
+        0x08 => "Fortran 90",
+        0x09 => "Pascal 83",
+        0x0a => "Modula 2",
+        0x0b => "Java",
+        0x0c => "ANSI C99",
+        0x0d => "ADA 95",
+        0x0e => "Fortran 95",
+        0x0f => "PLI",
+        0x10 => "Objective C",
+        0x11 => "Objective C++",
+        0x12 => "Unified Parallel C",
+        0x13 => "D",
+        0x14 => "Python",
+        0x15 => "OpenCL",
+        0x16 => "Go",
+        0x17 => "Modula 3",
+        0x18 => "Haskell",
+        0x19 => "C++03",
+        0x1a => "C++11",
+        0x1b => "OCaml",
+        0x1c => "Rust",
+        0x1d => "C11",
+        0x1e => "Swift",
+        0x1f => "Julia",
+        0x20 => "Dylan",
+        0x21 => "C++14",
+        0x22 => "Fortran 03",
+        0x23 => "Fortran 08",
+        0x24 => "RenderScript",
+        0x25 => "BLISS",
+        0x8001 => "MIPS assembler",
+        _ => return None,
+    })
+}
+
+/// DW_AT_encoding(基本类型编码)的取值
+pub fn encoding_name(encoding:u64) -> Option<&'static str> {
+    Some(match encoding {
+        0x01 => "machine address",
+        0x02 => "boolean",
+        0x03 => "complex float",
+        0x04 => "float",
+        0x05 => "signed",
+        0x06 => "signed char",
+        0x07 => "unsigned",
+        0x08 => "unsigned char",
+        0x09 => "imaginary float",
+        0x0a => "packed_decimal",
+        0x0b => "numeric_string",
+        0x0c => "edited",
+        0x0d => "signed_fixed",
+        0x0e => "unsigned_fixed",
+        0x0f => "decimal float",
+        0x10 => "UTF",
+        0x11 => "UCS",
+        0x12 => "ASCII",
+        _ => return None,
+    })
+}
@@ -0,0 +1,711 @@
+use std::collections::BTreeMap;
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::dwarf::reader::DwarfReader;
+use crate::elf::bounds::invalid;
+use crate::elf::header::{parse_header, MachineType};
+use crate::elf::section::{find_section, parse_sections, read_section_data};
+
+// 指针编码(DW_EH_PE_*), 低4位为数据格式, 高4位为基址
+pub const DW_EH_PE_ABSPTR:u8 = 0x00;
+pub const DW_EH_PE_ULEB128:u8 = 0x01;
+pub const DW_EH_PE_UDATA2:u8 = 0x02;
+pub const DW_EH_PE_UDATA4:u8 = 0x03;
+pub const DW_EH_PE_UDATA8:u8 = 0x04;
+pub const DW_EH_PE_SLEB128:u8 = 0x09;
+pub const DW_EH_PE_SDATA2:u8 = 0x0a;
+pub const DW_EH_PE_SDATA4:u8 = 0x0b;
+pub const DW_EH_PE_SDATA8:u8 = 0x0c;
+pub const DW_EH_PE_PCREL:u8 = 0x10;
+pub const DW_EH_PE_TEXTREL:u8 = 0x20;
+pub const DW_EH_PE_DATAREL:u8 = 0x30;
+pub const DW_EH_PE_FUNCREL:u8 = 0x40;
+pub const DW_EH_PE_ALIGNED:u8 = 0x50;
+pub const DW_EH_PE_INDIRECT:u8 = 0x80;
+pub const DW_EH_PE_OMIT:u8 = 0xff;
+
+// 调用帧指令(DW_CFA_*)
+const DW_CFA_ADVANCE_LOC:u8 = 0x40;
+const DW_CFA_OFFSET:u8 = 0x80;
+const DW_CFA_RESTORE:u8 = 0xc0;
+const DW_CFA_NOP:u8 = 0x00;
+const DW_CFA_SET_LOC:u8 = 0x01;
+const DW_CFA_ADVANCE_LOC1:u8 = 0x02;
+const DW_CFA_ADVANCE_LOC2:u8 = 0x03;
+const DW_CFA_ADVANCE_LOC4:u8 = 0x04;
+const DW_CFA_OFFSET_EXTENDED:u8 = 0x05;
+const DW_CFA_RESTORE_EXTENDED:u8 = 0x06;
+const DW_CFA_UNDEFINED:u8 = 0x07;
+const DW_CFA_SAME_VALUE:u8 = 0x08;
+const DW_CFA_REGISTER:u8 = 0x09;
+const DW_CFA_REMEMBER_STATE:u8 = 0x0a;
+const DW_CFA_RESTORE_STATE:u8 = 0x0b;
+const DW_CFA_DEF_CFA:u8 = 0x0c;
+const DW_CFA_DEF_CFA_REGISTER:u8 = 0x0d;
+const DW_CFA_DEF_CFA_OFFSET:u8 = 0x0e;
+const DW_CFA_DEF_CFA_EXPRESSION:u8 = 0x0f;
+const DW_CFA_EXPRESSION:u8 = 0x10;
+const DW_CFA_OFFSET_EXTENDED_SF:u8 = 0x11;
+const DW_CFA_DEF_CFA_SF:u8 = 0x12;
+const DW_CFA_DEF_CFA_OFFSET_SF:u8 = 0x13;
+const DW_CFA_VAL_OFFSET:u8 = 0x14;
+const DW_CFA_VAL_OFFSET_SF:u8 = 0x15;
+const DW_CFA_VAL_EXPRESSION:u8 = 0x16;
+const DW_CFA_GNU_WINDOW_SAVE:u8 = 0x2d;
+const DW_CFA_GNU_ARGS_SIZE:u8 = 0x2e;
+const DW_CFA_GNU_NEGATIVE_OFFSET_EXTENDED:u8 = 0x2f;
+
+/// 通用信息项(CIE), 多个FDE共享的初始规则和编码方式
+pub struct Cie {
+    pub offset:u64,
+    pub length:u64,
+    pub version:u8,
+    pub augmentation:String,
+    pub code_alignment_factor:u64,
+    pub data_alignment_factor:i64,
+    pub return_address_register:u64,
+    pub augmentation_data:Vec<u8>,
+    pub fde_encoding:u8,
+    pub lsda_encoding:u8,
+    pub personality_encoding:u8,
+    pub personality:Option<u64>,
+    pub signal_frame:bool,
+    /// 初始指令在内存中的地址, 用于解析DW_CFA_set_loc中的pc相对指针
+    pub instructions_address:u64,
+    pub instructions:Vec<u8>,
+}
+
+/// 帧描述项(FDE), 描述一个函数(地址范围)的展开规则
+pub struct Fde {
+    pub offset:u64,
+    pub length:u64,
+    pub cie_pointer:u64,
+    pub cie_offset:u64,
+    pub pc_begin:u64,
+    pub pc_range:u64,
+    pub lsda:Option<u64>,
+    pub instructions_address:u64,
+    pub instructions:Vec<u8>,
+}
+
+pub enum FrameEntry {
+    Cie(Cie),
+    Fde(Fde),
+    /// 长度为0的结束标记
+    Terminator(u64),
+}
+
+/// 解析后的.eh_frame节
+pub struct EhFrame {
+    pub address:u64,
+    pub machine:MachineType,
+    pub entries:Vec<FrameEntry>,
+}
+
+/// .eh_frame_hdr节, table是按初始地址排序的(初始地址, FDE地址)二分查找表
+pub struct EhFrameHdr {
+    pub address:u64,
+    pub version:u8,
+    pub eh_frame_ptr_encoding:u8,
+    pub fde_count_encoding:u8,
+    pub table_encoding:u8,
+    pub eh_frame_ptr:Option<u64>,
+    pub fde_count:u64,
+    pub table:Vec<(u64, u64)>,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum RegisterRule {
+    Undefined,
+    SameValue,
+    /// 保存在CFA+N处
+    Offset(i64),
+    /// 值为CFA+N
+    ValOffset(i64),
+    Register(u64),
+    Expression(Vec<u8>),
+    ValExpression(Vec<u8>),
+}
+
+#[derive(Clone, PartialEq)]
+pub enum CfaRule {
+    RegisterOffset(u64, i64),
+    Expression(Vec<u8>),
+}
+
+/// 展开表中的一行: 从address开始生效的CFA和各寄存器规则
+#[derive(Clone)]
+pub struct UnwindRow {
+    pub address:u64,
+    pub cfa:CfaRule,
+    pub registers:BTreeMap<u64, RegisterRule>,
+}
+
+/// 读取编码后的指针时需要的基址
+struct PointerContext {
+    /// 被读取数据起始处在内存中的地址
+    address:u64,
+    data_base:u64,
+}
+
+/// 按DW_EH_PE_*编码读取一个指针, DW_EH_PE_omit返回None
+fn read_encoded(reader:&mut DwarfReader, encoding:u8, context:&PointerContext) -> io::Result<Option<u64>> {
+    if encoding == DW_EH_PE_OMIT {
+        return Ok(None);
+    }
+    if encoding & 0x70 == DW_EH_PE_ALIGNED {
+        let pos = reader.position();
+        reader.seek(pos.div_ceil(8) * 8)?;
+    }
+    let field_address = context.address.wrapping_add(reader.position() as u64);
+    let value = match encoding & 0x0f {
+        DW_EH_PE_ABSPTR => reader.u64()?,
+        DW_EH_PE_ULEB128 => reader.uleb128()?,
+        DW_EH_PE_UDATA2 => reader.u16()? as u64,
+        DW_EH_PE_UDATA4 => reader.u32()? as u64,
+        DW_EH_PE_UDATA8 => reader.u64()?,
+        DW_EH_PE_SLEB128 => reader.sleb128()? as u64,
+        DW_EH_PE_SDATA2 => reader.u16()? as i16 as i64 as u64,
+        DW_EH_PE_SDATA4 => reader.u32()? as i32 as i64 as u64,
+        DW_EH_PE_SDATA8 => reader.u64()?,
+        format => return Err(invalid(format!("unsupported pointer format {:#x}", format))),
+    };
+    let value = match encoding & 0x70 {
+        DW_EH_PE_PCREL => value.wrapping_add(field_address),
+        DW_EH_PE_DATAREL => value.wrapping_add(context.data_base),
+        // textrel/funcrel的基址与运行环境有关, 这里保留原值
+        _ => value,
+    };
+    Ok(Some(value))
+}
+
+/// 指针编码的文字描述, 如"pcrel sdata4"
+pub fn encoding_to_string(encoding:u8) -> String {
+    if encoding == DW_EH_PE_OMIT {
+        return String::from("omit");
+    }
+    let format = match encoding & 0x0f {
+        DW_EH_PE_ABSPTR => "absptr",
+        DW_EH_PE_ULEB128 => "uleb128",
+        DW_EH_PE_UDATA2 => "udata2",
+        DW_EH_PE_UDATA4 => "udata4",
+        DW_EH_PE_UDATA8 => "udata8",
+        DW_EH_PE_SLEB128 => "sleb128",
+        DW_EH_PE_SDATA2 => "sdata2",
+        DW_EH_PE_SDATA4 => "sdata4",
+        DW_EH_PE_SDATA8 => "sdata8",
+        _ => "unknown",
+    };
+    let application = match encoding & 0x70 {
+        DW_EH_PE_PCREL => "pcrel ",
+        DW_EH_PE_TEXTREL => "textrel ",
+        DW_EH_PE_DATAREL => "datarel ",
+        DW_EH_PE_FUNCREL => "funcrel ",
+        DW_EH_PE_ALIGNED => "aligned ",
+        _ => "",
+    };
+    let indirect = if encoding & DW_EH_PE_INDIRECT != 0 { "indirect " } else { "" };
+    format!("{}{}{}",indirect,application,format)
+}
+
+/// DWARF寄存器编号对应的名字
+pub fn register_name(machine:&MachineType, register:u64) -> String {
+    match machine {
+        MachineType::AMDX86_64 => {
+            const NAMES:[&str;17] = ["rax", "rdx", "rcx", "rbx", "rsi", "rdi", "rbp", "rsp", "r8", "r9", "r10", "r11", "r12", "r13", "r14", "r15", "rip"];
+            match NAMES.get(register as usize) {
+                Some(name) => String::from(*name),
+                None => format!("r{}",register),
+            }
+        }
+        MachineType::AArch64 => match register {
+            0..=30 => format!("x{}",register),
+            31 => String::from("sp"),
+            64..=95 => format!("v{}",register - 64),
+            _ => format!("r{}",register),
+        },
+        _ => format!("r{}",register),
+    }
+}
+
+impl Cie {
+    fn parse(reader:&mut DwarfReader, offset:u64, length:u64, section_address:u64) -> io::Result<Self> {
+        let version = reader.u8()?;
+        let augmentation = reader.cstr()?.to_string();
+        if augmentation.contains("eh") {
+            reader.u64()?;
+        }
+        if version >= 4 {
+            // address_size和segment_selector_size
+            reader.skip(2)?;
+        }
+        let code_alignment_factor = reader.uleb128()?;
+        let data_alignment_factor = reader.sleb128()?;
+        let return_address_register = if version == 1 { reader.u8()? as u64 } else { reader.uleb128()? };
+        let mut cie = Self {
+            offset,
+            length,
+            version,
+            augmentation,
+            code_alignment_factor,
+            data_alignment_factor,
+            return_address_register,
+            augmentation_data:Vec::new(),
+            fde_encoding:DW_EH_PE_ABSPTR,
+            lsda_encoding:DW_EH_PE_OMIT,
+            personality_encoding:DW_EH_PE_OMIT,
+            personality:None,
+            signal_frame:false,
+            instructions_address:0,
+            instructions:Vec::new(),
+        };
+        if cie.augmentation.starts_with('z') {
+            let len = reader.uleb128()?;
+            let data_address = section_address.wrapping_add(reader.position() as u64);
+            let mut data = reader.split(len)?;
+            cie.augmentation_data = data.clone().bytes(data.remaining())?.to_vec();
+            let context = PointerContext { address:data_address, data_base:0 };
+            for c in cie.augmentation.clone().chars().skip(1) {
+                match c {
+                    'L' => cie.lsda_encoding = data.u8()?,
+                    'R' => cie.fde_encoding = data.u8()?,
+                    'P' => {
+                        cie.personality_encoding = data.u8()?;
+                        cie.personality = read_encoded(&mut data, cie.personality_encoding, &context)?;
+                    }
+                    'S' => cie.signal_frame = true,
+                    // 'B'(AArch64 BTI)和'G'(MTE)不带参数
+                    _ => {}
+                }
+            }
+        }
+        cie.instructions_address = section_address.wrapping_add(reader.position() as u64);
+        cie.instructions = reader.bytes(reader.remaining())?.to_vec();
+        Ok(cie)
+    }
+}
+
+impl EhFrame {
+    /// 解析.eh_frame的内容, address为节的虚拟地址
+    pub fn parse(data:&[u8], address:u64, machine:MachineType) -> io::Result<Self> {
+        let mut reader = DwarfReader::new(data);
+        let mut entries = Vec::new();
+        while !reader.is_empty() {
+            let offset = reader.position() as u64;
+            let (length, dwarf64) = reader.initial_length()?;
+            if length == 0 {
+                entries.push(FrameEntry::Terminator(offset));
+                continue;
+            }
+            let id_position = reader.position() as u64;
+            let mut entry = reader.split(length)?;
+            // 子读取器中的位置加上该值即为节内偏移
+            let base = id_position;
+            let id = entry.offset(dwarf64)?;
+            if id == 0 {
+                let mut cie_reader = DwarfReader::new(&data[base as usize..(base + length) as usize]);
+                cie_reader.offset(dwarf64)?;
+                entries.push(FrameEntry::Cie(Cie::parse(&mut cie_reader, offset, length, address.wrapping_add(base))?));
+                continue;
+            }
+            let cie_offset = id_position.checked_sub(id).ok_or_else(|| invalid(format!("FDE at {:#x} has invalid CIE pointer {:#x}", offset, id)))?;
+            let cie = entries.iter().find_map(|entry| match entry {
+                FrameEntry::Cie(cie) if cie.offset == cie_offset => Some(cie),
+                _ => None,
+            }).ok_or_else(|| invalid(format!("FDE at {:#x} refers to missing CIE at {:#x}", offset, cie_offset)))?;
+            let context = PointerContext { address:address.wrapping_add(base), data_base:0 };
+            let pc_begin = read_encoded(&mut entry, cie.fde_encoding, &context)?.unwrap_or(0);
+            // pc_range只使用编码中的数据格式, 不加基址
+            let pc_range = read_encoded(&mut entry, cie.fde_encoding & 0x0f, &context)?.unwrap_or(0);
+            let mut lsda = None;
+            if cie.augmentation.starts_with('z') {
+                let len = entry.uleb128()?;
+                let mut augmentation = entry.clone();
+                if cie.augmentation.contains('L') {
+                    lsda = read_encoded(&mut augmentation, cie.lsda_encoding, &context)?;
+                }
+                entry.skip(usize::try_from(len).unwrap_or(usize::MAX))?;
+            }
+            let instructions_address = address.wrapping_add(base + entry.position() as u64);
+            let instructions = entry.bytes(entry.remaining())?.to_vec();
+            entries.push(FrameEntry::Fde(Fde { offset, length, cie_pointer:id, cie_offset, pc_begin, pc_range, lsda, instructions_address, instructions }));
+        }
+        Ok(Self { address, machine, entries })
+    }
+
+    pub fn cie_at(&self, offset:u64) -> Option<&Cie> {
+        self.entries.iter().find_map(|entry| match entry {
+            FrameEntry::Cie(cie) if cie.offset == offset => Some(cie),
+            _ => None,
+        })
+    }
+
+    pub fn fdes(&self) -> impl Iterator<Item = &Fde> {
+        self.entries.iter().filter_map(|entry| match entry {
+            FrameEntry::Fde(fde) => Some(fde),
+            _ => None,
+        })
+    }
+
+    /// 包含address的FDE
+    pub fn find_fde(&self, address:u64) -> Option<&Fde> {
+        self.fdes().find(|fde| address >= fde.pc_begin && address - fde.pc_begin < fde.pc_range)
+    }
+
+    /// CIE初始指令执行后的规则
+    pub fn cie_rows(&self, cie:&Cie) -> io::Result<Vec<UnwindRow>> {
+        let initial = UnwindRow { address:0, cfa:CfaRule::RegisterOffset(0, 0), registers:BTreeMap::new() };
+        execute(cie, &cie.instructions, cie.instructions_address, initial, None)
+    }
+
+    /// 执行CIE和FDE中的指令, 得到该FDE范围内每个地址区间的展开规则
+    pub fn unwind_rows(&self, fde:&Fde) -> io::Result<Vec<UnwindRow>> {
+        let cie = self.cie_at(fde.cie_offset).ok_or_else(|| invalid(format!("missing CIE at {:#x}", fde.cie_offset)))?;
+        let mut initial = self.cie_rows(cie)?.pop().unwrap_or(UnwindRow { address:0, cfa:CfaRule::RegisterOffset(0, 0), registers:BTreeMap::new() });
+        initial.address = fde.pc_begin;
+        let initial_registers = initial.registers.clone();
+        execute(cie, &fde.instructions, fde.instructions_address, initial, Some(&initial_registers))
+    }
+}
+
+fn read_block(reader:&mut DwarfReader) -> io::Result<Vec<u8>> {
+    let len = reader.uleb128()?;
+    Ok(reader.bytes(usize::try_from(len).unwrap_or(usize::MAX))?.to_vec())
+}
+
+/// 调用帧指令解释器, initial_registers为DW_CFA_restore恢复到的CIE初始规则
+fn execute(cie:&Cie, instructions:&[u8], address:u64, initial:UnwindRow, initial_registers:Option<&BTreeMap<u64, RegisterRule>>) -> io::Result<Vec<UnwindRow>> {
+    let mut reader = DwarfReader::new(instructions);
+    let context = PointerContext { address, data_base:0 };
+    let mut rows:Vec<UnwindRow> = Vec::new();
+    let mut row = initial;
+    let mut stack:Vec<(CfaRule, BTreeMap<u64, RegisterRule>)> = Vec::new();
+    let caf = cie.code_alignment_factor;
+    let daf = cie.data_alignment_factor;
+    let restore = |row:&mut UnwindRow, register:u64| {
+        match initial_registers.and_then(|registers| registers.get(&register)) {
+            Some(rule) => { row.registers.insert(register, rule.clone()); }
+            None => { row.registers.remove(&register); }
+        }
+    };
+    let advance = |row:&mut UnwindRow, rows:&mut Vec<UnwindRow>, new_address:u64| {
+        if rows.last().is_some_and(|last| last.address == row.address) {
+            rows.pop();
+        }
+        rows.push(row.clone());
+        row.address = new_address;
+    };
+    while !reader.is_empty() {
+        let opcode = reader.u8()?;
+        let operand = (opcode & 0x3f) as u64;
+        match opcode & 0xc0 {
+            DW_CFA_ADVANCE_LOC => {
+                let new_address = row.address.wrapping_add(operand.wrapping_mul(caf));
+                advance(&mut row, &mut rows, new_address);
+                continue;
+            }
+            DW_CFA_OFFSET => {
+                let offset = (reader.uleb128()? as i64).wrapping_mul(daf);
+                row.registers.insert(operand, RegisterRule::Offset(offset));
+                continue;
+            }
+            DW_CFA_RESTORE => {
+                restore(&mut row, operand);
+                continue;
+            }
+            _ => {}
+        }
+        match opcode {
+            DW_CFA_NOP => {}
+            DW_CFA_SET_LOC => {
+                let new_address = read_encoded(&mut reader, cie.fde_encoding, &context)?.unwrap_or(row.address);
+                advance(&mut row, &mut rows, new_address);
+            }
+            DW_CFA_ADVANCE_LOC1 | DW_CFA_ADVANCE_LOC2 | DW_CFA_ADVANCE_LOC4 => {
+                let delta = match opcode {
+                    DW_CFA_ADVANCE_LOC1 => reader.u8()? as u64,
+                    DW_CFA_ADVANCE_LOC2 => reader.u16()? as u64,
+                    _ => reader.u32()? as u64,
+                };
+                let new_address = row.address.wrapping_add(delta.wrapping_mul(caf));
+                advance(&mut row, &mut rows, new_address);
+            }
+            DW_CFA_OFFSET_EXTENDED => {
+                let register = reader.uleb128()?;
+                let offset = (reader.uleb128()? as i64).wrapping_mul(daf);
+                row.registers.insert(register, RegisterRule::Offset(offset));
+            }
+            DW_CFA_OFFSET_EXTENDED_SF => {
+                let register = reader.uleb128()?;
+                let offset = reader.sleb128()?.wrapping_mul(daf);
+                row.registers.insert(register, RegisterRule::Offset(offset));
+            }
+            DW_CFA_GNU_NEGATIVE_OFFSET_EXTENDED => {
+                let register = reader.uleb128()?;
+                let offset = (reader.uleb128()? as i64).wrapping_neg().wrapping_mul(daf);
+                row.registers.insert(register, RegisterRule::Offset(offset));
+            }
+            DW_CFA_VAL_OFFSET => {
+                let register = reader.uleb128()?;
+                let offset = (reader.uleb128()? as i64).wrapping_mul(daf);
+                row.registers.insert(register, RegisterRule::ValOffset(offset));
+            }
+            DW_CFA_VAL_OFFSET_SF => {
+                let register = reader.uleb128()?;
+                let offset = reader.sleb128()?.wrapping_mul(daf);
+                row.registers.insert(register, RegisterRule::ValOffset(offset));
+            }
+            DW_CFA_RESTORE_EXTENDED => {
+                let register = reader.uleb128()?;
+                restore(&mut row, register);
+            }
+            DW_CFA_UNDEFINED => {
+                let register = reader.uleb128()?;
+                row.registers.insert(register, RegisterRule::Undefined);
+            }
+            DW_CFA_SAME_VALUE => {
+                let register = reader.uleb128()?;
+                row.registers.insert(register, RegisterRule::SameValue);
+            }
+            DW_CFA_REGISTER => {
+                let register = reader.uleb128()?;
+                let other = reader.uleb128()?;
+                row.registers.insert(register, RegisterRule::Register(other));
+            }
+            DW_CFA_REMEMBER_STATE => stack.push((row.cfa.clone(), row.registers.clone())),
+            DW_CFA_RESTORE_STATE => {
+                let (cfa, registers) = stack.pop().ok_or_else(|| invalid(String::from("DW_CFA_restore_state without DW_CFA_remember_state")))?;
+                row.cfa = cfa;
+                row.registers = registers;
+            }
+            DW_CFA_DEF_CFA => {
+                let register = reader.uleb128()?;
+                let offset = reader.uleb128()? as i64;
+                row.cfa = CfaRule::RegisterOffset(register, offset);
+            }
+            DW_CFA_DEF_CFA_SF => {
+                let register = reader.uleb128()?;
+                let offset = reader.sleb128()?.wrapping_mul(daf);
+                row.cfa = CfaRule::RegisterOffset(register, offset);
+            }
+            DW_CFA_DEF_CFA_REGISTER => {
+                let register = reader.uleb128()?;
+                let offset = match row.cfa { CfaRule::RegisterOffset(_, offset) => offset, CfaRule::Expression(_) => 0 };
+                row.cfa = CfaRule::RegisterOffset(register, offset);
+            }
+            DW_CFA_DEF_CFA_OFFSET | DW_CFA_DEF_CFA_OFFSET_SF => {
+                let offset = if opcode == DW_CFA_DEF_CFA_OFFSET { reader.uleb128()? as i64 } else { reader.sleb128()?.wrapping_mul(daf) };
+                let register = match row.cfa { CfaRule::RegisterOffset(register, _) => register, CfaRule::Expression(_) => 0 };
+                row.cfa = CfaRule::RegisterOffset(register, offset);
+            }
+            DW_CFA_DEF_CFA_EXPRESSION => row.cfa = CfaRule::Expression(read_block(&mut reader)?),
+            DW_CFA_EXPRESSION | DW_CFA_VAL_EXPRESSION => {
+                let register = reader.uleb128()?;
+                let expression = read_block(&mut reader)?;
+                let rule = if opcode == DW_CFA_EXPRESSION { RegisterRule::Expression(expression) } else { RegisterRule::ValExpression(expression) };
+                row.registers.insert(register, rule);
+            }
+            DW_CFA_GNU_ARGS_SIZE => {
+                reader.uleb128()?;
+            }
+            DW_CFA_GNU_WINDOW_SAVE => {}
+            _ => return Err(invalid(format!("unknown call frame instruction {:#x}", opcode))),
+        }
+    }
+    if rows.last().is_some_and(|last| last.address == row.address) {
+        rows.pop();
+    }
+    rows.push(row);
+    Ok(rows)
+}
+
+impl EhFrameHdr {
+    /// 解析.eh_frame_hdr的内容, address为节的虚拟地址
+    pub fn parse(data:&[u8], address:u64) -> io::Result<Self> {
+        let mut reader = DwarfReader::new(data);
+        let version = reader.u8()?;
+        if version != 1 {
+            return Err(invalid(format!("unsupported .eh_frame_hdr version {}", version)));
+        }
+        let eh_frame_ptr_encoding = reader.u8()?;
+        let fde_count_encoding = reader.u8()?;
+        let table_encoding = reader.u8()?;
+        let context = PointerContext { address, data_base:address };
+        let eh_frame_ptr = read_encoded(&mut reader, eh_frame_ptr_encoding, &context)?;
+        let fde_count = read_encoded(&mut reader, fde_count_encoding, &context)?.unwrap_or(0);
+        let mut table = Vec::new();
+        if table_encoding != DW_EH_PE_OMIT {
+            for _ in 0..fde_count {
+                let initial_location = read_encoded(&mut reader, table_encoding, &context)?.unwrap_or(0);
+                let fde_address = read_encoded(&mut reader, table_encoding, &context)?.unwrap_or(0);
+                table.push((initial_location, fde_address));
+            }
+        }
+        Ok(Self { address, version, eh_frame_ptr_encoding, fde_count_encoding, table_encoding, eh_frame_ptr, fde_count, table })
+    }
+
+    /// 二分查找包含address的表项, 返回FDE的地址, 与运行时展开器的查找方式一致
+    pub fn lookup(&self, address:u64) -> Option<u64> {
+        let index = self.table.partition_point(|&(location, _)| location <= address);
+        index.checked_sub(1).map(|index| self.table[index].1)
+    }
+}
+
+fn cfa_to_string(machine:&MachineType, cfa:&CfaRule) -> String {
+    match cfa {
+        CfaRule::RegisterOffset(register, offset) => format!("{}{:+}",register_name(machine, *register),offset),
+        CfaRule::Expression(_) => String::from("exp"),
+    }
+}
+
+fn rule_to_string(machine:&MachineType, rule:&RegisterRule) -> String {
+    match rule {
+        RegisterRule::Undefined => String::from("u"),
+        RegisterRule::SameValue => String::from("s"),
+        RegisterRule::Offset(offset) => format!("c{:+}",offset),
+        RegisterRule::ValOffset(offset) => format!("v{:+}",offset),
+        RegisterRule::Register(register) => register_name(machine, *register),
+        RegisterRule::Expression(_) => String::from("exp"),
+        RegisterRule::ValExpression(_) => String::from("vexp"),
+    }
+}
+
+impl EhFrame {
+    fn write_rows(&self, f:&mut Formatter<'_>, rows:&[UnwindRow], return_address_register:u64) -> std::fmt::Result {
+        // 所有行中出现过的寄存器作为列, 返回地址寄存器放在最后
+        let mut registers:Vec<u64> = rows.iter().flat_map(|row| row.registers.keys().copied()).collect();
+        registers.sort();
+        registers.dedup();
+        registers.retain(|&register| register != return_address_register);
+        registers.push(return_address_register);
+        write!(f,"   LOC           CFA      ")?;
+        for &register in &registers {
+            let name = if register == return_address_register { String::from("ra") } else { register_name(&self.machine, register) };
+            write!(f,"{:<6}",name)?;
+        }
+        writeln!(f)?;
+        for row in rows {
+            write!(f,"{:016x} {:<9}",row.address,cfa_to_string(&self.machine, &row.cfa))?;
+            for register in &registers {
+                let rule = row.registers.get(register).map(|rule| rule_to_string(&self.machine, rule)).unwrap_or_else(|| String::from("u"));
+                write!(f,"{:<6}",rule)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for EhFrame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.entries {
+            match entry {
+                FrameEntry::Cie(cie) => {
+                    write!(f,"{:08x} {:016x} {:08x} CIE \"{}\" cf={} df={} ra={}",cie.offset,cie.length,0,cie.augmentation,cie.code_alignment_factor,cie.data_alignment_factor,cie.return_address_register)?;
+                    if let Some(personality) = cie.personality {
+                        write!(f," personality={:#x} ({})",personality,encoding_to_string(cie.personality_encoding))?;
+                    }
+                    if cie.lsda_encoding != DW_EH_PE_OMIT {
+                        write!(f," lsda_enc={}",encoding_to_string(cie.lsda_encoding))?;
+                    }
+                    if cie.augmentation.contains('R') {
+                        write!(f," fde_enc={}",encoding_to_string(cie.fde_encoding))?;
+                    }
+                    if cie.signal_frame {
+                        write!(f," signal_frame")?;
+                    }
+                    writeln!(f)?;
+                    match self.cie_rows(cie) {
+                        Ok(rows) => self.write_rows(f, &rows, cie.return_address_register)?,
+                        Err(e) => writeln!(f,"  <error: {}>",e)?,
+                    }
+                }
+                FrameEntry::Fde(fde) => {
+                    write!(f,"{:08x} {:016x} {:08x} FDE cie={:08x} pc={:016x}..{:016x}",fde.offset,fde.length,fde.cie_pointer,fde.cie_offset,fde.pc_begin,fde.pc_begin.wrapping_add(fde.pc_range))?;
+                    if let Some(lsda) = fde.lsda {
+                        write!(f," lsda={:#x}",lsda)?;
+                    }
+                    writeln!(f)?;
+                    let return_address_register = self.cie_at(fde.cie_offset).map(|cie| cie.return_address_register).unwrap_or(0);
+                    match self.unwind_rows(fde) {
+                        Ok(rows) => self.write_rows(f, &rows, return_address_register)?,
+                        Err(e) => writeln!(f,"  <error: {}>",e)?,
+                    }
+                }
+                FrameEntry::Terminator(offset) => writeln!(f,"{:08x} ZERO terminator",offset)?,
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for EhFrameHdr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f,"  Version:               {}",self.version)?;
+        writeln!(f,"  eh_frame_ptr encoding: {:#x} ({})",self.eh_frame_ptr_encoding,encoding_to_string(self.eh_frame_ptr_encoding))?;
+        if let Some(eh_frame_ptr) = self.eh_frame_ptr {
+            writeln!(f,"  eh_frame_ptr:          {:#x}",eh_frame_ptr)?;
+        }
+        writeln!(f,"  fde_count encoding:    {:#x} ({})",self.fde_count_encoding,encoding_to_string(self.fde_count_encoding))?;
+        writeln!(f,"  table encoding:        {:#x} ({})",self.table_encoding,encoding_to_string(self.table_encoding))?;
+        writeln!(f,"  FDE count:             {}",self.fde_count)?;
+        writeln!(f,"  {:<18} {:<18}","Initial location","FDE address")?;
+        for (location, fde) in &self.table {
+            writeln!(f,"  {:<#18x} {:<#18x}",location,fde)?;
+        }
+        Ok(())
+    }
+}
+
+/// 检查.eh_frame_hdr的查找表: 是否有序, 每项是否指向初始地址相同的FDE
+pub fn check_eh_frame_hdr(hdr:&EhFrameHdr, eh_frame:&EhFrame) -> Vec<String> {
+    let mut problems = Vec::new();
+    if hdr.eh_frame_ptr.is_some_and(|ptr| ptr != eh_frame.address) {
+        problems.push(format!("eh_frame_ptr {:#x} does not point to .eh_frame at {:#x}", hdr.eh_frame_ptr.unwrap(), eh_frame.address));
+    }
+    if hdr.table.windows(2).any(|pair| pair[0].0 > pair[1].0) {
+        problems.push(String::from("search table is not sorted by initial location"));
+    }
+    // 搜索表的每一项都要按偏移找FDE, 先建立一次索引
+    let fdes:BTreeMap<u64, &Fde> = eh_frame.fdes().map(|fde| (fde.offset, fde)).collect();
+    for &(location, fde_address) in &hdr.table {
+        let offset = fde_address.wrapping_sub(eh_frame.address);
+        match fdes.get(&offset) {
+            Some(fde) if fde.pc_begin == location => {}
+            Some(fde) => problems.push(format!("entry {:#x} points to FDE at {:#x} which starts at {:#x}", location, fde_address, fde.pc_begin)),
+            None => problems.push(format!("entry {:#x} points to {:#x} which is not an FDE", location, fde_address)),
+        }
+    }
+    let fde_count = fdes.len() as u64;
+    if hdr.fde_count != fde_count {
+        problems.push(format!("fde_count is {} but .eh_frame has {} FDEs", hdr.fde_count, fde_count));
+    }
+    problems
+}
+
+/// 读取并解析.eh_frame, 文件没有该节时返回None
+pub fn parse_eh_frame<R:Read + Seek>(elf:&mut R) -> io::Result<Option<EhFrame>> {
+    let header = parse_header(elf)?;
+    let sections = parse_sections(elf)?;
+    match find_section(&sections, ".eh_frame") {
+        Some(section) => {
+            let data = read_section_data(elf, section)?;
+            Ok(Some(EhFrame::parse(&data, section.sh_addr, header.e_machine)?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// 读取并解析.eh_frame_hdr, 文件没有该节时返回None
+pub fn parse_eh_frame_hdr<R:Read + Seek>(elf:&mut R) -> io::Result<Option<EhFrameHdr>> {
+    let sections = parse_sections(elf)?;
+    match find_section(&sections, ".eh_frame_hdr") {
+        Some(section) => {
+            let data = read_section_data(elf, section)?;
+            Ok(Some(EhFrameHdr::parse(&data, section.sh_addr)?))
+        }
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,491 @@
+use std::collections::HashMap;
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::dwarf::abbrev::{parse_abbrevs, Abbreviation};
+use crate::dwarf::constants::*;
+use crate::dwarf::reader::{str_at, DwarfReader};
+use crate::dwarf::sections::DwarfSections;
+
+/// 属性值, 间接形式(strx, addrx, rnglistx, loclistx)同时保存索引和解析后的结果
+pub enum AttributeValue {
+    Address(u64),
+    AddressIndex(u64, Option<u64>),
+    Block(Vec<u8>),
+    Exprloc(Vec<u8>),
+    Udata(u64),
+    Sdata(i64),
+    Data16([u8;16]),
+    Flag(bool),
+    String(String),
+    /// DW_FORM_strp/DW_FORM_line_strp: 字符串节中的偏移和字符串
+    StringOffset(u64, String),
+    StringIndex(u64, Option<String>),
+    /// 指向.debug_info中的DIE, 已换算为节内偏移
+    Reference(u64),
+    TypeSignature(u64),
+    SecOffset(u64),
+    RangeListIndex(u64, Option<u64>),
+    LocationListIndex(u64, Option<u64>),
+    /// 指向补充目标文件(.gnu_debugaltlink)的偏移
+    SupOffset(u64),
+}
+
+pub struct Attribute {
+    /// 属性在.debug_info中的偏移
+    pub offset:u64,
+    pub name:u64,
+    pub form:u64,
+    pub value:AttributeValue,
+}
+
+/// 调试信息条目(DIE)
+pub struct Die {
+    pub offset:u64,
+    pub depth:usize,
+    pub abbrev_code:u64,
+    pub tag:u64,
+    pub attributes:Vec<Attribute>,
+    pub children:Vec<Die>,
+}
+
+/// .debug_info中的一个单元, root为单元的顶层DIE(如DW_TAG_compile_unit)
+pub struct CompilationUnit {
+    pub offset:u64,
+    pub unit_length:u64,
+    pub dwarf64:bool,
+    pub version:u16,
+    pub unit_type:u8,
+    pub abbrev_offset:u64,
+    pub address_size:u8,
+    pub dwo_id:Option<u64>,
+    pub type_signature:Option<u64>,
+    pub type_offset:Option<u64>,
+    /// 由顶层DIE的DW_AT_*_base属性确定的各个索引表基址
+    pub str_offsets_base:u64,
+    pub addr_base:u64,
+    pub rnglists_base:u64,
+    pub loclists_base:u64,
+    pub root:Option<Die>,
+}
+
+impl AttributeValue {
+    /// 以无符号整数读取属性值, 间接地址和范围列表返回解析后的偏移
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            AttributeValue::Address(value) | AttributeValue::Udata(value) | AttributeValue::Reference(value) | AttributeValue::SecOffset(value) | AttributeValue::SupOffset(value) => Some(*value),
+            AttributeValue::Sdata(value) => Some(*value as u64),
+            AttributeValue::Flag(value) => Some(*value as u64),
+            AttributeValue::AddressIndex(_, value) | AttributeValue::RangeListIndex(_, value) | AttributeValue::LocationListIndex(_, value) => *value,
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AttributeValue::String(value) | AttributeValue::StringOffset(_, value) => Some(value),
+            AttributeValue::StringIndex(_, value) => value.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl Die {
+    pub fn attr(&self, name:u64) -> Option<&AttributeValue> {
+        self.attributes.iter().find(|attr| attr.name == name).map(|attr| &attr.value)
+    }
+
+    pub fn attr_str(&self, name:u64) -> Option<&str> {
+        self.attr(name).and_then(|value| value.as_str())
+    }
+
+    pub fn attr_u64(&self, name:u64) -> Option<u64> {
+        self.attr(name).and_then(|value| value.as_u64())
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.attr_str(DW_AT_NAME)
+    }
+
+    /// 按先序遍历当前DIE及其所有子孙
+    pub fn walk(&self) -> Vec<&Die> {
+        let mut result = Vec::new();
+        let mut pending = vec![self];
+        while let Some(die) = pending.pop() {
+            result.push(die);
+            pending.extend(die.children.iter().rev());
+        }
+        result
+    }
+}
+
+/// 默认的drop逐层递归释放children, 畸形文件中每层只占一个字节的深层嵌套会导致栈溢出, 因此先把子孙移到worklist中再释放
+impl Drop for Die {
+    fn drop(&mut self) {
+        let mut pending = std::mem::take(&mut self.children);
+        while let Some(mut die) = pending.pop() {
+            pending.append(&mut die.children);
+        }
+    }
+}
+
+/// 解析属性时需要的单元信息
+struct UnitContext {
+    offset:u64,
+    version:u16,
+    dwarf64:bool,
+    address_size:u8,
+}
+
+fn read_block(reader:&mut DwarfReader, len:u64) -> io::Result<Vec<u8>> {
+    Ok(reader.bytes(usize::try_from(len).unwrap_or(usize::MAX))?.to_vec())
+}
+
+fn read_value(reader:&mut DwarfReader, form:u64, implicit_const:Option<i64>, unit:&UnitContext, sections:&DwarfSections) -> io::Result<AttributeValue> {
+    let value = match form {
+        DW_FORM_ADDR => AttributeValue::Address(reader.uint(unit.address_size)?),
+        DW_FORM_BLOCK1 => { let len = reader.u8()? as u64; AttributeValue::Block(read_block(reader, len)?) }
+        DW_FORM_BLOCK2 => { let len = reader.u16()? as u64; AttributeValue::Block(read_block(reader, len)?) }
+        DW_FORM_BLOCK4 => { let len = reader.u32()? as u64; AttributeValue::Block(read_block(reader, len)?) }
+        DW_FORM_BLOCK => { let len = reader.uleb128()?; AttributeValue::Block(read_block(reader, len)?) }
+        DW_FORM_EXPRLOC => { let len = reader.uleb128()?; AttributeValue::Exprloc(read_block(reader, len)?) }
+        DW_FORM_DATA1 => AttributeValue::Udata(reader.u8()? as u64),
+        DW_FORM_DATA2 => AttributeValue::Udata(reader.u16()? as u64),
+        DW_FORM_DATA4 => AttributeValue::Udata(reader.u32()? as u64),
+        DW_FORM_DATA8 => AttributeValue::Udata(reader.u64()?),
+        DW_FORM_DATA16 => AttributeValue::Data16(reader.bytes(16)?.try_into().unwrap()),
+        DW_FORM_UDATA => AttributeValue::Udata(reader.uleb128()?),
+        DW_FORM_SDATA => AttributeValue::Sdata(reader.sleb128()?),
+        DW_FORM_IMPLICIT_CONST => AttributeValue::Sdata(implicit_const.unwrap_or(0)),
+        DW_FORM_FLAG => AttributeValue::Flag(reader.u8()? != 0),
+        DW_FORM_FLAG_PRESENT => AttributeValue::Flag(true),
+        DW_FORM_STRING => AttributeValue::String(reader.cstr()?.to_string()),
+        DW_FORM_STRP => {
+            let offset = reader.offset(unit.dwarf64)?;
+            AttributeValue::StringOffset(offset, str_at(&sections.debug_str, offset)?.to_string())
+        }
+        DW_FORM_LINE_STRP => {
+            let offset = reader.offset(unit.dwarf64)?;
+            AttributeValue::StringOffset(offset, str_at(&sections.debug_line_str, offset)?.to_string())
+        }
+        DW_FORM_STRP_SUP | DW_FORM_GNU_STRP_ALT | DW_FORM_GNU_REF_ALT => AttributeValue::SupOffset(reader.offset(unit.dwarf64)?),
+        DW_FORM_STRX | DW_FORM_GNU_STR_INDEX => AttributeValue::StringIndex(reader.uleb128()?, None),
+        DW_FORM_STRX1 => AttributeValue::StringIndex(reader.u8()? as u64, None),
+        DW_FORM_STRX2 => AttributeValue::StringIndex(reader.u16()? as u64, None),
+        DW_FORM_STRX3 => AttributeValue::StringIndex(reader.u24()? as u64, None),
+        DW_FORM_STRX4 => AttributeValue::StringIndex(reader.u32()? as u64, None),
+        DW_FORM_ADDRX | DW_FORM_GNU_ADDR_INDEX => AttributeValue::AddressIndex(reader.uleb128()?, None),
+        DW_FORM_ADDRX1 => AttributeValue::AddressIndex(reader.u8()? as u64, None),
+        DW_FORM_ADDRX2 => AttributeValue::AddressIndex(reader.u16()? as u64, None),
+        DW_FORM_ADDRX3 => AttributeValue::AddressIndex(reader.u24()? as u64, None),
+        DW_FORM_ADDRX4 => AttributeValue::AddressIndex(reader.u32()? as u64, None),
+        DW_FORM_REF1 => AttributeValue::Reference(unit.offset + reader.u8()? as u64),
+        DW_FORM_REF2 => AttributeValue::Reference(unit.offset + reader.u16()? as u64),
+        DW_FORM_REF4 => AttributeValue::Reference(unit.offset + reader.u32()? as u64),
+        DW_FORM_REF8 => AttributeValue::Reference(unit.offset.wrapping_add(reader.u64()?)),
+        DW_FORM_REF_UDATA => AttributeValue::Reference(unit.offset.wrapping_add(reader.uleb128()?)),
+        DW_FORM_REF_ADDR => {
+            // DWARF 2中ref_addr的长度等于地址长度
+            if unit.version <= 2 {
+                AttributeValue::Reference(reader.uint(unit.address_size)?)
+            } else {
+                AttributeValue::Reference(reader.offset(unit.dwarf64)?)
+            }
+        }
+        DW_FORM_REF_SUP4 => AttributeValue::SupOffset(reader.u32()? as u64),
+        DW_FORM_REF_SUP8 => AttributeValue::SupOffset(reader.u64()?),
+        DW_FORM_REF_SIG8 => AttributeValue::TypeSignature(reader.u64()?),
+        DW_FORM_SEC_OFFSET => AttributeValue::SecOffset(reader.offset(unit.dwarf64)?),
+        DW_FORM_LOCLISTX => AttributeValue::LocationListIndex(reader.uleb128()?, None),
+        DW_FORM_RNGLISTX => AttributeValue::RangeListIndex(reader.uleb128()?, None),
+        DW_FORM_INDIRECT => {
+            let form = reader.uleb128()?;
+            return read_value(reader, form, implicit_const, unit, sections);
+        }
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported attribute form {:#x}", form))),
+    };
+    Ok(value)
+}
+
+fn parse_die(reader:&mut DwarfReader, abbrev:&Abbreviation, offset:u64, depth:usize, unit:&UnitContext, sections:&DwarfSections) -> io::Result<Die> {
+    let mut attributes = Vec::with_capacity(abbrev.attributes.len());
+    for spec in &abbrev.attributes {
+        let attr_offset = unit.offset + reader.position() as u64;
+        let value = read_value(reader, spec.form, spec.implicit_const, unit, sections)?;
+        attributes.push(Attribute { offset:attr_offset, name:spec.name, form:spec.form, value });
+    }
+    Ok(Die { offset, depth, abbrev_code:abbrev.code, tag:abbrev.tag, attributes, children:Vec::new() })
+}
+
+/// 从字符串偏移表/地址表等"基址+索引"结构中读取一项
+fn read_indexed(data:&[u8], base:u64, index:u64, size:u8) -> Option<u64> {
+    let pos = index.checked_mul(size as u64)?.checked_add(base)?;
+    let mut reader = DwarfReader::new(data);
+    reader.seek(usize::try_from(pos).ok()?).ok()?;
+    reader.uint(size).ok()
+}
+
+impl CompilationUnit {
+    fn parse(reader:&mut DwarfReader, sections:&DwarfSections) -> io::Result<Self> {
+        let offset = reader.position() as u64;
+        let (unit_length, dwarf64) = reader.clone().initial_length()?;
+        let length_size:u64 = if dwarf64 { 12 } else { 4 };
+        let mut unit_reader = reader.split(length_size.saturating_add(unit_length))?;
+        unit_reader.initial_length()?;
+        let version = unit_reader.u16()?;
+        if !(2..=5).contains(&version) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported .debug_info version {} at offset {:#x}", version, offset)));
+        }
+        let mut unit = Self {
+            offset,
+            unit_length,
+            dwarf64,
+            version,
+            unit_type:DW_UT_COMPILE,
+            abbrev_offset:0,
+            address_size:0,
+            dwo_id:None,
+            type_signature:None,
+            type_offset:None,
+            str_offsets_base:0,
+            addr_base:0,
+            rnglists_base:0,
+            loclists_base:0,
+            root:None,
+        };
+        if version >= 5 {
+            unit.unit_type = unit_reader.u8()?;
+            unit.address_size = unit_reader.u8()?;
+            unit.abbrev_offset = unit_reader.offset(dwarf64)?;
+            match unit.unit_type {
+                DW_UT_SKELETON | DW_UT_SPLIT_COMPILE => unit.dwo_id = Some(unit_reader.u64()?),
+                DW_UT_TYPE | DW_UT_SPLIT_TYPE => {
+                    unit.type_signature = Some(unit_reader.u64()?);
+                    unit.type_offset = Some(unit_reader.offset(dwarf64)?);
+                }
+                _ => {}
+            }
+        } else {
+            unit.abbrev_offset = unit_reader.offset(dwarf64)?;
+            unit.address_size = unit_reader.u8()?;
+        }
+        let abbrevs = parse_abbrevs(&sections.debug_abbrev, unit.abbrev_offset)?;
+        unit.root = unit.parse_dies(&mut unit_reader, &abbrevs, sections)?;
+        unit.resolve_indexes(sections);
+        Ok(unit)
+    }
+
+    fn parse_dies(&self, reader:&mut DwarfReader, abbrevs:&HashMap<u64, Abbreviation>, sections:&DwarfSections) -> io::Result<Option<Die>> {
+        let context = UnitContext { offset:self.offset, version:self.version, dwarf64:self.dwarf64, address_size:self.address_size };
+        let mut roots:Vec<Die> = Vec::new();
+        // 尚未读完子节点的父DIE
+        let mut parents:Vec<Die> = Vec::new();
+        fn attach(die:Die, parents:&mut [Die], roots:&mut Vec<Die>) {
+            match parents.last_mut() {
+                Some(parent) => parent.children.push(die),
+                None => roots.push(die),
+            }
+        }
+        while !reader.is_empty() {
+            let offset = self.offset + reader.position() as u64;
+            let code = reader.uleb128()?;
+            if code == 0 {
+                if let Some(die) = parents.pop() {
+                    attach(die, &mut parents, &mut roots);
+                }
+                continue;
+            }
+            let abbrev = abbrevs.get(&code).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("unknown abbrev code {} at offset {:#x}", code, offset))
+            })?;
+            let die = parse_die(reader, abbrev, offset, parents.len(), &context, sections)?;
+            if abbrev.has_children {
+                parents.push(die);
+            } else {
+                attach(die, &mut parents, &mut roots);
+            }
+        }
+        while let Some(die) = parents.pop() {
+            attach(die, &mut parents, &mut roots);
+        }
+        Ok(roots.into_iter().next())
+    }
+
+    /// 根据顶层DIE中的各个base属性解析strx/addrx/rnglistx/loclistx
+    fn resolve_indexes(&mut self, sections:&DwarfSections) {
+        let offset_size = if self.dwarf64 { 8 } else { 4 };
+        let Some(root) = self.root.as_mut() else { return };
+        // DWARF 5的各类偏移表带有头部, 缺少base属性时默认跳过头部
+        let default_base = if self.version >= 5 { offset_size as u64 * 2 } else { 0 };
+        let str_offsets_base = root.attr_u64(DW_AT_STR_OFFSETS_BASE).unwrap_or(default_base);
+        let addr_base = root.attr_u64(DW_AT_ADDR_BASE).or_else(|| root.attr_u64(DW_AT_GNU_ADDR_BASE)).unwrap_or(default_base);
+        let rnglists_base = root.attr_u64(DW_AT_RNGLISTS_BASE).unwrap_or(default_base + 4);
+        let loclists_base = root.attr_u64(DW_AT_LOCLISTS_BASE).unwrap_or(default_base + 4);
+        self.str_offsets_base = str_offsets_base;
+        self.addr_base = addr_base;
+        self.rnglists_base = rnglists_base;
+        self.loclists_base = loclists_base;
+        let address_size = self.address_size;
+        let mut pending = vec![root];
+        while let Some(die) = pending.pop() {
+            for attr in &mut die.attributes {
+                match &mut attr.value {
+                    AttributeValue::StringIndex(index, value) => {
+                        *value = read_indexed(&sections.debug_str_offsets, str_offsets_base, *index, offset_size)
+                            .and_then(|offset| str_at(&sections.debug_str, offset).ok())
+                            .map(|s| s.to_string());
+                    }
+                    AttributeValue::AddressIndex(index, value) => {
+                        *value = read_indexed(&sections.debug_addr, addr_base, *index, address_size);
+                    }
+                    AttributeValue::RangeListIndex(index, value) => {
+                        *value = read_indexed(&sections.debug_rnglists, rnglists_base, *index, offset_size)
+                            .and_then(|offset| offset.checked_add(rnglists_base));
+                    }
+                    AttributeValue::LocationListIndex(index, value) => {
+                        *value = read_indexed(&sections.debug_loclists, loclists_base, *index, offset_size)
+                            .and_then(|offset| offset.checked_add(loclists_base));
+                    }
+                    _ => {}
+                }
+            }
+            pending.extend(die.children.iter_mut());
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.root.as_ref()?.name()
+    }
+
+    /// 单元在.debug_info中占用的字节数, 包括initial length本身
+    pub fn size(&self) -> u64 {
+        self.unit_length + if self.dwarf64 { 12 } else { 4 }
+    }
+
+    /// 读取.debug_addr中第index个地址
+    pub fn address_at(&self, sections:&DwarfSections, index:u64) -> Option<u64> {
+        read_indexed(&sections.debug_addr, self.addr_base, index, self.address_size)
+    }
+
+    /// 编译器及编译选项, 如"GNU C17 12.2.0 -O2 -g"
+    pub fn producer(&self) -> Option<&str> {
+        self.root.as_ref()?.attr_str(DW_AT_PRODUCER)
+    }
+
+    pub fn comp_dir(&self) -> Option<&str> {
+        self.root.as_ref()?.attr_str(DW_AT_COMP_DIR)
+    }
+
+    pub fn language(&self) -> Option<u64> {
+        self.root.as_ref()?.attr_u64(DW_AT_LANGUAGE)
+    }
+
+    /// 按偏移查找该单元内的DIE
+    pub fn die_at(&self, offset:u64) -> Option<&Die> {
+        self.root.as_ref()?.walk().into_iter().find(|die| die.offset == offset)
+    }
+}
+
+fn write_bytes(f:&mut Formatter<'_>, bytes:&[u8]) -> std::fmt::Result {
+    for byte in bytes {
+        write!(f,"{:x} ",byte)?;
+    }
+    Ok(())
+}
+
+/// 与readelf一致, 0不带0x前缀
+fn hex(value:u64) -> String {
+    if value == 0 { String::from("0") } else { format!("{:#x}",value) }
+}
+
+impl std::fmt::Display for Attribute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f,"    <{:x}>   {:<18}: ",self.offset,attr_name(self.name))?;
+        match &self.value {
+            AttributeValue::Address(value) => write!(f,"{}",hex(*value))?,
+            AttributeValue::AddressIndex(index, value) => match value {
+                Some(value) => write!(f,"(index: {:#x}): {:#x}",index,value)?,
+                None => write!(f,"(index: {:#x}): <no .debug_addr>",index)?,
+            },
+            AttributeValue::Block(bytes) | AttributeValue::Exprloc(bytes) => {
+                write!(f,"{} byte block: ",bytes.len())?;
+                write_bytes(f, bytes)?;
+            }
+            AttributeValue::Udata(value) => match self.name {
+                DW_AT_HIGH_PC => write!(f,"{}",hex(*value))?,
+                DW_AT_LANGUAGE => write!(f,"{}\t({})",value,language_name(*value).unwrap_or("Unknown"))?,
+                DW_AT_ENCODING => write!(f,"{}\t({})",value,encoding_name(*value).unwrap_or("unknown"))?,
+                _ => write!(f,"{}",value)?,
+            },
+            AttributeValue::Sdata(value) => write!(f,"{}",value)?,
+            AttributeValue::Data16(bytes) => write_bytes(f, bytes)?,
+            AttributeValue::Flag(value) => write!(f,"{}",*value as u8)?,
+            AttributeValue::String(value) => write!(f,"{}",value)?,
+            AttributeValue::StringOffset(offset, value) => {
+                let kind = if self.form == DW_FORM_LINE_STRP { "indirect line string" } else { "indirect string" };
+                write!(f,"({}, offset: {}): {}",kind,hex(*offset),value)?
+            }
+            AttributeValue::StringIndex(index, value) => {
+                write!(f,"(indexed string: {:#x}): {}",index,value.as_deref().unwrap_or("<no .debug_str_offsets>"))?
+            }
+            AttributeValue::Reference(offset) => write!(f,"<{:#x}>",offset)?,
+            AttributeValue::TypeSignature(signature) => write!(f,"signature: {:#x}",signature)?,
+            AttributeValue::SecOffset(offset) => match self.name {
+                DW_AT_LOCATION | DW_AT_FRAME_BASE => write!(f,"{} (location list)",hex(*offset))?,
+                _ => write!(f,"{}",hex(*offset))?,
+            },
+            AttributeValue::RangeListIndex(index, offset) | AttributeValue::LocationListIndex(index, offset) => match offset {
+                Some(offset) => write!(f,"(index: {:#x}): {:#x}",index,offset)?,
+                None => write!(f,"(index: {:#x})",index)?,
+            },
+            AttributeValue::SupOffset(offset) => write!(f,"(alt indirect, offset: {:#x})",offset)?,
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for CompilationUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f,"  Compilation Unit @ offset {}:",hex(self.offset))?;
+        writeln!(f,"   Length:        {:#x} ({})",self.unit_length,if self.dwarf64 {"64-bit"} else {"32-bit"})?;
+        writeln!(f,"   Version:       {}",self.version)?;
+        if self.version >= 5 {
+            writeln!(f,"   Unit Type:     {} ({})",unit_type_name(self.unit_type),self.unit_type)?;
+        }
+        writeln!(f,"   Abbrev Offset: {}",hex(self.abbrev_offset))?;
+        writeln!(f,"   Pointer Size:  {}",self.address_size)?;
+        if let Some(dwo_id) = self.dwo_id {
+            writeln!(f,"   DWO ID:        {:#x}",dwo_id)?;
+        }
+        if let Some(signature) = self.type_signature {
+            writeln!(f,"   Signature:     {:#x}",signature)?;
+        }
+        if let Some(type_offset) = self.type_offset {
+            writeln!(f,"   Type Offset:   {:#x}",type_offset)?;
+        }
+        if let Some(root) = &self.root {
+            for die in root.walk() {
+                writeln!(f," <{}><{:x}>: Abbrev Number: {} ({})",die.depth,die.offset,die.abbrev_code,tag_name(die.tag))?;
+                for attr in &die.attributes {
+                    writeln!(f,"{}",attr)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 解析.debug_info中的所有单元
+pub fn parse_units(sections:&DwarfSections) -> io::Result<Vec<CompilationUnit>> {
+    let mut reader = DwarfReader::new(&sections.debug_info);
+    let mut units = Vec::new();
+    while !reader.is_empty() {
+        units.push(CompilationUnit::parse(&mut reader, sections)?);
+    }
+    Ok(units)
+}
+
+pub fn parse_debug_info<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<CompilationUnit>> {
+    let sections = DwarfSections::load(elf)?;
+    parse_units(&sections)
+}
@@ -0,0 +1,485 @@
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::dwarf::constants::*;
+use crate::dwarf::reader::{str_at, DwarfReader};
+use crate::elf::section::{find_section, parse_sections, read_section_data};
+
+// 标准操作码
+const DW_LNS_COPY:u8 = 1;
+const DW_LNS_ADVANCE_PC:u8 = 2;
+const DW_LNS_ADVANCE_LINE:u8 = 3;
+const DW_LNS_SET_FILE:u8 = 4;
+const DW_LNS_SET_COLUMN:u8 = 5;
+const DW_LNS_NEGATE_STMT:u8 = 6;
+const DW_LNS_SET_BASIC_BLOCK:u8 = 7;
+const DW_LNS_CONST_ADD_PC:u8 = 8;
+const DW_LNS_FIXED_ADVANCE_PC:u8 = 9;
+const DW_LNS_SET_PROLOGUE_END:u8 = 10;
+const DW_LNS_SET_EPILOGUE_BEGIN:u8 = 11;
+const DW_LNS_SET_ISA:u8 = 12;
+
+// 扩展操作码
+const DW_LNE_END_SEQUENCE:u8 = 1;
+const DW_LNE_SET_ADDRESS:u8 = 2;
+const DW_LNE_DEFINE_FILE:u8 = 3;
+const DW_LNE_SET_DISCRIMINATOR:u8 = 4;
+
+// DWARF 5 目录/文件表的内容类型
+const DW_LNCT_PATH:u64 = 1;
+const DW_LNCT_DIRECTORY_INDEX:u64 = 2;
+const DW_LNCT_TIMESTAMP:u64 = 3;
+const DW_LNCT_SIZE:u64 = 4;
+const DW_LNCT_MD5:u64 = 5;
+
+/// 行号程序引用的源文件
+pub struct FileEntry {
+    pub name:String,
+    pub directory_index:u64,
+    pub mtime:u64,
+    pub size:u64,
+    pub md5:Option<[u8;16]>,
+}
+
+/// 行号程序头
+pub struct LineProgramHeader {
+    /// 该行号程序在.debug_line中的偏移
+    pub offset:u64,
+    pub unit_length:u64,
+    pub dwarf64:bool,
+    pub version:u16,
+    pub address_size:u8,
+    pub segment_selector_size:u8,
+    pub header_length:u64,
+    pub minimum_instruction_length:u8,
+    pub maximum_operations_per_instruction:u8,
+    pub default_is_stmt:bool,
+    pub line_base:i8,
+    pub line_range:u8,
+    pub opcode_base:u8,
+    pub standard_opcode_lengths:Vec<u8>,
+    pub include_directories:Vec<String>,
+    pub file_names:Vec<FileEntry>,
+}
+
+/// 行号矩阵中的一行
+#[derive(Clone)]
+pub struct LineRow {
+    pub address:u64,
+    pub op_index:u64,
+    pub file:u64,
+    pub line:u64,
+    pub column:u64,
+    pub is_stmt:bool,
+    pub basic_block:bool,
+    pub end_sequence:bool,
+    pub prologue_end:bool,
+    pub epilogue_begin:bool,
+    pub isa:u64,
+    pub discriminator:u64,
+}
+
+/// 一个编译单元的行号程序, 包含程序头和解码后的行号矩阵
+pub struct LineProgram {
+    pub header:LineProgramHeader,
+    pub rows:Vec<LineRow>,
+}
+
+/// 地址到源码位置的映射
+pub struct LineLocation {
+    pub address:u64,
+    pub file:String,
+    pub line:u64,
+    pub column:u64,
+    pub end_sequence:bool,
+}
+
+impl LineRow {
+    fn new(default_is_stmt:bool) -> Self {
+        Self {
+            address:0,
+            op_index:0,
+            file:1,
+            line:1,
+            column:0,
+            is_stmt:default_is_stmt,
+            basic_block:false,
+            end_sequence:false,
+            prologue_end:false,
+            epilogue_begin:false,
+            isa:0,
+            discriminator:0,
+        }
+    }
+}
+
+impl LineProgramHeader {
+    fn file_entry(&self, file:u64) -> Option<&FileEntry> {
+        // DWARF 5的文件编号从0开始, 之前的版本从1开始
+        let index = if self.version >= 5 { file } else { file.checked_sub(1)? };
+        self.file_names.get(usize::try_from(index).ok()?)
+    }
+
+    fn directory(&self, index:u64) -> Option<&str> {
+        let index = if self.version >= 5 { index } else { index.checked_sub(1)? };
+        self.include_directories.get(usize::try_from(index).ok()?).map(|dir| dir.as_str())
+    }
+
+    /// 文件名, 不含目录
+    pub fn file_name(&self, file:u64) -> Option<&str> {
+        self.file_entry(file).map(|entry| entry.name.as_str())
+    }
+
+    /// 文件的完整路径, 相对路径会拼接上所在目录
+    pub fn file_path(&self, file:u64) -> Option<String> {
+        let entry = self.file_entry(file)?;
+        if entry.name.starts_with('/') {
+            return Some(entry.name.clone());
+        }
+        match self.directory(entry.directory_index) {
+            Some(dir) if !dir.is_empty() => Some(format!("{}/{}", dir.trim_end_matches('/'), entry.name)),
+            _ => Some(entry.name.clone()),
+        }
+    }
+
+    fn parse(reader:&mut DwarfReader, offset:u64, debug_line_str:&[u8], debug_str:&[u8]) -> io::Result<(Self, usize)> {
+        let (unit_length, dwarf64) = reader.initial_length()?;
+        let version = reader.u16()?;
+        if !(2..=5).contains(&version) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported .debug_line version {} at offset {:#x}", version, offset)));
+        }
+        let (address_size, segment_selector_size) = if version >= 5 {
+            (reader.u8()?, reader.u8()?)
+        } else {
+            (0, 0)
+        };
+        let header_length = reader.offset(dwarf64)?;
+        let program_start = reader.position() + usize::try_from(header_length).unwrap_or(usize::MAX);
+        let minimum_instruction_length = reader.u8()?;
+        let maximum_operations_per_instruction = if version >= 4 { reader.u8()? } else { 1 };
+        let default_is_stmt = reader.u8()? != 0;
+        let line_base = reader.i8()?;
+        let line_range = reader.u8()?;
+        let opcode_base = reader.u8()?;
+        let standard_opcode_lengths = reader.bytes(opcode_base.saturating_sub(1) as usize)?.to_vec();
+        let mut header = Self {
+            offset,
+            unit_length,
+            dwarf64,
+            version,
+            address_size,
+            segment_selector_size,
+            header_length,
+            minimum_instruction_length,
+            maximum_operations_per_instruction,
+            default_is_stmt,
+            line_base,
+            line_range,
+            opcode_base,
+            standard_opcode_lengths,
+            include_directories:Vec::new(),
+            file_names:Vec::new(),
+        };
+        if version >= 5 {
+            let formats = parse_entry_formats(reader)?;
+            let count = entry_count(reader, &formats)?;
+            for _ in 0..count {
+                let entry = header.parse_entry(reader, &formats, debug_line_str, debug_str)?;
+                header.include_directories.push(entry.name);
+            }
+            let formats = parse_entry_formats(reader)?;
+            let count = entry_count(reader, &formats)?;
+            for _ in 0..count {
+                let entry = header.parse_entry(reader, &formats, debug_line_str, debug_str)?;
+                header.file_names.push(entry);
+            }
+        } else {
+            loop {
+                let dir = reader.cstr()?;
+                if dir.is_empty() {
+                    break;
+                }
+                header.include_directories.push(dir.to_string());
+            }
+            loop {
+                let name = reader.cstr()?;
+                if name.is_empty() {
+                    break;
+                }
+                header.file_names.push(parse_legacy_file_entry(reader, name)?);
+            }
+        }
+        Ok((header, program_start))
+    }
+
+    fn parse_entry(&self, reader:&mut DwarfReader, formats:&[(u64, u64)], debug_line_str:&[u8], debug_str:&[u8]) -> io::Result<FileEntry> {
+        let mut entry = FileEntry { name:String::new(), directory_index:0, mtime:0, size:0, md5:None };
+        for &(content_type, form) in formats {
+            match form {
+                DW_FORM_STRING | DW_FORM_LINE_STRP | DW_FORM_STRP => {
+                    let value = match form {
+                        DW_FORM_STRING => reader.cstr()?,
+                        DW_FORM_LINE_STRP => str_at(debug_line_str, reader.offset(self.dwarf64)?)?,
+                        _ => str_at(debug_str, reader.offset(self.dwarf64)?)?,
+                    };
+                    if content_type == DW_LNCT_PATH {
+                        entry.name = value.to_string();
+                    }
+                }
+                DW_FORM_DATA16 => {
+                    let value:[u8;16] = reader.bytes(16)?.try_into().unwrap();
+                    if content_type == DW_LNCT_MD5 {
+                        entry.md5 = Some(value);
+                    }
+                }
+                DW_FORM_BLOCK | DW_FORM_BLOCK1 | DW_FORM_BLOCK2 | DW_FORM_BLOCK4 => {
+                    let len = match form {
+                        DW_FORM_BLOCK1 => reader.u8()? as u64,
+                        DW_FORM_BLOCK2 => reader.u16()? as u64,
+                        DW_FORM_BLOCK4 => reader.u32()? as u64,
+                        _ => reader.uleb128()?,
+                    };
+                    reader.split(len)?;
+                }
+                _ => {
+                    let value = match form {
+                        DW_FORM_DATA1 => reader.u8()? as u64,
+                        DW_FORM_DATA2 => reader.u16()? as u64,
+                        DW_FORM_DATA4 => reader.u32()? as u64,
+                        DW_FORM_DATA8 => reader.u64()?,
+                        DW_FORM_UDATA => reader.uleb128()?,
+                        DW_FORM_SDATA => reader.sleb128()? as u64,
+                        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported form {:#x} in line table header", form))),
+                    };
+                    match content_type {
+                        DW_LNCT_DIRECTORY_INDEX => entry.directory_index = value,
+                        DW_LNCT_TIMESTAMP => entry.mtime = value,
+                        DW_LNCT_SIZE => entry.size = value,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(entry)
+    }
+}
+
+fn parse_entry_formats(reader:&mut DwarfReader) -> io::Result<Vec<(u64, u64)>> {
+    let count = reader.u8()?;
+    let mut formats = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        formats.push((reader.uleb128()?, reader.uleb128()?));
+    }
+    Ok(formats)
+}
+
+fn parse_legacy_file_entry(reader:&mut DwarfReader, name:&str) -> io::Result<FileEntry> {
+    Ok(FileEntry {
+        name:name.to_string(),
+        directory_index:reader.uleb128()?,
+        mtime:reader.uleb128()?,
+        size:reader.uleb128()?,
+        md5:None,
+    })
+}
+
+/// 读取DWARF 5目录表或文件表的项数; 有格式时每项至少占一个字节,
+/// 项数超过剩余的字节数或没有格式却有项时是损坏的数据, 不能按它循环
+fn entry_count(reader:&mut DwarfReader, formats:&[(u64, u64)]) -> io::Result<u64> {
+    let count = reader.uleb128()?;
+    if count > 0 && (formats.is_empty() || count > reader.remaining() as u64) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("bad .debug_line entry count {}", count)));
+    }
+    Ok(count)
+}
+
+impl LineProgram {
+    /// 解析从当前位置开始的一个行号程序
+    fn parse(reader:&mut DwarfReader, debug_line_str:&[u8], debug_str:&[u8]) -> io::Result<Self> {
+        let offset = reader.position() as u64;
+        let (unit_length, dwarf64) = reader.clone().initial_length()?;
+        let length_size:u64 = if dwarf64 { 12 } else { 4 };
+        let mut unit = reader.split(length_size.saturating_add(unit_length))?;
+        let (mut header, program_start) = LineProgramHeader::parse(&mut unit, offset, debug_line_str, debug_str)?;
+        unit.seek(program_start)?;
+        let rows = run_program(&mut header, &mut unit)?;
+        Ok(Self { header, rows })
+    }
+
+    /// 行号矩阵中的每一行对应的地址和源码位置
+    pub fn locations(&self) -> Vec<LineLocation> {
+        self.rows.iter().map(|row| LineLocation {
+            address:row.address,
+            file:self.header.file_path(row.file).unwrap_or_else(|| format!("<file {}>", row.file)),
+            line:row.line,
+            column:row.column,
+            end_sequence:row.end_sequence,
+        }).collect()
+    }
+
+    /// 查找覆盖address的行, 同一地址有多行时取最后一行
+    pub fn find_row(&self, address:u64) -> Option<&LineRow> {
+        self.rows.windows(2).find(|pair| {
+            !pair[0].end_sequence && pair[0].address <= address && address < pair[1].address
+        }).map(|pair| &pair[0])
+    }
+
+    /// 编译单元的主文件名
+    fn unit_name(&self) -> String {
+        let primary = if self.header.version >= 5 { 0 } else { 1 };
+        self.header.file_path(primary).unwrap_or_default()
+    }
+}
+
+/// DW_LNE_define_file定义的文件追加到header的文件表中, 之后的DW_LNS_set_file可以引用它
+fn run_program(header:&mut LineProgramHeader, reader:&mut DwarfReader) -> io::Result<Vec<LineRow>> {
+    let mut rows = Vec::new();
+    let mut state = LineRow::new(header.default_is_stmt);
+    let max_ops = header.maximum_operations_per_instruction.max(1) as u64;
+    let min_inst = header.minimum_instruction_length as u64;
+    let advance = |state:&mut LineRow, operation_advance:u64| {
+        let op = state.op_index.wrapping_add(operation_advance);
+        state.address = state.address.wrapping_add(min_inst.wrapping_mul(op / max_ops));
+        state.op_index = op % max_ops;
+    };
+    let emit = |state:&mut LineRow, rows:&mut Vec<LineRow>| {
+        rows.push(state.clone());
+        state.basic_block = false;
+        state.prologue_end = false;
+        state.epilogue_begin = false;
+        state.discriminator = 0;
+    };
+    while !reader.is_empty() {
+        let opcode = reader.u8()?;
+        if opcode >= header.opcode_base {
+            if header.line_range == 0 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "line_range of line program is zero"));
+            }
+            let adjusted = (opcode - header.opcode_base) as u64;
+            advance(&mut state, adjusted / header.line_range as u64);
+            let line_advance = header.line_base as i64 + (adjusted % header.line_range as u64) as i64;
+            state.line = state.line.wrapping_add(line_advance as u64);
+            emit(&mut state, &mut rows);
+            continue;
+        }
+        match opcode {
+            0 => {
+                let len = reader.uleb128()?;
+                let mut ext = reader.split(len)?;
+                if len == 0 {
+                    continue;
+                }
+                match ext.u8()? {
+                    DW_LNE_END_SEQUENCE => {
+                        state.end_sequence = true;
+                        emit(&mut state, &mut rows);
+                        state = LineRow::new(header.default_is_stmt);
+                    }
+                    DW_LNE_SET_ADDRESS => {
+                        let size = ext.remaining() as u8;
+                        state.address = ext.uint(size)?;
+                        state.op_index = 0;
+                    }
+                    DW_LNE_DEFINE_FILE => {
+                        // 已在DWARF 5中废弃, 格式与DWARF 4文件头中的文件项相同
+                        let name = ext.cstr()?;
+                        let entry = parse_legacy_file_entry(&mut ext, name)?;
+                        header.file_names.push(entry);
+                    }
+                    DW_LNE_SET_DISCRIMINATOR => {
+                        state.discriminator = ext.uleb128()?;
+                    }
+                    _ => {}
+                }
+            }
+            DW_LNS_COPY => emit(&mut state, &mut rows),
+            DW_LNS_ADVANCE_PC => {
+                let operation_advance = reader.uleb128()?;
+                advance(&mut state, operation_advance);
+            }
+            DW_LNS_ADVANCE_LINE => {
+                let line_advance = reader.sleb128()?;
+                state.line = state.line.wrapping_add(line_advance as u64);
+            }
+            DW_LNS_SET_FILE => state.file = reader.uleb128()?,
+            DW_LNS_SET_COLUMN => state.column = reader.uleb128()?,
+            DW_LNS_NEGATE_STMT => state.is_stmt = !state.is_stmt,
+            DW_LNS_SET_BASIC_BLOCK => state.basic_block = true,
+            DW_LNS_CONST_ADD_PC => {
+                if header.line_range != 0 {
+                    advance(&mut state, (255 - header.opcode_base) as u64 / header.line_range as u64);
+                }
+            }
+            DW_LNS_FIXED_ADVANCE_PC => {
+                state.address = state.address.wrapping_add(reader.u16()? as u64);
+                state.op_index = 0;
+            }
+            DW_LNS_SET_PROLOGUE_END => state.prologue_end = true,
+            DW_LNS_SET_EPILOGUE_BEGIN => state.epilogue_begin = true,
+            DW_LNS_SET_ISA => state.isa = reader.uleb128()?,
+            _ => {
+                // 未知的标准操作码, 按头部记录的参数个数跳过
+                let args = header.standard_opcode_lengths.get(opcode as usize - 1).copied().unwrap_or(0);
+                for _ in 0..args {
+                    reader.uleb128()?;
+                }
+            }
+        }
+    }
+    Ok(rows)
+}
+
+impl std::fmt::Display for LineProgram {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f,"CU: {}:",self.unit_name())?;
+        writeln!(f,"{:<36} {:>11} {:>8}    {:>16}    {:>4}    {:<4}","File name","Line number","Column","Starting address","View","Stmt")?;
+        let mut view = 0;
+        let mut prev:Option<&LineRow> = None;
+        for row in &self.rows {
+            view = match prev {
+                Some(prev) if !prev.end_sequence && prev.address == row.address => view + 1,
+                _ => 0,
+            };
+            prev = Some(row);
+            let name = self.header.file_name(row.file).unwrap_or("<unknown>");
+            let view_str = if view == 0 { String::new() } else { view.to_string() };
+            if row.end_sequence {
+                writeln!(f,"{:<36} {:>11} {:>8}    {:>#16x}",name,"-","",row.address)?;
+                writeln!(f)?;
+                continue;
+            }
+            writeln!(f,"{:<36} {:>11} {:>8}    {:>#16x}    {:>4}    {}",name,row.line,row.column,row.address,view_str,if row.is_stmt {"x"} else {""})?;
+        }
+        Ok(())
+    }
+}
+
+/// 解析.debug_line节中的所有行号程序
+pub fn parse_line_programs(debug_line:&[u8], debug_line_str:&[u8], debug_str:&[u8]) -> io::Result<Vec<LineProgram>> {
+    let mut reader = DwarfReader::new(debug_line);
+    let mut programs = Vec::new();
+    while !reader.is_empty() {
+        programs.push(LineProgram::parse(&mut reader, debug_line_str, debug_str)?);
+    }
+    Ok(programs)
+}
+
+/// 从ELF文件中读取.debug_line及其引用的字符串节并解析, 文件没有.debug_line时返回空
+pub fn parse_debug_line<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<LineProgram>> {
+    let sections = parse_sections(elf)?;
+    let mut load = |name:&str| -> io::Result<Vec<u8>> {
+        match find_section(&sections, name) {
+            Some(section) => read_section_data(elf, section),
+            None => Ok(Vec::new()),
+        }
+    };
+    let debug_line = load(".debug_line")?;
+    let debug_line_str = load(".debug_line_str")?;
+    let debug_str = load(".debug_str")?;
+    parse_line_programs(&debug_line, &debug_line_str, &debug_str)
+}
+
+/// 所有行号程序展开后的地址到(文件, 行, 列)的映射
+pub fn parse_line_locations<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<LineLocation>> {
+    Ok(parse_debug_line(elf)?.iter().flat_map(|program| program.locations()).collect())
+}
@@ -0,0 +1,117 @@
+use std::io;
+use crate::dwarf::constants::*;
+use crate::dwarf::info::{AttributeValue, CompilationUnit, Die};
+use crate::dwarf::reader::DwarfReader;
+use crate::dwarf::sections::DwarfSections;
+
+// DWARF 5 .debug_rnglists中的条目类型
+const DW_RLE_END_OF_LIST:u8 = 0x00;
+const DW_RLE_BASE_ADDRESSX:u8 = 0x01;
+const DW_RLE_STARTX_ENDX:u8 = 0x02;
+const DW_RLE_STARTX_LENGTH:u8 = 0x03;
+const DW_RLE_OFFSET_PAIR:u8 = 0x04;
+const DW_RLE_BASE_ADDRESS:u8 = 0x05;
+const DW_RLE_START_END:u8 = 0x06;
+const DW_RLE_START_LENGTH:u8 = 0x07;
+
+/// 地址范围[begin, end)
+pub type Range = (u64, u64);
+
+fn missing_address(index:u64) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("address index {} is out of .debug_addr", index))
+}
+
+/// 读取.debug_rnglists中offset处的范围列表
+fn parse_rnglist(unit:&CompilationUnit, sections:&DwarfSections, offset:u64, mut base:u64) -> io::Result<Vec<Range>> {
+    let mut reader = DwarfReader::new(&sections.debug_rnglists);
+    reader.seek(usize::try_from(offset).unwrap_or(usize::MAX))?;
+    let address = |index:u64| unit.address_at(sections, index).ok_or_else(|| missing_address(index));
+    let mut ranges = Vec::new();
+    loop {
+        match reader.u8()? {
+            DW_RLE_END_OF_LIST => break,
+            DW_RLE_BASE_ADDRESSX => base = address(reader.uleb128()?)?,
+            DW_RLE_STARTX_ENDX => {
+                let begin = address(reader.uleb128()?)?;
+                let end = address(reader.uleb128()?)?;
+                ranges.push((begin, end));
+            }
+            DW_RLE_STARTX_LENGTH => {
+                let begin = address(reader.uleb128()?)?;
+                let length = reader.uleb128()?;
+                ranges.push((begin, begin.wrapping_add(length)));
+            }
+            DW_RLE_OFFSET_PAIR => {
+                let begin = reader.uleb128()?;
+                let end = reader.uleb128()?;
+                ranges.push((base.wrapping_add(begin), base.wrapping_add(end)));
+            }
+            DW_RLE_BASE_ADDRESS => base = reader.uint(unit.address_size)?,
+            DW_RLE_START_END => {
+                let begin = reader.uint(unit.address_size)?;
+                let end = reader.uint(unit.address_size)?;
+                ranges.push((begin, end));
+            }
+            DW_RLE_START_LENGTH => {
+                let begin = reader.uint(unit.address_size)?;
+                let length = reader.uleb128()?;
+                ranges.push((begin, begin.wrapping_add(length)));
+            }
+            kind => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown range list entry {:#x}", kind))),
+        }
+    }
+    Ok(ranges)
+}
+
+/// 读取DWARF 2-4 .debug_ranges中offset处的范围列表
+fn parse_ranges(unit:&CompilationUnit, sections:&DwarfSections, offset:u64, mut base:u64) -> io::Result<Vec<Range>> {
+    let mut reader = DwarfReader::new(&sections.debug_ranges);
+    reader.seek(usize::try_from(offset).unwrap_or(usize::MAX))?;
+    let max = if unit.address_size == 4 { u32::MAX as u64 } else { u64::MAX };
+    let mut ranges = Vec::new();
+    loop {
+        let begin = reader.uint(unit.address_size)?;
+        let end = reader.uint(unit.address_size)?;
+        if begin == 0 && end == 0 {
+            break;
+        }
+        if begin == max {
+            base = end;
+            continue;
+        }
+        ranges.push((base.wrapping_add(begin), base.wrapping_add(end)));
+    }
+    Ok(ranges)
+}
+
+/// 单元的基地址, 即顶层DIE的DW_AT_low_pc
+pub fn unit_base_address(unit:&CompilationUnit) -> u64 {
+    unit.root.as_ref().and_then(|root| root.attr_u64(DW_AT_LOW_PC)).unwrap_or(0)
+}
+
+/// DIE覆盖的地址范围, 来自DW_AT_low_pc/DW_AT_high_pc或DW_AT_ranges
+pub fn die_ranges(unit:&CompilationUnit, die:&Die, sections:&DwarfSections) -> io::Result<Vec<Range>> {
+    if let Some(value) = die.attr(DW_AT_RANGES) {
+        let base = unit_base_address(unit);
+        return match value {
+            AttributeValue::RangeListIndex(_, Some(offset)) => parse_rnglist(unit, sections, *offset, base),
+            AttributeValue::RangeListIndex(index, None) => Err(io::Error::new(io::ErrorKind::InvalidData, format!("range list index {} is out of .debug_rnglists", index))),
+            value => {
+                let offset = value.as_u64().unwrap_or(0);
+                if unit.version >= 5 {
+                    parse_rnglist(unit, sections, offset, base)
+                } else {
+                    parse_ranges(unit, sections, offset, base)
+                }
+            }
+        };
+    }
+    let Some(low) = die.attr_u64(DW_AT_LOW_PC) else { return Ok(Vec::new()) };
+    let high = match die.attr(DW_AT_HIGH_PC) {
+        // DWARF 4开始high_pc可以是相对low_pc的长度
+        Some(AttributeValue::Address(high)) | Some(AttributeValue::AddressIndex(_, Some(high))) => *high,
+        Some(value) => low.wrapping_add(value.as_u64().unwrap_or(0)),
+        None => low.wrapping_add(1),
+    };
+    Ok(vec![(low, high)])
+}
@@ -0,0 +1,167 @@
+use std::io;
+
+/// DWARF数据的游标, 所有多字节值按小端读取
+#[derive(Clone)]
+pub struct DwarfReader<'a> {
+    data:&'a [u8],
+    pos:usize,
+}
+
+fn eof() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "unexpected end of DWARF data")
+}
+
+impl<'a> DwarfReader<'a> {
+    pub fn new(data:&'a [u8]) -> Self {
+        Self { data, pos:0 }
+    }
+
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub fn seek(&mut self, pos:usize) -> io::Result<()> {
+        if pos > self.data.len() {
+            return Err(eof());
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    pub fn bytes(&mut self, len:usize) -> io::Result<&'a [u8]> {
+        let end = self.pos.checked_add(len).ok_or_else(eof)?;
+        if end > self.data.len() {
+            return Err(eof());
+        }
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    pub fn skip(&mut self, len:usize) -> io::Result<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    pub fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn i8(&mut self) -> io::Result<i8> {
+        Ok(self.u8()? as i8)
+    }
+
+    pub fn u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    pub fn u24(&mut self) -> io::Result<u32> {
+        let b = self.bytes(3)?;
+        Ok(b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16)
+    }
+
+    pub fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    pub fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    /// 按字节数读取无符号数, 用于地址和DW_FORM_data*
+    pub fn uint(&mut self, size:u8) -> io::Result<u64> {
+        match size {
+            1 => Ok(self.u8()? as u64),
+            2 => Ok(self.u16()? as u64),
+            3 => Ok(self.u24()? as u64),
+            4 => Ok(self.u32()? as u64),
+            8 => self.u64(),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported value size {}", size))),
+        }
+    }
+
+    pub fn uleb128(&mut self) -> io::Result<u64> {
+        let mut result = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                result |= ((byte & 0x7f) as u64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+    }
+
+    pub fn sleb128(&mut self) -> io::Result<i64> {
+        let mut result = 0i64;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                result |= ((byte & 0x7f) as i64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    result |= -1i64 << shift;
+                }
+                return Ok(result);
+            }
+        }
+    }
+
+    /// 读取以0结尾的字符串
+    pub fn cstr(&mut self) -> io::Result<&'a str> {
+        let rest = &self.data[self.pos..];
+        let len = rest.iter().position(|&b| b == 0).ok_or_else(eof)?;
+        self.pos += len + 1;
+        std::str::from_utf8(&rest[..len]).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// 读取initial length, 返回(长度, 是否为64位DWARF格式)
+    pub fn initial_length(&mut self) -> io::Result<(u64, bool)> {
+        let length = self.u32()?;
+        if length == 0xffff_ffff {
+            Ok((self.u64()?, true))
+        } else if length >= 0xffff_fff0 {
+            Err(io::Error::new(io::ErrorKind::InvalidData, format!("reserved initial length {:#x}", length)))
+        } else {
+            Ok((length as u64, false))
+        }
+    }
+
+    /// 读取节内偏移, 32位DWARF为4字节, 64位DWARF为8字节
+    pub fn offset(&mut self, dwarf64:bool) -> io::Result<u64> {
+        if dwarf64 {
+            self.u64()
+        } else {
+            Ok(self.u32()? as u64)
+        }
+    }
+
+    /// 截取从当前位置开始长度为len的子读取器, 并跳过这段数据
+    pub fn split(&mut self, len:u64) -> io::Result<DwarfReader<'a>> {
+        let len = usize::try_from(len).map_err(|_| eof())?;
+        Ok(DwarfReader::new(self.bytes(len)?))
+    }
+}
+
+/// 从字符串节(.debug_str, .debug_line_str)中按偏移取出字符串
+pub fn str_at(data:&[u8], offset:u64) -> io::Result<&str> {
+    let offset = usize::try_from(offset).map_err(|_| eof())?;
+    if offset > data.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("string offset {:#x} out of range", offset)));
+    }
+    let mut reader = DwarfReader::new(&data[offset..]);
+    reader.cstr()
+}
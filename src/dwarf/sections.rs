@@ -0,0 +1,49 @@
+use std::io;
+use std::io::{Read, Seek};
+use crate::elf::section::{find_section, parse_sections, read_section_data, ElfSection};
+
+/// 解析DWARF所需的各个调试节的内容, 文件中不存在的节为空
+#[derive(Default)]
+pub struct DwarfSections {
+    pub debug_info:Vec<u8>,
+    pub debug_abbrev:Vec<u8>,
+    pub debug_str:Vec<u8>,
+    pub debug_line:Vec<u8>,
+    pub debug_line_str:Vec<u8>,
+    pub debug_str_offsets:Vec<u8>,
+    pub debug_addr:Vec<u8>,
+    pub debug_ranges:Vec<u8>,
+    pub debug_rnglists:Vec<u8>,
+    pub debug_loclists:Vec<u8>,
+}
+
+impl DwarfSections {
+    /// 读取ELF文件中的调试节, 找不到时再尝试分离调试文件(.dwo)中的同名节
+    pub fn load<R:Read + Seek>(elf:&mut R) -> io::Result<Self> {
+        let sections = parse_sections(elf)?;
+        Self::from_sections(elf, &sections)
+    }
+
+    /// 与load相同, 使用已经解析的节表
+    pub fn from_sections<R:Read + Seek>(elf:&mut R, sections:&[ElfSection]) -> io::Result<Self> {
+        let mut load = |name:&str| -> io::Result<Vec<u8>> {
+            let dwo = format!("{}.dwo", name);
+            match find_section(sections, name).or_else(|| find_section(sections, &dwo)) {
+                Some(section) => read_section_data(elf, section),
+                None => Ok(Vec::new()),
+            }
+        };
+        Ok(Self {
+            debug_info:load(".debug_info")?,
+            debug_abbrev:load(".debug_abbrev")?,
+            debug_str:load(".debug_str")?,
+            debug_line:load(".debug_line")?,
+            debug_line_str:load(".debug_line_str")?,
+            debug_str_offsets:load(".debug_str_offsets")?,
+            debug_addr:load(".debug_addr")?,
+            debug_ranges:load(".debug_ranges")?,
+            debug_rnglists:load(".debug_rnglists")?,
+            debug_loclists:load(".debug_loclists")?,
+        })
+    }
+}
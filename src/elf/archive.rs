@@ -0,0 +1,254 @@
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use std::path::{Component, Path};
+use crate::elf::bounds::{invalid, read_at};
+use crate::json::impl_to_json;
+
+/// 普通静态库和thin静态库的魔数
+pub const ARMAG:&[u8;8] = b"!<arch>\n";
+pub const THIN_ARMAG:&[u8;8] = b"!<thin>\n";
+
+/// 成员头的大小
+const HEADER_SIZE:u64 = 60;
+
+/// 静态库中的一个成员(通常是.o文件)
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ArchiveMember {
+    /// 解析长文件名后的成员名, thin静态库中是相对于静态库所在目录的路径
+    pub name:String,
+    /// 成员头在静态库中的偏移, 符号索引中记录的就是这个偏移
+    pub header_offset:u64,
+    /// 成员内容在静态库中的偏移, thin静态库的成员内容在外部文件中
+    pub data_offset:u64,
+    pub size:u64,
+    pub date:u64,
+    pub uid:u32,
+    pub gid:u32,
+    pub mode:u32,
+}
+
+impl_to_json!(ArchiveMember { name, header_offset, data_offset, size, date, uid, gid, mode });
+
+/// 符号索引中的一项: 符号名及定义该符号的成员头偏移
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ArchiveSymbol {
+    pub name:String,
+    pub member_offset:u64,
+}
+
+impl_to_json!(ArchiveSymbol { name, member_offset });
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Archive {
+    pub thin:bool,
+    pub members:Vec<ArchiveMember>,
+    pub symbols:Vec<ArchiveSymbol>,
+    /// 符号索引中字符串表的大小
+    pub symbol_table_size:u64,
+}
+
+impl_to_json!(Archive { thin, members, symbols, symbol_table_size });
+
+/// 成员头中以空格填充的字段
+struct RawHeader {
+    name:String,
+    date:u64,
+    uid:u32,
+    gid:u32,
+    mode:u32,
+    size:u64,
+}
+
+fn field(data:&[u8]) -> &str {
+    std::str::from_utf8(data).unwrap_or("").trim_end()
+}
+
+impl RawHeader {
+    fn parse(data:&[u8], offset:u64) -> io::Result<Self> {
+        if &data[58..60] != b"`\n" {
+            return Err(invalid(format!("bad archive member header at {:#x}", offset)));
+        }
+        let number = |data:&[u8], radix:u32| u64::from_str_radix(field(data), radix).unwrap_or(0);
+        Ok(Self {
+            name:String::from_utf8_lossy(&data[0..16]).trim_end().to_string(),
+            date:number(&data[16..28], 10),
+            uid:number(&data[28..34], 10) as u32,
+            gid:number(&data[34..40], 10) as u32,
+            mode:number(&data[40..48], 8) as u32,
+            size:field(&data[48..58]).parse::<u64>()
+                .map_err(|_| invalid(format!("bad archive member size at {:#x}", offset)))?,
+        })
+    }
+}
+
+/// 从GNU长文件名表(`//`成员)中取出offset处的名字, 名字以`/\n`结尾
+fn long_name(table:&[u8], offset:usize) -> io::Result<String> {
+    let name = table.get(offset..).ok_or_else(|| invalid(format!("long name offset {} is out of the name table", offset)))?;
+    let len = name.iter().position(|&b| b == b'\n').unwrap_or(name.len());
+    Ok(String::from_utf8_lossy(&name[..len]).trim_end_matches('/').to_string())
+}
+
+/// 解析GNU符号索引, `/`使用4字节大端整数, `/SYM64/`使用8字节
+fn parse_gnu_symbols(data:&[u8], word:usize) -> io::Result<(Vec<ArchiveSymbol>, u64)> {
+    let read = |pos:usize| -> io::Result<u64> {
+        let bytes = data.get(pos..pos + word).ok_or_else(|| invalid(String::from("truncated archive symbol index")))?;
+        Ok(bytes.iter().fold(0u64, |value, &b| (value << 8) | b as u64))
+    };
+    let count = read(0)? as usize;
+    let strings_start = count.checked_add(1).and_then(|n| n.checked_mul(word))
+        .filter(|&start| start <= data.len())
+        .ok_or_else(|| invalid(String::from("truncated archive symbol index")))?;
+    let mut strings = &data[strings_start..];
+    let mut symbols = Vec::new();
+    for i in 0..count {
+        let member_offset = read((i + 1) * word)?;
+        let len = strings.iter().position(|&b| b == 0).unwrap_or(strings.len());
+        symbols.push(ArchiveSymbol { name:String::from_utf8_lossy(&strings[..len]).into_owned(), member_offset });
+        strings = strings.get(len + 1..).unwrap_or(&[]);
+    }
+    Ok((symbols, (data.len() - strings_start) as u64))
+}
+
+/// 解析BSD符号索引(`__.SYMDEF`), 由ranlib结构数组和字符串表组成, 使用小端整数
+fn parse_bsd_symbols(data:&[u8]) -> io::Result<(Vec<ArchiveSymbol>, u64)> {
+    let read = |pos:usize| -> io::Result<u64> {
+        let bytes = data.get(pos..pos + 4).ok_or_else(|| invalid(String::from("truncated archive symbol index")))?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()) as u64)
+    };
+    let ranlib_size = read(0)? as usize;
+    let strings_size = read(4 + ranlib_size)? as usize;
+    let strings = data.get(8 + ranlib_size..8 + ranlib_size + strings_size)
+        .ok_or_else(|| invalid(String::from("truncated archive symbol index")))?;
+    let mut symbols = Vec::new();
+    for i in 0..ranlib_size / 8 {
+        let name_offset = read(4 + i * 8)? as usize;
+        let member_offset = read(8 + i * 8)?;
+        let name = strings.get(name_offset..).unwrap_or(&[]);
+        let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+        symbols.push(ArchiveSymbol { name:String::from_utf8_lossy(&name[..len]).into_owned(), member_offset });
+    }
+    Ok((symbols, strings_size as u64))
+}
+
+/// 文件是否是静态库(包括thin静态库)
+pub fn is_archive<R:Read + Seek>(file:&mut R) -> io::Result<bool> {
+    file.rewind()?;
+    let mut magic = [0u8;8];
+    match file.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == ARMAG || &magic == THIN_ARMAG),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// 解析静态库的成员列表和符号索引
+pub fn parse_archive<R:Read + Seek>(file:&mut R) -> io::Result<Archive> {
+    file.rewind()?;
+    let mut magic = [0u8;8];
+    file.read_exact(&mut magic)?;
+    let thin = match &magic {
+        ARMAG => false,
+        THIN_ARMAG => true,
+        _ => return Err(invalid(String::from("not an archive"))),
+    };
+    let end = file.seek(io::SeekFrom::End(0))?;
+    let mut archive = Archive { thin, members:Vec::new(), symbols:Vec::new(), symbol_table_size:0 };
+    let mut long_names = Vec::new();
+    let mut offset = ARMAG.len() as u64;
+    while offset + HEADER_SIZE <= end {
+        file.seek(io::SeekFrom::Start(offset))?;
+        let mut data = [0u8;HEADER_SIZE as usize];
+        file.read_exact(&mut data)?;
+        let header = RawHeader::parse(&data, offset)?;
+        let mut data_offset = offset + HEADER_SIZE;
+        let mut size = header.size;
+        let mut name = header.name.clone();
+        if let Some(len) = header.name.strip_prefix("#1/") {
+            // BSD长文件名: `#1/长度`, 名字紧跟在成员头后面并计入成员大小
+            let len = len.parse::<u64>().map_err(|_| invalid(format!("bad long name reference {}", header.name)))?;
+            size = size.checked_sub(len).ok_or_else(|| invalid(format!("bad BSD long name length at {:#x}", offset)))?;
+            let bytes = read_at(file, data_offset, len, "archive member name")?;
+            let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            name = String::from_utf8_lossy(&bytes[..end]).into_owned();
+            data_offset += len;
+        }
+        // 符号索引和长文件名表即使在thin静态库中也保存在库内
+        let special = name == "/" || name == "/SYM64/" || name == "//" || name.starts_with("__.SYMDEF");
+        let stored = !thin || special;
+        if stored && data_offset.checked_add(size).is_none_or(|member_end| member_end > end) {
+            return Err(invalid(format!("archive member at {:#x} extends past the end of file", offset)));
+        }
+        let read_body = |file:&mut R| read_at(file, data_offset, size, "archive member");
+        let mut member_name = None;
+        if name == "/" || name == "/SYM64/" {
+            let word = if name == "/" { 4 } else { 8 };
+            (archive.symbols, archive.symbol_table_size) = parse_gnu_symbols(&read_body(file)?, word)?;
+        } else if name.starts_with("__.SYMDEF") {
+            (archive.symbols, archive.symbol_table_size) = parse_bsd_symbols(&read_body(file)?)?;
+        } else if name == "//" {
+            long_names = read_body(file)?;
+        } else if let Some(index) = name.strip_prefix('/') {
+            // GNU长文件名: `/偏移`
+            let index = index.parse::<usize>().map_err(|_| invalid(format!("bad long name reference {}", name)))?;
+            member_name = Some(long_name(&long_names, index)?);
+        } else if header.name.starts_with("#1/") {
+            member_name = Some(name);
+        } else {
+            // GNU短文件名以`/`结尾
+            member_name = Some(name.trim_end_matches('/').to_string());
+        }
+        if let Some(name) = member_name {
+            archive.members.push(ArchiveMember {
+                name,
+                header_offset:offset,
+                data_offset,
+                size,
+                date:header.date,
+                uid:header.uid,
+                gid:header.gid,
+                mode:header.mode,
+            });
+        }
+        // 成员按2字节对齐
+        let next = if stored { data_offset + size } else { offset + HEADER_SIZE };
+        offset = next + next % 2;
+    }
+    Ok(archive)
+}
+
+impl Archive {
+    /// 符号索引中成员头偏移对应的成员
+    pub fn member_at(&self, header_offset:u64) -> Option<&ArchiveMember> {
+        self.members.iter().find(|member| member.header_offset == header_offset)
+    }
+
+    /// 读取成员的内容, thin静态库从相对于dir(静态库所在目录)的外部文件读取;
+    /// 成员名来自文件内容, 绝对路径和含`..`的路径可能指向dir之外, 不读取
+    pub fn read_member<R:Read + Seek>(&self, file:&mut R, dir:&Path, member:&ArchiveMember) -> io::Result<Vec<u8>> {
+        if self.thin {
+            let name = Path::new(&member.name);
+            if !name.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir)) {
+                return Err(invalid(format!("thin archive member path {} leaves the archive directory", member.name)));
+            }
+            return std::fs::read(dir.join(name));
+        }
+        read_at(file, member.data_offset, member.size, &format!("archive member {}",member.name))
+    }
+}
+
+impl std::fmt::Display for Archive {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f,"Index: ({} entries, {:#x} bytes in the symbol table)",self.symbols.len(),self.symbol_table_size)?;
+        let mut current = None;
+        for symbol in &self.symbols {
+            if current != Some(symbol.member_offset) {
+                current = Some(symbol.member_offset);
+                let name = self.member_at(symbol.member_offset).map(|member| member.name.as_str()).unwrap_or("??");
+                writeln!(f,"Contents of member {} at offset {:#x}",name,symbol.member_offset)?;
+            }
+            writeln!(f,"\t{}",symbol.name)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use std::borrow::Cow;
+use std::io;
+use std::io::{Read, Seek};
+use std::ops::Range;
+use crate::elf::header::{BitType, ElfHeader};
+
+/// ELF64文件头的大小, 节头表和段头表不能与文件头重叠
+pub const EHDR_SIZE:u64 = 64;
+
+pub fn invalid(message:String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub fn file_len<R:Seek>(elf:&mut R) -> io::Result<u64> {
+    elf.seek(io::SeekFrom::End(0))
+}
+
+/// 文件中[offset, offset + size)的范围, 溢出或超出文件长度len时返回错误
+pub fn checked_range(len:u64, offset:u64, size:u64, what:&str) -> io::Result<Range<u64>> {
+    match offset.checked_add(size) {
+        Some(end) if end <= len => Ok(offset..end),
+        _ => Err(invalid(format!("{} at {:#x} with size {:#x} extends past the end of the file ({:#x} bytes)",what,offset,size,len))),
+    }
+}
+
+/// 检查文件头中的表项大小(e_phentsize, e_shentsize), sizes是ELF32和ELF64的标准结构大小
+///
+/// 表项可以比标准结构大, 厂商工具链附加在后面的字段被忽略; 比标准结构小或文件类别未知时返回错误,
+/// ELF32的表项布局不同, 大小正确时返回Unsupported
+pub fn entry_size(header:&ElfHeader, field:&str, entsize:u16, sizes:[u64; 2]) -> io::Result<u64> {
+    let (class, size, supported) = match BitType::from(header.e_ident[4]) {
+        BitType::BIT32 => ("ELF32", sizes[0], false),
+        BitType::BIT64 => ("ELF64", sizes[1], true),
+        BitType::UNKNOWN => return Err(invalid(format!("unknown ELF class {}",header.e_ident[4]))),
+    };
+    if (entsize as u64) < size {
+        return Err(invalid(format!("{} is {}, smaller than the {}-byte {} entry",field,entsize,size,class)));
+    }
+    if !supported {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, format!("{} files are not supported",class)));
+    }
+    Ok(entsize as u64)
+}
+
+/// count个entsize字节的表项组成的表(节头表, 段头表)在文件中的范围, 表超出文件或与文件头重叠时返回错误
+pub fn table_range(len:u64, offset:u64, count:u64, entsize:u64, what:&str) -> io::Result<Range<u64>> {
+    if count == 0 {
+        return Ok(offset..offset);
+    }
+    let size = count.checked_mul(entsize).ok_or_else(|| invalid(format!("{} size overflows",what)))?;
+    let range = checked_range(len, offset, size, what)?;
+    if range.start < EHDR_SIZE {
+        return Err(invalid(format!("{} at {:#x} overlaps the ELF header",what,offset)));
+    }
+    Ok(range)
+}
+
+/// 两个范围是否重叠, 空范围不与任何范围重叠
+pub fn overlaps(a:&Range<u64>, b:&Range<u64>) -> bool {
+    a.start < a.end && b.start < b.end && a.start < b.end && b.start < a.end
+}
+
+/// 读取文件中[offset, offset + size)的内容
+///
+/// 先检查范围是否在文件内, 声明的大小不可信, 不能按它直接分配内存
+pub fn read_at<R:Read + Seek>(elf:&mut R, offset:u64, size:u64, what:&str) -> io::Result<Vec<u8>> {
+    let len = file_len(elf)?;
+    let range = checked_range(len, offset, size, what)?;
+    elf.seek(io::SeekFrom::Start(range.start))?;
+    let mut data = vec![0u8;size as usize];
+    elf.read_exact(&mut data)?;
+    Ok(data)
+}
+
+/// 字符串表中offset处以'\0'结尾的字符串, offset超出表时返回None
+///
+/// 合法的UTF-8借用自表, 否则按有损转换复制, 一个无效的名字不影响其他名字的解析
+pub fn string_at(table:&[u8], offset:usize) -> Option<Cow<'_, str>> {
+    let bytes = table.get(offset..)?;
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    Some(String::from_utf8_lossy(&bytes[..len]))
+}
@@ -0,0 +1,436 @@
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::elf::header::{parse_header, HeaderType, MachineType};
+use crate::elf::note::*;
+use crate::elf::segment::{parse_segments, ElfSegment, SegmentType};
+use crate::json::impl_to_json;
+
+// 辅助向量(NT_AUXV)中的类型
+pub const AT_NULL:u64 = 0;
+pub const AT_IGNORE:u64 = 1;
+pub const AT_EXECFD:u64 = 2;
+pub const AT_PHDR:u64 = 3;
+pub const AT_PHENT:u64 = 4;
+pub const AT_PHNUM:u64 = 5;
+pub const AT_PAGESZ:u64 = 6;
+pub const AT_BASE:u64 = 7;
+pub const AT_FLAGS:u64 = 8;
+pub const AT_ENTRY:u64 = 9;
+pub const AT_NOTELF:u64 = 10;
+pub const AT_UID:u64 = 11;
+pub const AT_EUID:u64 = 12;
+pub const AT_GID:u64 = 13;
+pub const AT_EGID:u64 = 14;
+pub const AT_PLATFORM:u64 = 15;
+pub const AT_HWCAP:u64 = 16;
+pub const AT_CLKTCK:u64 = 17;
+pub const AT_SECURE:u64 = 23;
+pub const AT_BASE_PLATFORM:u64 = 24;
+pub const AT_RANDOM:u64 = 25;
+pub const AT_HWCAP2:u64 = 26;
+pub const AT_RSEQ_FEATURE_SIZE:u64 = 27;
+pub const AT_RSEQ_ALIGN:u64 = 28;
+pub const AT_HWCAP3:u64 = 29;
+pub const AT_HWCAP4:u64 = 30;
+pub const AT_EXECFN:u64 = 31;
+pub const AT_SYSINFO_EHDR:u64 = 33;
+pub const AT_MINSIGSTKSZ:u64 = 51;
+
+/// elf_prstatus中寄存器之前的部分(pr_info到pr_cstime)的大小
+const PRSTATUS_REGS_OFFSET:usize = 112;
+
+// 用户态寄存器在elf_prstatus.pr_reg中的顺序
+const X86_64_REGISTERS:[&str;27] = ["r15", "r14", "r13", "r12", "rbp", "rbx", "r11", "r10", "r9", "r8", "rax", "rcx", "rdx", "rsi", "rdi", "orig_rax", "rip", "cs", "eflags", "rsp", "ss", "fs_base", "gs_base", "ds", "es", "fs", "gs"];
+const AARCH64_REGISTERS:[&str;34] = ["x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13", "x14", "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27", "x28", "x29", "x30", "sp", "pc", "pstate"];
+
+/// 一个线程的状态(NT_PRSTATUS)
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PrStatus {
+    pub signal:i32,
+    pub code:i32,
+    pub current_signal:u16,
+    pub pid:i32,
+    pub ppid:i32,
+    pub pgrp:i32,
+    pub sid:i32,
+    /// 寄存器名及值, 不支持的架构为空
+    pub registers:Vec<(&'static str, u64)>,
+}
+
+impl_to_json!(PrStatus { signal, code, current_signal, pid, ppid, pgrp, sid, registers });
+
+/// 进程信息(NT_PRPSINFO)
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PrPsInfo {
+    pub state:u8,
+    pub sname:char,
+    pub zombie:bool,
+    pub nice:i8,
+    pub flag:u64,
+    pub uid:u32,
+    pub gid:u32,
+    pub pid:i32,
+    pub ppid:i32,
+    pub pgrp:i32,
+    pub sid:i32,
+    pub fname:String,
+    pub psargs:String,
+}
+
+impl_to_json!(PrPsInfo { state, sname, zombie, nice, flag, uid, gid, pid, ppid, pgrp, sid, fname, psargs });
+
+/// 导致转储的信号(NT_SIGINFO)
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SigInfo {
+    pub signo:i32,
+    pub errno:i32,
+    pub code:i32,
+    /// SIGSEGV/SIGBUS/SIGILL/SIGFPE/SIGTRAP的出错地址
+    pub address:Option<u64>,
+}
+
+impl_to_json!(SigInfo { signo, errno, code, address });
+
+/// NT_FILE中的一项: 映射的地址范围和文件偏移
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MappedFile {
+    pub start:u64,
+    pub end:u64,
+    pub offset:u64,
+    pub path:String,
+}
+
+impl_to_json!(MappedFile { start, end, offset, path });
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CoreDump {
+    pub machine:MachineType,
+    pub threads:Vec<PrStatus>,
+    pub process:Option<PrPsInfo>,
+    pub signal:Option<SigInfo>,
+    pub auxv:Vec<(u64, u64)>,
+    pub page_size:u64,
+    pub files:Vec<MappedFile>,
+    pub fpregset:bool,
+    /// NT_X86_XSTATE的大小
+    pub xstate:Option<usize>,
+    pub loads:Vec<ElfSegment>,
+    pub notes:Vec<ElfNote>,
+}
+
+impl_to_json!(CoreDump { machine, threads, process, signal, auxv, page_size, files, fpregset, xstate, loads, notes });
+
+fn u64_at(data:&[u8], pos:usize) -> u64 {
+    data.get(pos..pos + 8).map(|b| u64::from_le_bytes(b.try_into().unwrap())).unwrap_or(0)
+}
+
+fn i32_at(data:&[u8], pos:usize) -> i32 {
+    data.get(pos..pos + 4).map(|b| i32::from_le_bytes(b.try_into().unwrap())).unwrap_or(0)
+}
+
+fn cstring(data:&[u8]) -> String {
+    let len = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..len]).trim_end().to_string()
+}
+
+fn truncated(what:&str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("truncated {} note", what))
+}
+
+impl PrStatus {
+    pub fn parse(desc:&[u8], machine:&MachineType) -> io::Result<Self> {
+        let names:&[&'static str] = match machine {
+            MachineType::AMDX86_64 => &X86_64_REGISTERS,
+            MachineType::AArch64 => &AARCH64_REGISTERS,
+            _ => &[],
+        };
+        if desc.len() < PRSTATUS_REGS_OFFSET + names.len() * 8 {
+            return Err(truncated("NT_PRSTATUS"));
+        }
+        let registers = names.iter().enumerate()
+            .map(|(i, &name)| (name, u64_at(desc, PRSTATUS_REGS_OFFSET + i * 8)))
+            .collect();
+        Ok(Self {
+            signal:i32_at(desc, 0),
+            code:i32_at(desc, 4),
+            current_signal:u16::from_le_bytes(desc[12..14].try_into().unwrap()),
+            pid:i32_at(desc, 32),
+            ppid:i32_at(desc, 36),
+            pgrp:i32_at(desc, 40),
+            sid:i32_at(desc, 44),
+            registers,
+        })
+    }
+
+    /// 程序计数器的值
+    pub fn pc(&self) -> Option<u64> {
+        self.registers.iter().find(|(name, _)| *name == "rip" || *name == "pc").map(|&(_, value)| value)
+    }
+}
+
+impl PrPsInfo {
+    pub fn parse(desc:&[u8]) -> io::Result<Self> {
+        if desc.len() < 136 {
+            return Err(truncated("NT_PRPSINFO"));
+        }
+        Ok(Self {
+            state:desc[0],
+            sname:desc[1] as char,
+            zombie:desc[2] != 0,
+            nice:desc[3] as i8,
+            flag:u64_at(desc, 8),
+            uid:i32_at(desc, 16) as u32,
+            gid:i32_at(desc, 20) as u32,
+            pid:i32_at(desc, 24),
+            ppid:i32_at(desc, 28),
+            pgrp:i32_at(desc, 32),
+            sid:i32_at(desc, 36),
+            fname:cstring(&desc[40..56]),
+            psargs:cstring(&desc[56..136]),
+        })
+    }
+}
+
+impl SigInfo {
+    pub fn parse(desc:&[u8]) -> io::Result<Self> {
+        if desc.len() < 24 {
+            return Err(truncated("NT_SIGINFO"));
+        }
+        let signo = i32_at(desc, 0);
+        // SIGILL, SIGTRAP, SIGBUS, SIGFPE, SIGSEGV的si_addr位于联合体开头
+        let address = matches!(signo, 4 | 5 | 7 | 8 | 11).then(|| u64_at(desc, 16));
+        Ok(Self { signo, errno:i32_at(desc, 4), code:i32_at(desc, 8), address })
+    }
+}
+
+/// 解析NT_FILE: 映射数量, 页大小, (起始, 结束, 页偏移)数组, 然后是文件名
+fn parse_mapped_files(desc:&[u8]) -> io::Result<(u64, Vec<MappedFile>)> {
+    if desc.len() < 16 {
+        return Err(truncated("NT_FILE"));
+    }
+    let count = u64_at(desc, 0) as usize;
+    let page_size = u64_at(desc, 8);
+    let names_start = count.checked_mul(24).and_then(|size| size.checked_add(16))
+        .filter(|&start| start <= desc.len())
+        .ok_or_else(|| truncated("NT_FILE"))?;
+    let mut names = desc[names_start..].split(|&b| b == 0);
+    let mut files = Vec::new();
+    for i in 0..count {
+        let entry = 16 + i * 24;
+        files.push(MappedFile {
+            start:u64_at(desc, entry),
+            end:u64_at(desc, entry + 8),
+            offset:u64_at(desc, entry + 16).wrapping_mul(page_size),
+            path:names.next().map(|name| String::from_utf8_lossy(name).into_owned()).unwrap_or_default(),
+        });
+    }
+    Ok((page_size, files))
+}
+
+pub fn signal_name(signal:i32) -> &'static str {
+    match signal {
+        1 => "SIGHUP",
+        2 => "SIGINT",
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        5 => "SIGTRAP",
+        6 => "SIGABRT",
+        7 => "SIGBUS",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        10 => "SIGUSR1",
+        11 => "SIGSEGV",
+        12 => "SIGUSR2",
+        13 => "SIGPIPE",
+        14 => "SIGALRM",
+        15 => "SIGTERM",
+        16 => "SIGSTKFLT",
+        17 => "SIGCHLD",
+        18 => "SIGCONT",
+        19 => "SIGSTOP",
+        20 => "SIGTSTP",
+        21 => "SIGTTIN",
+        22 => "SIGTTOU",
+        23 => "SIGURG",
+        24 => "SIGXCPU",
+        25 => "SIGXFSZ",
+        26 => "SIGVTALRM",
+        27 => "SIGPROF",
+        28 => "SIGWINCH",
+        29 => "SIGIO",
+        30 => "SIGPWR",
+        31 => "SIGSYS",
+        _ => "?",
+    }
+}
+
+pub fn auxv_name(key:u64) -> String {
+    let name = match key {
+        AT_NULL => "AT_NULL",
+        AT_IGNORE => "AT_IGNORE",
+        AT_EXECFD => "AT_EXECFD",
+        AT_PHDR => "AT_PHDR",
+        AT_PHENT => "AT_PHENT",
+        AT_PHNUM => "AT_PHNUM",
+        AT_PAGESZ => "AT_PAGESZ",
+        AT_BASE => "AT_BASE",
+        AT_FLAGS => "AT_FLAGS",
+        AT_ENTRY => "AT_ENTRY",
+        AT_NOTELF => "AT_NOTELF",
+        AT_UID => "AT_UID",
+        AT_EUID => "AT_EUID",
+        AT_GID => "AT_GID",
+        AT_EGID => "AT_EGID",
+        AT_PLATFORM => "AT_PLATFORM",
+        AT_HWCAP => "AT_HWCAP",
+        AT_CLKTCK => "AT_CLKTCK",
+        AT_SECURE => "AT_SECURE",
+        AT_BASE_PLATFORM => "AT_BASE_PLATFORM",
+        AT_RANDOM => "AT_RANDOM",
+        AT_HWCAP2 => "AT_HWCAP2",
+        AT_RSEQ_FEATURE_SIZE => "AT_RSEQ_FEATURE_SIZE",
+        AT_RSEQ_ALIGN => "AT_RSEQ_ALIGN",
+        AT_HWCAP3 => "AT_HWCAP3",
+        AT_HWCAP4 => "AT_HWCAP4",
+        AT_EXECFN => "AT_EXECFN",
+        AT_SYSINFO_EHDR => "AT_SYSINFO_EHDR",
+        AT_MINSIGSTKSZ => "AT_MINSIGSTKSZ",
+        _ => return format!("AT_<{}>",key),
+    };
+    String::from(name)
+}
+
+/// 进程状态字符(R, S, D, T, Z...)的含义
+fn state_name(sname:char) -> &'static str {
+    match sname {
+        'R' => "running",
+        'S' => "sleeping",
+        'D' => "disk sleep",
+        'T' => "stopped",
+        't' => "tracing stop",
+        'Z' => "zombie",
+        'X' => "dead",
+        _ => "unknown",
+    }
+}
+
+impl CoreDump {
+    /// 包含address的文件映射
+    pub fn mapping_at(&self, address:u64) -> Option<&MappedFile> {
+        self.files.iter().find(|file| address >= file.start && address < file.end)
+    }
+
+    /// 辅助向量中key对应的值
+    pub fn auxv_value(&self, key:u64) -> Option<u64> {
+        self.auxv.iter().find(|&&(k, _)| k == key).map(|&(_, value)| value)
+    }
+}
+
+/// 解析core文件中的注释和PT_LOAD段
+pub fn parse_core<R:Read + Seek>(elf:&mut R) -> io::Result<CoreDump> {
+    let header = parse_header(elf)?;
+    if !matches!(header.e_type, HeaderType::CORE) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "not a core file"));
+    }
+    let loads = parse_segments(elf)?.into_iter()
+        .filter(|segment| matches!(segment.p_type, SegmentType::LOAD))
+        .collect();
+    let mut core = CoreDump {
+        machine:header.e_machine,
+        threads:Vec::new(),
+        process:None,
+        signal:None,
+        auxv:Vec::new(),
+        page_size:0,
+        files:Vec::new(),
+        fpregset:false,
+        xstate:None,
+        loads,
+        notes:Vec::new(),
+    };
+    let notes = parse_note_segments(elf)?;
+    for note in &notes {
+        match (note.name.as_str(), note.n_type) {
+            ("CORE", NT_PRSTATUS) => core.threads.push(PrStatus::parse(&note.desc, &core.machine)?),
+            ("CORE", NT_PRPSINFO) => core.process = Some(PrPsInfo::parse(&note.desc)?),
+            ("CORE", NT_SIGINFO) => core.signal = Some(SigInfo::parse(&note.desc)?),
+            ("CORE", NT_AUXV) => {
+                core.auxv = note.desc.chunks_exact(16)
+                    .map(|pair| (u64_at(pair, 0), u64_at(pair, 8)))
+                    .take_while(|&(key, _)| key != AT_NULL)
+                    .collect();
+            }
+            ("CORE", NT_FILE) => (core.page_size, core.files) = parse_mapped_files(&note.desc)?,
+            ("CORE", NT_FPREGSET) => core.fpregset = true,
+            ("LINUX", NT_X86_XSTATE) => core.xstate = Some(note.desc.len()),
+            _ => {}
+        }
+    }
+    core.notes = notes;
+    Ok(core)
+}
+
+impl std::fmt::Display for CoreDump {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(process) = &self.process {
+            writeln!(f,"Process:")?;
+            writeln!(f,"  {:<10}{}","Command:",process.fname)?;
+            writeln!(f,"  {:<10}{}","Args:",process.psargs)?;
+            writeln!(f,"  {:<10}{} ({})","State:",process.sname,state_name(process.sname))?;
+            writeln!(f,"  {:<10}{}  PPID: {}  PGRP: {}  SID: {}","PID:",process.pid,process.ppid,process.pgrp,process.sid)?;
+            writeln!(f,"  {:<10}{}  GID: {}","UID:",process.uid,process.gid)?;
+            writeln!(f)?;
+        }
+        if let Some(signal) = &self.signal {
+            write!(f,"Signal: {} ({}) code={} errno={}",signal.signo,signal_name(signal.signo),signal.code,signal.errno)?;
+            if let Some(address) = signal.address {
+                write!(f," fault address={:#x}",address)?;
+            }
+            writeln!(f)?;
+            writeln!(f)?;
+        }
+        writeln!(f,"Threads: {}",self.threads.len())?;
+        for (i, thread) in self.threads.iter().enumerate() {
+            write!(f,"  Thread {} (LWP {}): signal {} ({})",i + 1,thread.pid,thread.current_signal,signal_name(thread.current_signal as i32))?;
+            if let Some(pc) = thread.pc() {
+                write!(f," pc={:#x}",pc)?;
+                if let Some(file) = self.mapping_at(pc) {
+                    write!(f," in {}",file.path)?;
+                }
+            }
+            writeln!(f)?;
+            for row in thread.registers.chunks(3) {
+                write!(f,"   ")?;
+                for (name, value) in row {
+                    write!(f," {:<8} {:#018x}",name,value)?;
+                }
+                writeln!(f)?;
+            }
+        }
+        writeln!(f,"  Floating point registers: {}",if self.fpregset { "present" } else { "absent" })?;
+        match self.xstate {
+            Some(size) => writeln!(f,"  XSAVE extended state: present ({} bytes)",size)?,
+            None => writeln!(f,"  XSAVE extended state: absent")?,
+        }
+        writeln!(f)?;
+        writeln!(f,"Auxiliary vector:")?;
+        for &(key, value) in &self.auxv {
+            writeln!(f,"  {:<22}{:#x}",auxv_name(key),value)?;
+        }
+        writeln!(f)?;
+        writeln!(f,"Mapped files (page size {}):",self.page_size)?;
+        writeln!(f,"  {:<18} {:<18} {:<18} Path","Start","End","File offset")?;
+        for file in &self.files {
+            writeln!(f,"  {:#018x} {:#018x} {:#018x} {}",file.start,file.end,file.offset,file.path)?;
+        }
+        writeln!(f)?;
+        writeln!(f,"Memory segments:")?;
+        writeln!(f,"  {:<18} {:<18} {:<18} {:<18} {:<5} Backing file","VirtAddr","MemSiz","Offset","FileSiz","Flags")?;
+        for segment in &self.loads {
+            let backing = self.mapping_at(segment.p_vaddr).map(|file| file.path.as_str()).unwrap_or("");
+            writeln!(f,"  {:#018x} {:#018x} {:#018x} {:#018x} {:<5} {}",segment.p_vaddr,segment.p_memsz,segment.p_offset,segment.p_filesz,segment.p_flags.to_string(),backing)?;
+        }
+        Ok(())
+    }
+}
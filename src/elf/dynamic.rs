@@ -0,0 +1,250 @@
+use std::borrow::Cow;
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::elf::bounds::{read_at, string_at};
+use crate::elf::section::{parse_sections, read_section_data};
+use crate::elf::segment::{parse_segments, read_segment_data, vaddr_to_offset, SegmentType};
+use crate::json::impl_to_json;
+
+pub const SHT_DYNAMIC:u32 = 6;
+
+pub const DT_NULL:u64 = 0;
+pub const DT_NEEDED:u64 = 1;
+pub const DT_PLTRELSZ:u64 = 2;
+pub const DT_PLTGOT:u64 = 3;
+pub const DT_HASH:u64 = 4;
+pub const DT_STRTAB:u64 = 5;
+pub const DT_SYMTAB:u64 = 6;
+pub const DT_RELA:u64 = 7;
+pub const DT_RELASZ:u64 = 8;
+pub const DT_RELAENT:u64 = 9;
+pub const DT_STRSZ:u64 = 10;
+pub const DT_SYMENT:u64 = 11;
+pub const DT_INIT:u64 = 12;
+pub const DT_FINI:u64 = 13;
+pub const DT_SONAME:u64 = 14;
+pub const DT_RPATH:u64 = 15;
+pub const DT_SYMBOLIC:u64 = 16;
+pub const DT_REL:u64 = 17;
+pub const DT_RELSZ:u64 = 18;
+pub const DT_RELENT:u64 = 19;
+pub const DT_PLTREL:u64 = 20;
+pub const DT_DEBUG:u64 = 21;
+pub const DT_TEXTREL:u64 = 22;
+pub const DT_JMPREL:u64 = 23;
+pub const DT_BIND_NOW:u64 = 24;
+pub const DT_INIT_ARRAY:u64 = 25;
+pub const DT_FINI_ARRAY:u64 = 26;
+pub const DT_INIT_ARRAYSZ:u64 = 27;
+pub const DT_FINI_ARRAYSZ:u64 = 28;
+pub const DT_RUNPATH:u64 = 29;
+pub const DT_FLAGS:u64 = 30;
+pub const DT_PREINIT_ARRAY:u64 = 32;
+pub const DT_PREINIT_ARRAYSZ:u64 = 33;
+pub const DT_SYMTAB_SHNDX:u64 = 34;
+pub const DT_RELRSZ:u64 = 35;
+pub const DT_RELR:u64 = 36;
+pub const DT_RELRENT:u64 = 37;
+pub const DT_GNU_HASH:u64 = 0x6ffffef5;
+pub const DT_VERSYM:u64 = 0x6ffffff0;
+pub const DT_RELACOUNT:u64 = 0x6ffffff9;
+pub const DT_RELCOUNT:u64 = 0x6ffffffa;
+pub const DT_FLAGS_1:u64 = 0x6ffffffb;
+pub const DT_VERDEF:u64 = 0x6ffffffc;
+pub const DT_VERDEFNUM:u64 = 0x6ffffffd;
+pub const DT_VERNEED:u64 = 0x6ffffffe;
+pub const DT_VERNEEDNUM:u64 = 0x6fffffff;
+
+// DT_FLAGS中的标志
+pub const DF_ORIGIN:u64 = 0x1;
+pub const DF_SYMBOLIC:u64 = 0x2;
+pub const DF_TEXTREL:u64 = 0x4;
+pub const DF_BIND_NOW:u64 = 0x8;
+pub const DF_STATIC_TLS:u64 = 0x10;
+
+// DT_FLAGS_1中的标志
+pub const DF_1_NOW:u64 = 0x1;
+pub const DF_1_GLOBAL:u64 = 0x2;
+pub const DF_1_NODELETE:u64 = 0x8;
+pub const DF_1_INITFIRST:u64 = 0x20;
+pub const DF_1_NOOPEN:u64 = 0x40;
+pub const DF_1_ORIGIN:u64 = 0x80;
+pub const DF_1_INTERPOSE:u64 = 0x400;
+pub const DF_1_NODEFLIB:u64 = 0x800;
+pub const DF_1_PIE:u64 = 0x08000000;
+
+/// 64位动态段表项的大小
+const DYNAMIC_SIZE:usize = 16;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ElfDynamic {
+    pub d_tag:u64,
+    pub d_val:u64,
+    /// NEEDED, SONAME, RPATH, RUNPATH等表项在.dynstr中的字符串
+    pub string:Option<String>,
+}
+
+impl_to_json!(ElfDynamic { d_tag, d_val, string });
+
+impl From<Vec<u8>> for ElfDynamic {
+    fn from(data: Vec<u8>) -> Self {
+        Self {
+            d_tag: u64::from_ne_bytes(data[0..8].try_into().unwrap()),
+            d_val: u64::from_ne_bytes(data[8..16].try_into().unwrap()),
+            string:None,
+        }
+    }
+}
+
+/// 值为.dynstr偏移的表项
+pub fn has_string(tag:u64) -> bool {
+    matches!(tag, DT_NEEDED | DT_SONAME | DT_RPATH | DT_RUNPATH)
+}
+
+pub fn tag_name(tag:u64) -> String {
+    let name = match tag {
+        DT_NULL => "NULL",
+        DT_NEEDED => "NEEDED",
+        DT_PLTRELSZ => "PLTRELSZ",
+        DT_PLTGOT => "PLTGOT",
+        DT_HASH => "HASH",
+        DT_STRTAB => "STRTAB",
+        DT_SYMTAB => "SYMTAB",
+        DT_RELA => "RELA",
+        DT_RELASZ => "RELASZ",
+        DT_RELAENT => "RELAENT",
+        DT_STRSZ => "STRSZ",
+        DT_SYMENT => "SYMENT",
+        DT_INIT => "INIT",
+        DT_FINI => "FINI",
+        DT_SONAME => "SONAME",
+        DT_RPATH => "RPATH",
+        DT_SYMBOLIC => "SYMBOLIC",
+        DT_REL => "REL",
+        DT_RELSZ => "RELSZ",
+        DT_RELENT => "RELENT",
+        DT_PLTREL => "PLTREL",
+        DT_DEBUG => "DEBUG",
+        DT_TEXTREL => "TEXTREL",
+        DT_JMPREL => "JMPREL",
+        DT_BIND_NOW => "BIND_NOW",
+        DT_INIT_ARRAY => "INIT_ARRAY",
+        DT_FINI_ARRAY => "FINI_ARRAY",
+        DT_INIT_ARRAYSZ => "INIT_ARRAYSZ",
+        DT_FINI_ARRAYSZ => "FINI_ARRAYSZ",
+        DT_RUNPATH => "RUNPATH",
+        DT_FLAGS => "FLAGS",
+        DT_PREINIT_ARRAY => "PREINIT_ARRAY",
+        DT_PREINIT_ARRAYSZ => "PREINIT_ARRAYSZ",
+        DT_SYMTAB_SHNDX => "SYMTAB_SHNDX",
+        DT_RELRSZ => "RELRSZ",
+        DT_RELR => "RELR",
+        DT_RELRENT => "RELRENT",
+        DT_GNU_HASH => "GNU_HASH",
+        DT_VERSYM => "VERSYM",
+        DT_RELACOUNT => "RELACOUNT",
+        DT_RELCOUNT => "RELCOUNT",
+        DT_FLAGS_1 => "FLAGS_1",
+        DT_VERDEF => "VERDEF",
+        DT_VERDEFNUM => "VERDEFNUM",
+        DT_VERNEED => "VERNEED",
+        DT_VERNEEDNUM => "VERNEEDNUM",
+        _ => return format!("<unknown>: {:#x}",tag),
+    };
+    String::from(name)
+}
+
+fn flags_to_string(value:u64, names:&[(u64, &str)]) -> String {
+    let mut flags:Vec<String> = names.iter()
+        .filter(|&&(flag, _)| value & flag != 0)
+        .map(|&(_, name)| String::from(name))
+        .collect();
+    let known = names.iter().fold(0, |all, &(flag, _)| all | flag);
+    if value & !known != 0 {
+        flags.push(format!("{:#x}",value & !known));
+    }
+    flags.join(" ")
+}
+
+impl ElfDynamic {
+    /// readelf -d中Name/Value一栏的内容
+    pub fn value(&self) -> String {
+        let string = self.string.as_deref().unwrap_or("");
+        match self.d_tag {
+            DT_NEEDED => format!("Shared library: [{}]",string),
+            DT_SONAME => format!("Library soname: [{}]",string),
+            DT_RPATH => format!("Library rpath: [{}]",string),
+            DT_RUNPATH => format!("Library runpath: [{}]",string),
+            DT_FLAGS => flags_to_string(self.d_val, &[(DF_ORIGIN, "ORIGIN"), (DF_SYMBOLIC, "SYMBOLIC"), (DF_TEXTREL, "TEXTREL"), (DF_BIND_NOW, "BIND_NOW"), (DF_STATIC_TLS, "STATIC_TLS")]),
+            DT_FLAGS_1 => format!("Flags: {}",flags_to_string(self.d_val, &[(DF_1_NOW, "NOW"), (DF_1_GLOBAL, "GLOBAL"), (DF_1_NODELETE, "NODELETE"), (DF_1_INITFIRST, "INITFIRST"), (DF_1_NOOPEN, "NOOPEN"), (DF_1_ORIGIN, "ORIGIN"), (DF_1_INTERPOSE, "INTERPOSE"), (DF_1_NODEFLIB, "NODEFLIB"), (DF_1_PIE, "PIE")])),
+            DT_PLTREL => String::from(match self.d_val { DT_RELA => "RELA", DT_REL => "REL", _ => "?" }),
+            DT_PLTRELSZ | DT_RELASZ | DT_RELAENT | DT_STRSZ | DT_SYMENT | DT_RELSZ | DT_RELENT
+            | DT_INIT_ARRAYSZ | DT_FINI_ARRAYSZ | DT_PREINIT_ARRAYSZ | DT_RELRSZ | DT_RELRENT => format!("{} (bytes)",self.d_val),
+            DT_VERDEFNUM | DT_VERNEEDNUM | DT_RELACOUNT | DT_RELCOUNT => self.d_val.to_string(),
+            _ => format!("{:#x}",self.d_val),
+        }
+    }
+}
+
+impl std::fmt::Display for ElfDynamic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f," {:#018x} {:<20} {}",self.d_tag,format!("({})",tag_name(self.d_tag)),self.value())
+    }
+}
+
+/// 第一个tag表项的值
+pub fn dynamic_value(entries:&[ElfDynamic], tag:u64) -> Option<u64> {
+    entries.iter().find(|entry| entry.d_tag == tag).map(|entry| entry.d_val)
+}
+
+/// 所有tag表项的字符串, 如全部DT_NEEDED
+pub fn dynamic_strings(entries:&[ElfDynamic], tag:u64) -> Vec<&str> {
+    entries.iter()
+        .filter(|entry| entry.d_tag == tag)
+        .filter_map(|entry| entry.string.as_deref())
+        .collect()
+}
+
+fn parse_entries(data:&[u8], strtab:&[u8]) -> Vec<ElfDynamic> {
+    let mut entries = Vec::new();
+    for chunk in data.chunks_exact(DYNAMIC_SIZE) {
+        let mut entry = ElfDynamic::from(chunk.to_vec());
+        if has_string(entry.d_tag) {
+            entry.string = string_at(strtab, entry.d_val as usize).map(Cow::into_owned);
+        }
+        let end = entry.d_tag == DT_NULL;
+        entries.push(entry);
+        if end {
+            break;
+        }
+    }
+    entries
+}
+
+/// 解析动态段, 优先使用.dynamic节, 没有节头时通过PT_DYNAMIC和DT_STRTAB读取
+pub fn parse_dynamic<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<ElfDynamic>> {
+    let sections = parse_sections(elf).unwrap_or_default();
+    if let Some(section) = sections.iter().find(|section| section.sh_type == SHT_DYNAMIC) {
+        let data = read_section_data(elf, section)?;
+        let strtab = match sections.get(section.sh_link as usize) {
+            Some(strtab) => read_section_data(elf, strtab)?,
+            None => Vec::new(),
+        };
+        return Ok(parse_entries(&data, &strtab));
+    }
+    let segments = parse_segments(elf)?;
+    let Some(segment) = segments.iter().find(|segment| matches!(segment.p_type, SegmentType::DYNAMIC)) else {
+        return Ok(Vec::new());
+    };
+    let data = read_segment_data(elf, segment)?;
+    let entries = parse_entries(&data, &[]);
+    let strtab = match (dynamic_value(&entries, DT_STRTAB), dynamic_value(&entries, DT_STRSZ)) {
+        (Some(address), Some(size)) => match vaddr_to_offset(&segments, address) {
+            Some(offset) => read_at(elf, offset, size, "DT_STRTAB")?,
+            None => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+    Ok(parse_entries(&data, &strtab))
+}
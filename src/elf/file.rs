@@ -0,0 +1,199 @@
+use std::cell::OnceCell;
+use std::io;
+use std::io::Cursor;
+use std::path::Path;
+use crate::dwarf::info::{parse_units, CompilationUnit};
+use crate::dwarf::line::{parse_line_programs, LineProgram};
+use crate::dwarf::sections::DwarfSections;
+use crate::elf::bounds::checked_range;
+use crate::elf::header::ElfHeader;
+use crate::elf::mapped::{MappedElf, MappedFile};
+use crate::elf::note::{parse_notes, ElfNote, SHT_NOTE};
+use crate::elf::section::{find_section, ElfSection, SHF_COMPRESSED, SHT_NOBITS};
+use crate::elf::segment::{read_vaddr, ElfSegment, SegmentType};
+use crate::elf::symbol::{read_symbol_table, ElfSymbol, SHT_DYNSYM, SHT_SYMTAB};
+
+enum Data {
+    Mapped(MappedFile),
+    Owned(Vec<u8>),
+}
+
+/// 按需解析的ELF文件
+///
+/// 打开时只解析文件头; 节表(包括节名字符串表), 段表, 符号表, 注释和DWARF在第一次访问时解析并缓存,
+/// 只需要build-id的调用者不会解析节表
+pub struct ElfFile {
+    data:Data,
+    header:ElfHeader,
+    sections:OnceCell<Vec<ElfSection>>,
+    segments:OnceCell<Vec<ElfSegment>>,
+    symbols:OnceCell<Vec<ElfSymbol>>,
+    dynamic_symbols:OnceCell<Vec<ElfSymbol>>,
+    notes:OnceCell<Vec<ElfNote>>,
+    dwarf:OnceCell<DwarfSections>,
+    units:OnceCell<Vec<CompilationUnit>>,
+    line_programs:OnceCell<Vec<LineProgram>>,
+}
+
+/// 返回缓存的值, 第一次访问时用init计算; 计算失败时不缓存, 下次访问重新计算
+fn cached<T>(cell:&OnceCell<T>, init:impl FnOnce() -> io::Result<T>) -> io::Result<&T> {
+    if let Some(value) = cell.get() {
+        return Ok(value);
+    }
+    let value = init()?;
+    Ok(cell.get_or_init(|| value))
+}
+
+impl ElfFile {
+    /// 通过内存映射打开文件
+    pub fn open<P:AsRef<Path>>(path:P) -> io::Result<Self> {
+        Self::new(Data::Mapped(MappedFile::open(path)?))
+    }
+
+    /// 使用已经映射的文件, 如调用者先检查了文件类型
+    pub fn from_mapped(map:MappedFile) -> io::Result<Self> {
+        Self::new(Data::Mapped(map))
+    }
+
+    /// 解析内存中的文件内容, 如静态库的成员
+    pub fn from_bytes(data:Vec<u8>) -> io::Result<Self> {
+        Self::new(Data::Owned(data))
+    }
+
+    fn new(data:Data) -> io::Result<Self> {
+        let header = match &data {
+            Data::Mapped(map) => map.elf()?.header,
+            Data::Owned(bytes) => MappedElf::parse(bytes)?.header,
+        };
+        Ok(Self {
+            data,
+            header,
+            sections:OnceCell::new(),
+            segments:OnceCell::new(),
+            symbols:OnceCell::new(),
+            dynamic_symbols:OnceCell::new(),
+            notes:OnceCell::new(),
+            dwarf:OnceCell::new(),
+            units:OnceCell::new(),
+            line_programs:OnceCell::new(),
+        })
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        match &self.data {
+            Data::Mapped(map) => map.bytes(),
+            Data::Owned(bytes) => bytes,
+        }
+    }
+
+    /// 供没有缓存的`parse_*`函数使用
+    pub fn cursor(&self) -> Cursor<&[u8]> {
+        Cursor::new(self.bytes())
+    }
+
+    pub fn header(&self) -> &ElfHeader {
+        &self.header
+    }
+
+    /// 文件数据上的MappedElf, 节头表和段头表直接从文件数据中解析
+    fn elf(&self) -> io::Result<MappedElf<'_>> {
+        MappedElf::parse(self.bytes())
+    }
+
+    pub fn sections(&self) -> io::Result<&[ElfSection]> {
+        cached(&self.sections, || self.elf()?.section_headers()).map(Vec::as_slice)
+    }
+
+    pub fn section_by_name(&self, name:&str) -> io::Result<Option<&ElfSection>> {
+        Ok(find_section(self.sections()?, name))
+    }
+
+    /// 节的内容, 借用自文件数据; NOBITS节返回空
+    pub fn section_data(&self, section:&ElfSection) -> io::Result<&[u8]> {
+        if section.sh_type == SHT_NOBITS {
+            return Ok(&[]);
+        }
+        if section.sh_flags & SHF_COMPRESSED != 0 {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, format!("compressed section {} is not supported", section.name)));
+        }
+        self.range(section.sh_offset, section.sh_size, &format!("section {}",section.name))
+    }
+
+    /// 与bounds::read_at相同的检查和错误信息, 但不复制
+    fn range(&self, offset:u64, size:u64, what:&str) -> io::Result<&[u8]> {
+        let range = checked_range(self.bytes().len() as u64, offset, size, what)?;
+        Ok(&self.bytes()[range.start as usize..range.end as usize])
+    }
+
+    pub fn segments(&self) -> io::Result<&[ElfSegment]> {
+        cached(&self.segments, || self.elf()?.segments()).map(Vec::as_slice)
+    }
+
+    /// 按加载器映射的结果读取虚拟地址[vaddr, vaddr + len)的内容, .bss等部分以0填充, 未映射的地址返回错误
+    pub fn read_vaddr(&self, vaddr:u64, len:u64) -> io::Result<Vec<u8>> {
+        read_vaddr(&mut self.cursor(), self.segments()?, vaddr, len)
+    }
+
+    fn symbols_of_type(&self, sh_type:u32) -> io::Result<Vec<ElfSymbol>> {
+        let sections = self.sections()?;
+        match sections.iter().find(|section| section.sh_type == sh_type) {
+            Some(table) => read_symbol_table(&mut self.cursor(), sections, table),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// .symtab中的符号, 被strip的文件返回空
+    pub fn symbols(&self) -> io::Result<&[ElfSymbol]> {
+        cached(&self.symbols, || self.symbols_of_type(SHT_SYMTAB)).map(Vec::as_slice)
+    }
+
+    /// .dynsym中的符号
+    pub fn dynamic_symbols(&self) -> io::Result<&[ElfSymbol]> {
+        cached(&self.dynamic_symbols, || self.symbols_of_type(SHT_DYNSYM)).map(Vec::as_slice)
+    }
+
+    /// PT_NOTE段中的注释, 没有PT_NOTE段时(如可重定位文件)读取SHT_NOTE节
+    pub fn notes(&self) -> io::Result<&[ElfNote]> {
+        cached(&self.notes, || {
+            let mut notes = Vec::new();
+            let mut found = false;
+            for segment in self.segments()?.iter().filter(|segment| matches!(segment.p_type, SegmentType::NOTE)) {
+                found = true;
+                notes.extend(parse_notes(self.range(segment.p_offset, segment.p_filesz, &format!("{} segment",segment.p_type.to_string()))?, segment.p_align)?);
+            }
+            if !found {
+                for section in self.sections()?.iter().filter(|section| section.sh_type == SHT_NOTE) {
+                    notes.extend(parse_notes(self.section_data(section)?, section.sh_addralign)?);
+                }
+            }
+            Ok(notes)
+        }).map(Vec::as_slice)
+    }
+
+    /// NT_GNU_BUILD_ID的十六进制表示
+    pub fn build_id(&self) -> io::Result<Option<String>> {
+        Ok(self.notes()?.iter().find_map(ElfNote::build_id))
+    }
+
+    pub fn dwarf(&self) -> io::Result<&DwarfSections> {
+        cached(&self.dwarf, || DwarfSections::from_sections(&mut self.cursor(), self.sections()?))
+    }
+
+    /// .debug_info中的所有单元
+    pub fn units(&self) -> io::Result<&[CompilationUnit]> {
+        cached(&self.units, || parse_units(self.dwarf()?)).map(Vec::as_slice)
+    }
+
+    /// .debug_line中的所有行号程序, 只读取行号程序用到的节, 其他DWARF节损坏时不受影响
+    pub fn line_programs(&self) -> io::Result<&[LineProgram]> {
+        cached(&self.line_programs, || {
+            let load = |name:&str| -> io::Result<&[u8]> {
+                match self.section_by_name(name)? {
+                    Some(section) => self.section_data(section),
+                    None => Ok(&[]),
+                }
+            };
+            parse_line_programs(load(".debug_line")?, load(".debug_line_str")?, load(".debug_str")?)
+        }).map(Vec::as_slice)
+    }
+}
@@ -0,0 +1,318 @@
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::elf::bounds::invalid;
+use crate::elf::section::{parse_sections, read_section_data, ElfSection};
+use crate::elf::symbol::*;
+use crate::elf::version::{SHT_GNU_VERSYM, VERSYM_HIDDEN};
+use crate::json::impl_to_json;
+
+pub const SHT_HASH:u32 = 5;
+pub const SHT_GNU_HASH:u32 = 0x6ffffff6;
+
+/// SysV .hash使用的ELF哈希函数
+pub fn sysv_hash(name:&[u8]) -> u32 {
+    let mut hash:u32 = 0;
+    for &byte in name {
+        hash = (hash << 4).wrapping_add(byte as u32);
+        let high = hash & 0xf0000000;
+        hash ^= high >> 24;
+        hash &= !high;
+    }
+    hash
+}
+
+/// .gnu.hash使用的DJB哈希函数
+pub fn gnu_hash(name:&[u8]) -> u32 {
+    name.iter().fold(5381u32, |hash, &byte| hash.wrapping_mul(33).wrapping_add(byte as u32))
+}
+
+/// ld.so能否把不带版本的名字绑定到这个符号: 名字相同, 已定义, 不是LOCAL, 类型是数据, 函数, TLS或IFUNC;
+/// 值为0的非绝对符号(TLS除外)被忽略; 与check_match相同, versym(.gnu.version, 可以为空)标记为隐藏的非默认版本foo@VER不匹配
+fn binds(symbol:&ElfSymbol, name:&str, versym:&[u16]) -> bool {
+    let symbol_type = symbol.symbol_type();
+    symbol.name == name
+        && !symbol.is_undefined()
+        && symbol.bind() != STB_LOCAL
+        && !(symbol.st_value == 0 && symbol.st_shndx != SHN_ABS && symbol_type != STT_TLS)
+        && matches!(symbol_type, STT_NOTYPE | STT_OBJECT | STT_FUNC | STT_COMMON | STT_TLS | STT_GNU_IFUNC)
+        && versym.get(symbol.index).is_none_or(|&value| value & VERSYM_HIDDEN == 0)
+}
+
+fn u32_at(data:&[u8], index:usize) -> u32 {
+    u32::from_le_bytes(data[index * 4..index * 4 + 4].try_into().unwrap())
+}
+
+/// 表头之后count个4字节的字
+fn words(data:&[u8], start:usize, count:u64, section:&ElfSection) -> io::Result<Vec<u32>> {
+    let end = count.checked_mul(4).and_then(|size| size.checked_add(start as u64))
+        .filter(|&end| end <= data.len() as u64)
+        .ok_or_else(|| invalid(format!("{} is truncated",section.name)))?;
+    Ok(data[start..end as usize].chunks_exact(4).map(|word| u32::from_le_bytes(word.try_into().unwrap())).collect())
+}
+
+/// SysV哈希表(.hash): nbucket个桶, 每个桶是一条链的第一个符号索引, chain[i]是链中i之后的符号索引, 0结束
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SysvHashTable {
+    pub section:String,
+    pub buckets:Vec<u32>,
+    pub chains:Vec<u32>,
+}
+
+impl_to_json!(SysvHashTable { section, buckets, chains });
+
+impl SysvHashTable {
+    pub fn parse(section:&ElfSection, data:&[u8]) -> io::Result<Self> {
+        if data.len() < 8 {
+            return Err(invalid(format!("{} is truncated",section.name)));
+        }
+        let (nbucket, nchain) = (u32_at(data, 0) as u64, u32_at(data, 1) as u64);
+        let buckets = words(data, 8, nbucket, section)?;
+        let chains = words(data, 8 + buckets.len() * 4, nchain, section)?;
+        Ok(Self { section:section.name.clone(), buckets, chains })
+    }
+
+    /// 链中的符号索引, 链成环或超出chain数组时停止
+    fn chain(&self, bucket:u32) -> impl Iterator<Item = u32> + '_ {
+        let mut index = bucket;
+        (0..self.chains.len()).map_while(move |_| {
+            let current = index;
+            index = *self.chains.get(current as usize)?;
+            (current != 0).then_some(current)
+        })
+    }
+
+    /// 与ld.so相同: 用名字的哈希值选择桶, 沿链比较名字, versym是symbols对应的.gnu.version
+    pub fn lookup<'a>(&self, symbols:&'a [ElfSymbol], versym:&[u16], name:&str) -> Option<&'a ElfSymbol> {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let bucket = self.buckets[sysv_hash(name.as_bytes()) as usize % self.buckets.len()];
+        self.chain(bucket).filter_map(|index| symbols.get(index as usize)).find(|symbol| binds(symbol, name, versym))
+    }
+
+    /// 每个桶的链长度
+    pub fn bucket_lengths(&self) -> Vec<u64> {
+        self.buckets.iter().map(|&bucket| self.chain(bucket).count() as u64).collect()
+    }
+}
+
+/// GNU哈希表(.gnu.hash): 布隆过滤器先排除不存在的名字, 每个桶指向第一个哈希值落在该桶的符号,
+/// 同一个桶的符号在.dynsym中连续排列, chain中保存它们的哈希值, 最低位为1表示链结束;
+/// 索引小于symoffset的符号(通常是未定义符号)不在表中
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GnuHashTable {
+    pub section:String,
+    pub symoffset:u32,
+    pub bloom_shift:u32,
+    pub bloom:Vec<u64>,
+    pub buckets:Vec<u32>,
+    pub chains:Vec<u32>,
+}
+
+impl_to_json!(GnuHashTable { section, symoffset, bloom_shift, bloom, buckets, chains });
+
+impl GnuHashTable {
+    pub fn parse(section:&ElfSection, data:&[u8]) -> io::Result<Self> {
+        if data.len() < 16 {
+            return Err(invalid(format!("{} is truncated",section.name)));
+        }
+        let (nbuckets, symoffset, bloom_size, bloom_shift) = (u32_at(data, 0), u32_at(data, 1), u32_at(data, 2), u32_at(data, 3));
+        // ld.so用bloom_size - 1作为掩码, 并把哈希值右移bloom_shift位
+        if !bloom_size.is_power_of_two() || bloom_shift >= 32 {
+            return Err(invalid(format!("{} has an invalid bloom filter (size {}, shift {})",section.name,bloom_size,bloom_shift)));
+        }
+        let bloom = words(data, 16, bloom_size as u64 * 2, section)?
+            .chunks_exact(2)
+            .map(|word| word[0] as u64 | (word[1] as u64) << 32)
+            .collect();
+        let start = 16 + bloom_size as usize * 8;
+        let buckets = words(data, start, nbuckets as u64, section)?;
+        let start = start + buckets.len() * 4;
+        let chains = words(data, start, (data.len() - start) as u64 / 4, section)?;
+        Ok(Self { section:section.name.clone(), symoffset, bloom_shift, bloom, buckets, chains })
+    }
+
+    /// 从桶指向的符号开始的(符号索引, 哈希值), 到最低位为1的哈希值为止
+    fn chain(&self, bucket:u32) -> impl Iterator<Item = (u32, u32)> + '_ {
+        let start = bucket.checked_sub(self.symoffset).filter(|_| bucket != 0).map_or(self.chains.len(), |start| start as usize);
+        let mut done = false;
+        self.chains.iter().enumerate().skip(start).map_while(move |(i, &hash)| {
+            if done {
+                return None;
+            }
+            done = hash & 1 != 0;
+            Some((self.symoffset.checked_add(i as u32)?, hash))
+        })
+    }
+
+    /// 与ld.so相同: 先检查布隆过滤器的两位, 再在桶中比较哈希值(忽略最低位)和名字, versym是symbols对应的.gnu.version
+    pub fn lookup<'a>(&self, symbols:&'a [ElfSymbol], versym:&[u16], name:&str) -> Option<&'a ElfSymbol> {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let hash = gnu_hash(name.as_bytes());
+        let word = self.bloom[(hash / 64) as usize & (self.bloom.len() - 1)];
+        let mask = 1u64 << (hash % 64) | 1u64 << ((hash >> self.bloom_shift) % 64);
+        if word & mask != mask {
+            return None;
+        }
+        let bucket = self.buckets[hash as usize % self.buckets.len()];
+        self.chain(bucket)
+            .filter(|&(_, chain_hash)| chain_hash | 1 == hash | 1)
+            .filter_map(|(index, _)| symbols.get(index as usize))
+            .find(|symbol| binds(symbol, name, versym))
+    }
+
+    /// 每个桶的链长度, 与readelf相同, 非空的桶至少计为1
+    pub fn bucket_lengths(&self) -> Vec<u64> {
+        self.buckets.iter().map(|&bucket| match bucket {
+            0 => 0,
+            _ => (self.chain(bucket).count() as u64).max(1),
+        }).collect()
+    }
+}
+
+/// 桶中链长度的分布, 与readelf -I相同
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Histogram {
+    /// GNU哈希表所在的节名, SysV哈希表为None
+    pub section:Option<String>,
+    pub buckets:usize,
+    /// counts[n]是链长度为n的桶数
+    pub counts:Vec<u64>,
+}
+
+impl_to_json!(Histogram { section, buckets, counts });
+
+impl Histogram {
+    fn new(section:Option<String>, lengths:&[u64]) -> Self {
+        let max = lengths.iter().copied().max().unwrap_or(0);
+        let mut counts = vec![0u64;max as usize + 1];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        Self { section, buckets:lengths.len(), counts }
+    }
+}
+
+impl std::fmt::Display for Histogram {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let plural = if self.buckets == 1 { "bucket" } else { "buckets" };
+        match &self.section {
+            Some(section) => writeln!(f,"Histogram for `{}' bucket list length (total of {} {}):",section,self.buckets,plural)?,
+            None => writeln!(f,"Histogram for bucket list length (total of {} {}):",self.buckets,plural)?,
+        }
+        writeln!(f," Length  Number     % of total  Coverage")?;
+        let percent = |count:u64, total:u64| count as f64 * 100.0 / total as f64;
+        let symbols:u64 = self.counts.iter().enumerate().map(|(length, count)| length as u64 * count).sum();
+        let mut covered = 0;
+        for (length, &count) in self.counts.iter().enumerate() {
+            if length == 0 {
+                writeln!(f,"      0  {:<10} ({:5.1}%)",count,percent(count, self.buckets as u64))?;
+                continue;
+            }
+            covered += length as u64 * count;
+            writeln!(f,"{:7}  {:<10} ({:5.1}%)    {:5.1}%",length,count,percent(count, self.buckets as u64),percent(covered, symbols))?;
+        }
+        Ok(())
+    }
+}
+
+/// 文件中的哈希表及它们索引的动态符号表
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HashTables {
+    pub sysv:Option<SysvHashTable>,
+    pub gnu:Option<GnuHashTable>,
+    pub symbols:Vec<ElfSymbol>,
+    /// 与symbols一一对应的.gnu.version, 没有符号版本时为空
+    pub versym:Vec<u16>,
+}
+
+impl_to_json!(HashTables { sysv, gnu });
+
+impl HashTables {
+    /// 与ld.so相同, 有.gnu.hash时只使用它, 否则使用.hash; 查找的是不带版本的名字(如dlsym),
+    /// 与ld.so的区别: 不检查符号所在的版本是否存在, 一个名字有多个非隐藏的版本时返回哈希链中的第一个, 而ld.so选择基础版本
+    pub fn lookup(&self, name:&str) -> Option<&ElfSymbol> {
+        match (&self.gnu, &self.sysv) {
+            (Some(gnu), _) => gnu.lookup(&self.symbols, &self.versym, name),
+            (None, Some(sysv)) => sysv.lookup(&self.symbols, &self.versym, name),
+            (None, None) => None,
+        }
+    }
+
+    /// 按readelf -I的顺序, 先SysV后GNU; 与readelf相同, 所有桶都为空的.gnu.hash没有直方图
+    pub fn histograms(&self) -> Vec<Histogram> {
+        let mut histograms = Vec::new();
+        if let Some(sysv) = &self.sysv {
+            histograms.push(Histogram::new(None, &sysv.bucket_lengths()));
+        }
+        if let Some(gnu) = self.gnu.as_ref().filter(|gnu| gnu.buckets.iter().any(|&bucket| bucket != 0)) {
+            histograms.push(Histogram::new(Some(gnu.section.clone()), &gnu.bucket_lengths()));
+        }
+        histograms
+    }
+
+    /// 检查哈希表与符号表是否一致: 每个可以按名字绑定的动态符号都能通过每个哈希表找到
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if let Some(sysv) = &self.sysv {
+            if sysv.chains.len() != self.symbols.len() {
+                problems.push(format!("{} has {} chain entries, but the symbol table has {} symbols",sysv.section,sysv.chains.len(),self.symbols.len()));
+            }
+        }
+        if let Some(gnu) = &self.gnu {
+            let hashed = self.symbols.len().saturating_sub(gnu.symoffset as usize);
+            if gnu.chains.len() < hashed {
+                problems.push(format!("{} has {} chain entries, but {} symbols follow symoffset {}",gnu.section,gnu.chains.len(),hashed,gnu.symoffset));
+            }
+        }
+        for symbol in self.symbols.iter().skip(1).filter(|symbol| binds(symbol, &symbol.name, &self.versym)) {
+            let found = |found:Option<&ElfSymbol>| found.is_some_and(|found| found.name == symbol.name);
+            if let Some(sysv) = self.sysv.as_ref().filter(|sysv| !found(sysv.lookup(&self.symbols, &self.versym, &symbol.name))) {
+                problems.push(format!("symbol [{}] {} cannot be found through {}",symbol.index,symbol.name,sysv.section));
+            }
+            if let Some(gnu) = self.gnu.as_ref().filter(|gnu| !found(gnu.lookup(&self.symbols, &self.versym, &symbol.name))) {
+                problems.push(format!("symbol [{}] {} cannot be found through {}",symbol.index,symbol.name,gnu.section));
+            }
+        }
+        problems
+    }
+}
+
+impl std::fmt::Display for HashTables {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for histogram in self.histograms() {
+            writeln!(f)?;
+            write!(f,"{}",histogram)?;
+        }
+        Ok(())
+    }
+}
+
+/// 解析.hash和.gnu.hash节, 符号表是哈希表的sh_link指向的.dynsym; 没有哈希表时两者都为None
+pub fn parse_hash_tables<R:Read + Seek>(elf:&mut R) -> io::Result<HashTables> {
+    let sections = parse_sections(elf)?;
+    let mut tables = HashTables { sysv:None, gnu:None, symbols:Vec::new(), versym:Vec::new() };
+    let mut symbol_table = None;
+    for section in &sections {
+        match section.sh_type {
+            SHT_HASH => tables.sysv = Some(SysvHashTable::parse(section, &read_section_data(elf, section)?)?),
+            SHT_GNU_HASH => tables.gnu = Some(GnuHashTable::parse(section, &read_section_data(elf, section)?)?),
+            _ => continue,
+        }
+        symbol_table = symbol_table.or(sections.get(section.sh_link as usize));
+    }
+    if let Some(table) = symbol_table {
+        tables.symbols = read_symbol_table(elf, &sections, table)?;
+        // .gnu.version的sh_link指向它对应的符号表
+        let versym = sections.iter().find(|section| section.sh_type == SHT_GNU_VERSYM && section.sh_link == table.index as u32);
+        if let Some(section) = versym {
+            tables.versym = read_section_data(elf, section)?.chunks_exact(2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                .collect();
+        }
+    }
+    Ok(tables)
+}
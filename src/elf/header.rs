@@ -1,10 +1,12 @@
 use std::fmt::Formatter;
-use std::fs::File;
 use std::io::{Read, Seek};
 use std::io;
+use crate::json::impl_to_json;
 
 const DEFAULT_VERSION:u8 = 1;
+pub const ELFMAG:&[u8;4] = b"\x7fELF";
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ElfHeader{
     /*
     e_idnet:
@@ -30,6 +32,11 @@ pub struct ElfHeader{
     pub e_shstrndx:u16,
 }
 
+impl_to_json!(ElfHeader { e_ident, e_type, e_machine, e_version, e_entry, e_phoff, e_shoff, e_flags, e_ehsize, e_phentsize, e_phnum, e_shentsize, e_shnum, e_shstrndx });
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[allow(non_camel_case_types)]
 pub enum HeaderType {
     NONE,
     REL,
@@ -41,6 +48,9 @@ pub enum HeaderType {
     UNKNOWN
 }
 
+impl_to_json!(enum HeaderType);
+
+
 pub enum BitType {
     BIT32,
     BIT64,
@@ -48,7 +58,7 @@ pub enum BitType {
 }
 
 impl BitType {
-    fn from(data:u8) -> Self {
+    pub fn from(data:u8) -> Self {
         match data {
             1 => {BitType::BIT32},
             2 => {BitType::BIT64},
@@ -56,7 +66,8 @@ impl BitType {
         }
     }
 
-    fn to_string(&self) -> String {
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
         match self {
             BitType::BIT32 => {String::from("32-bit file")},
             BitType::BIT64 => {String::from("64-bit file")},
@@ -65,14 +76,15 @@ impl BitType {
     }
 }
 
-enum Encoding {
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+pub enum Encoding {
     LITTLE_ENDIAN,
     BIG_ENDIAN,
     UNKNOWN
 }
 
 impl Encoding {
-    fn from(data:u8) -> Self {
+    pub fn from(data:u8) -> Self {
         match data {
             1 => {Encoding::LITTLE_ENDIAN},
             2 => {Encoding::BIG_ENDIAN},
@@ -80,7 +92,8 @@ impl Encoding {
         }
     }
 
-    fn to_string(&self) -> String {
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
         match self {
             Encoding::LITTLE_ENDIAN => {String::from("little endian")},
             Encoding::BIG_ENDIAN => {String::from("big endian")},
@@ -103,7 +116,8 @@ impl HeaderType {
         }
     }
 
-    fn to_string(&self) -> String {
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
         match self {
             HeaderType::NONE => {String::from("Unknown file type")},
             HeaderType::REL => {String::from("REL(Relocatable file)")},
@@ -117,6 +131,9 @@ impl HeaderType {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[allow(non_camel_case_types)]
 pub enum MachineType {
     NONE,
     M32,
@@ -162,10 +179,16 @@ pub enum MachineType {
     STMicroelectronicsST100,
     AdvancedLogicCorpTinyJ,
     AMDX86_64,
+    AArch64,
+    RISCV,
     RESERVED,
 }
 
+impl_to_json!(enum MachineType);
+
+
 impl MachineType {
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         match self {
             MachineType::NONE => String::from("NONE (Unknown arch)"),
@@ -212,6 +235,8 @@ impl MachineType {
             MachineType::STMicroelectronicsST100 => String::from("ST100 (STMicroelectronics ST100)"),
             MachineType::AdvancedLogicCorpTinyJ => String::from("TINYJ (Advanced Logic Corp. TinyJ)"),
             MachineType::AMDX86_64 => String::from("X86_64 (AMD x86-64)"),
+            MachineType::AArch64 => String::from("AARCH64 (ARM 64-bit)"),
+            MachineType::RISCV => String::from("RISCV (RISC-V)"),
             MachineType::RESERVED => String::from("RESERVED"),
         }
     }
@@ -264,6 +289,8 @@ impl MachineType {
             60 => MachineType::STMicroelectronicsST100,
             61 => MachineType::AdvancedLogicCorpTinyJ,
             62 => MachineType::AMDX86_64,
+            183 => MachineType::AArch64,
+            243 => MachineType::RISCV,
             _ => MachineType::RESERVED,
         }
     }
@@ -275,10 +302,10 @@ impl ElfHeader {
         for x in &self.e_ident {
             write!(f, "{:02x} ", x)?;
         }
-        write!(f, "\n")?;
+        writeln!(f)?;
         writeln!(f,"{:40}{}","BitType",BitType::from(self.e_ident[4]).to_string())?;
-        write!(f,"{:40}{}\n","Encoding",Encoding::from(self.e_ident[5]).to_string())?;
-        write!(f,"{:40}{}\n","Version:",DEFAULT_VERSION)
+        writeln!(f,"{:40}{}","Encoding",Encoding::from(self.e_ident[5]).to_string())?;
+        writeln!(f,"{:40}{}","Version:",DEFAULT_VERSION)
     }
 
     fn parse_type(&self,f:&mut Formatter<'_>) -> std::fmt::Result {
@@ -293,51 +320,51 @@ impl ElfHeader {
 
     fn parse_version(&self,f:&mut Formatter<'_>) -> std::fmt::Result {
         match self.e_version {
-            0 => {write!(f,"{:40}Illegal version\n","Version:")?},
-            1 => {write!(f,"{:40}1\n","Version:")?},
-            _ => {return Err(std::fmt::Error::default())}
+            0 => {writeln!(f,"{:40}Illegal version","Version:")?},
+            1 => {writeln!(f,"{:40}1","Version:")?},
+            _ => {return Err(std::fmt::Error)}
         };
         Ok(())
     }
 
     fn parse_entry(&self,f:&mut Formatter<'_>) -> std::fmt::Result {
-        write!(f,"{:40}{:#x}\n","Entry point address:",self.e_entry)
+        writeln!(f,"{:40}{:#x}","Entry point address:",self.e_entry)
     }
 
     fn parse_phoff(&self,f:&mut Formatter<'_>) -> std::fmt::Result {
-        write!(f,"{:40}{}(bytes into file)\n","Start of program headers:",self.e_phoff)
+        writeln!(f,"{:40}{}(bytes into file)","Start of program headers:",self.e_phoff)
     }
 
     fn parse_shoff(&self,f:&mut Formatter<'_>) -> std::fmt::Result {
-        write!(f,"{:40}{}(bytes into file)\n","Start of section headers:",self.e_shoff)
+        writeln!(f,"{:40}{}(bytes into file)","Start of section headers:",self.e_shoff)
     }
 
     fn parse_flags(&self,f:&mut Formatter<'_>) -> std::fmt::Result {
-        write!(f,"{:40}{:#x}\n","Flags:",self.e_flags)
+        writeln!(f,"{:40}{:#x}","Flags:",self.e_flags)
     }
 
     fn parse_ehsize(&self,f:&mut Formatter<'_>) -> std::fmt::Result {
-        write!(f,"{:40}{}(bytes)\n","Size of this header:",self.e_ehsize)
+        writeln!(f,"{:40}{}(bytes)","Size of this header:",self.e_ehsize)
     }
 
     fn parse_phentsize(&self,f:&mut Formatter<'_>) -> std::fmt::Result {
-        write!(f,"{:40}{}(bytes)\n","Size of program headers:",self.e_phentsize)
+        writeln!(f,"{:40}{}(bytes)","Size of program headers:",self.e_phentsize)
     }
 
     fn parse_phnum(&self,f:&mut Formatter<'_>) -> std::fmt::Result {
-        write!(f,"{:40}{}\n","Number of program headers:",self.e_phnum)
+        writeln!(f,"{:40}{}","Number of program headers:",self.e_phnum)
     }
 
     fn parse_shentsize(&self,f:&mut Formatter<'_>) -> std::fmt::Result {
-        write!(f,"{:40}{}(bytes)\n","Size of section headers:",self.e_shentsize)
+        writeln!(f,"{:40}{}(bytes)","Size of section headers:",self.e_shentsize)
     }
 
     fn parse_shnum(&self,f:&mut Formatter<'_>) -> std::fmt::Result {
-        write!(f,"{:40}{}\n","Number of section headers:",self.e_shnum)
+        writeln!(f,"{:40}{}","Number of section headers:",self.e_shnum)
     }
 
     fn parse_shstrndx(&self,f:&mut Formatter<'_>) -> std::fmt::Result {
-        write!(f,"{:40}{}\n","Section header string table index:",self.e_shstrndx)
+        writeln!(f,"{:40}{}","Section header string table index:",self.e_shstrndx)
     }
 
 }
@@ -383,7 +410,18 @@ impl std::fmt::Display for ElfHeader {
     }
 }
 
-pub fn parse_header(elf:&mut File) -> io::Result<ElfHeader> {
+/// 文件是否以ELF魔数开头
+pub fn is_elf<R:Read + Seek>(file:&mut R) -> io::Result<bool> {
+    file.rewind()?;
+    let mut magic = [0u8;4];
+    match file.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == ELFMAG),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn parse_header<R:Read + Seek>(elf:&mut R) -> io::Result<ElfHeader> {
     elf.rewind()?;
     let mut data = vec![0u8;64];
     elf.read_exact(&mut data)?;
@@ -0,0 +1,206 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::io;
+use std::io::Cursor;
+use std::path::Path;
+use memmap2::Mmap;
+use crate::elf::bounds::{checked_range, invalid, overlaps, string_at, EHDR_SIZE};
+use crate::elf::header::{ElfHeader, ELFMAG};
+use crate::elf::section::{section_header_table, ElfSection, SHDR_SIZE, SHT_NOBITS};
+use crate::elf::segment::{program_header_table, segments_from_table, ElfSegment};
+
+/// 以只读方式映射到内存的文件
+///
+/// 映射期间文件被其他进程截断或修改时读取到的内容没有保证, 与GNU binutils等工具的做法相同
+pub struct MappedFile {
+    map:Mmap,
+}
+
+impl MappedFile {
+    pub fn open<P:AsRef<Path>>(path:P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: 映射为只读, 文件内容只以字节切片的形式使用, 不会产生未定义的类型值
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(Self { map })
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// 供现有的`parse_*`函数使用, 读取和定位只是内存拷贝, 没有系统调用
+    pub fn cursor(&self) -> Cursor<&[u8]> {
+        Cursor::new(&self.map)
+    }
+
+    pub fn elf(&self) -> io::Result<MappedElf<'_>> {
+        MappedElf::parse(&self.map)
+    }
+}
+
+/// 内存中的ELF文件, 节名, 字符串表和节内容都是借用自原始数据的切片, 不复制
+pub struct MappedElf<'a> {
+    data:&'a [u8],
+    pub header:ElfHeader,
+}
+
+/// 节头及其名字和内容, 名字和内容借用自MappedElf的数据
+pub struct MappedSection<'a> {
+    pub index:u16,
+    /// 节名, 合法的UTF-8借用自节名字符串表, 超出字符串表时为空
+    pub name:Cow<'a, str>,
+    pub sh_name:u32,
+    pub sh_type:u32,
+    pub sh_flags:u64,
+    pub sh_addr:u64,
+    pub sh_offset:u64,
+    pub sh_size:u64,
+    pub sh_link:u32,
+    pub sh_info:u32,
+    pub sh_addralign:u64,
+    pub sh_entsize:u64,
+    /// 节在文件中的内容, NOBITS节为空
+    pub data:&'a [u8],
+}
+
+fn u32_at(data:&[u8], offset:usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn u64_at(data:&[u8], offset:usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+impl<'a> MappedElf<'a> {
+    pub fn parse(data:&'a [u8]) -> io::Result<Self> {
+        if !data.starts_with(ELFMAG) {
+            return Err(invalid(String::from("not an ELF file")));
+        }
+        if data.len() < EHDR_SIZE as usize {
+            return Err(invalid(format!("file is too small for an ELF header ({} bytes)",data.len())));
+        }
+        let header = ElfHeader::from(data[..EHDR_SIZE as usize].to_vec());
+        Ok(Self { data, header })
+    }
+
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// 文件中[offset, offset + size)的内容, 超出文件范围时返回错误
+    pub fn bytes_at(&self, offset:u64, size:u64) -> io::Result<&'a [u8]> {
+        self.slice(offset, size, "range")
+    }
+
+    fn slice(&self, offset:u64, size:u64, what:&str) -> io::Result<&'a [u8]> {
+        let range = checked_range(self.data.len() as u64, offset, size, what)?;
+        Ok(&self.data[range.start as usize..range.end as usize])
+    }
+
+    /// 节头表的内容, 与parse_sections相同, 超出文件或与文件头, 段头表重叠时返回错误
+    fn section_table(&self) -> io::Result<&'a [u8]> {
+        let len = self.data.len() as u64;
+        let table = section_header_table(&self.header, len)?;
+        if program_header_table(&self.header, len).is_ok_and(|segments| overlaps(&table, &segments)) {
+            return Err(invalid(String::from("section header table overlaps the program header table")));
+        }
+        self.slice(table.start, table.end - table.start, "section header table")
+    }
+
+    fn section_header(&self, index:u16) -> io::Result<&'a [u8]> {
+        if index >= self.header.e_shnum {
+            return Err(invalid(format!("section index {} is out of range",index)));
+        }
+        let offset = index as usize * self.header.e_shentsize as usize;
+        Ok(&self.section_table()?[offset..offset + SHDR_SIZE as usize])
+    }
+
+    fn section_at(&self, index:u16, names:&'a [u8]) -> io::Result<MappedSection<'a>> {
+        let data = self.section_header(index)?;
+        let sh_name = u32_at(data, 0);
+        let sh_type = u32_at(data, 4);
+        let sh_offset = u64_at(data, 24);
+        let sh_size = u64_at(data, 32);
+        Ok(MappedSection {
+            index,
+            name:string_at(names, sh_name as usize).unwrap_or_default(),
+            sh_name,
+            sh_type,
+            sh_flags:u64_at(data, 8),
+            sh_addr:u64_at(data, 16),
+            sh_offset,
+            sh_size,
+            sh_link:u32_at(data, 40),
+            sh_info:u32_at(data, 44),
+            sh_addralign:u64_at(data, 48),
+            sh_entsize:u64_at(data, 56),
+            data:match sh_type {
+                SHT_NOBITS => &[],
+                _ => self.bytes_at(sh_offset, sh_size)?,
+            },
+        })
+    }
+
+    /// 节名字符串表(e_shstrndx指向的节)的内容, e_shstrndx超出节头表或指向NOBITS节时为空
+    pub fn section_names(&self) -> io::Result<&'a [u8]> {
+        if self.header.e_shstrndx >= self.header.e_shnum {
+            return Ok(&[]);
+        }
+        let data = self.section_header(self.header.e_shstrndx)?;
+        if u32_at(data, 4) == SHT_NOBITS {
+            return Ok(&[]);
+        }
+        self.slice(u64_at(data, 24), u64_at(data, 32), "section name string table")
+    }
+
+    /// 只解析节头和节名, 不检查节的内容是否在文件内, 结果与parse_sections相同;
+    /// 节头表和节名字符串表借用自文件数据, 不复制
+    pub fn section_headers(&self) -> io::Result<Vec<ElfSection>> {
+        if self.header.e_shnum == 0 {
+            return Ok(Vec::new());
+        }
+        let table = self.section_table()?;
+        let names = self.section_names()?;
+        Ok(table.chunks_exact(self.header.e_shentsize as usize)
+            .enumerate()
+            .map(|(i, entry)| {
+                let mut section = ElfSection::from(&entry[..SHDR_SIZE as usize]);
+                section.index = i as u16;
+                section.name = string_at(names, section.sh_name as usize).unwrap_or_default().into_owned();
+                section
+            })
+            .collect())
+    }
+
+    pub fn sections(&self) -> io::Result<Vec<MappedSection<'a>>> {
+        let names = self.section_names()?;
+        (0..self.header.e_shnum).map(|index| self.section_at(index, names)).collect()
+    }
+
+    /// 按名字查找节, 只解析找到之前的节头
+    pub fn section_by_name(&self, name:&str) -> io::Result<Option<MappedSection<'a>>> {
+        let names = self.section_names()?;
+        for index in 0..self.header.e_shnum {
+            let section = self.section_at(index, names)?;
+            if section.name == name {
+                return Ok(Some(section));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn segments(&self) -> io::Result<Vec<ElfSegment>> {
+        let header = &self.header;
+        if header.e_phnum == 0 {
+            return Ok(Vec::new());
+        }
+        let table = program_header_table(header, self.data.len() as u64)?;
+        let data = self.bytes_at(table.start, table.end - table.start)?;
+        Ok(segments_from_table(header, data))
+    }
+
+    /// 段在文件中的内容(p_filesz个字节)
+    pub fn segment_data(&self, segment:&ElfSegment) -> io::Result<&'a [u8]> {
+        self.bytes_at(segment.p_offset, segment.p_filesz)
+    }
+}
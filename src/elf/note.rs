@@ -0,0 +1,181 @@
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::elf::section::{parse_sections, read_section_data};
+use crate::elf::segment::{parse_segments, read_segment_data, SegmentType};
+use crate::json::impl_to_json;
+
+pub const SHT_NOTE:u32 = 7;
+
+// CORE名字下的注释类型
+pub const NT_PRSTATUS:u32 = 1;
+pub const NT_FPREGSET:u32 = 2;
+pub const NT_PRPSINFO:u32 = 3;
+pub const NT_TASKSTRUCT:u32 = 4;
+pub const NT_AUXV:u32 = 6;
+pub const NT_PRXFPREG:u32 = 0x46e62b7f;
+pub const NT_SIGINFO:u32 = 0x53494749;
+pub const NT_FILE:u32 = 0x46494c45;
+// LINUX名字下的注释类型
+pub const NT_X86_XSTATE:u32 = 0x202;
+pub const NT_ARM_VFP:u32 = 0x400;
+pub const NT_ARM_TLS:u32 = 0x401;
+pub const NT_ARM_HW_BREAK:u32 = 0x402;
+pub const NT_ARM_HW_WATCH:u32 = 0x403;
+pub const NT_ARM_SYSTEM_CALL:u32 = 0x404;
+pub const NT_ARM_SVE:u32 = 0x405;
+pub const NT_ARM_PAC_MASK:u32 = 0x406;
+// GNU名字下的注释类型
+pub const NT_GNU_ABI_TAG:u32 = 1;
+pub const NT_GNU_HWCAP:u32 = 2;
+pub const NT_GNU_BUILD_ID:u32 = 3;
+pub const NT_GNU_GOLD_VERSION:u32 = 4;
+pub const NT_GNU_PROPERTY_TYPE_0:u32 = 5;
+
+// NT_GNU_PROPERTY_TYPE_0中的属性
+pub const GNU_PROPERTY_STACK_SIZE:u32 = 1;
+pub const GNU_PROPERTY_NO_COPY_ON_PROTECTED:u32 = 2;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_AND:u32 = 0xc0000000;
+pub const GNU_PROPERTY_X86_FEATURE_1_AND:u32 = 0xc0000002;
+pub const GNU_PROPERTY_X86_ISA_1_NEEDED:u32 = 0xc0008002;
+pub const GNU_PROPERTY_X86_FEATURE_2_USED:u32 = 0xc0010001;
+pub const GNU_PROPERTY_X86_ISA_1_USED:u32 = 0xc0010002;
+pub const GNU_PROPERTY_X86_FEATURE_1_IBT:u32 = 0x1;
+pub const GNU_PROPERTY_X86_FEATURE_1_SHSTK:u32 = 0x2;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_BTI:u32 = 0x1;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_PAC:u32 = 0x2;
+
+/// 一条注释: 名字(如CORE, GNU)决定了类型的含义
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ElfNote {
+    pub name:String,
+    pub n_type:u32,
+    pub desc:Vec<u8>,
+}
+
+impl_to_json!(ElfNote { name, n_type, desc });
+
+impl ElfNote {
+    pub fn type_name(&self) -> String {
+        let name = match (self.name.as_str(), self.n_type) {
+            ("GNU", NT_GNU_ABI_TAG) => "NT_GNU_ABI_TAG (ABI version tag)",
+            ("GNU", NT_GNU_HWCAP) => "NT_GNU_HWCAP (DSO-supplied software HWCAP info)",
+            ("GNU", NT_GNU_BUILD_ID) => "NT_GNU_BUILD_ID (unique build ID bitstring)",
+            ("GNU", NT_GNU_GOLD_VERSION) => "NT_GNU_GOLD_VERSION (gold version)",
+            ("GNU", NT_GNU_PROPERTY_TYPE_0) => "NT_GNU_PROPERTY_TYPE_0",
+            ("CORE", NT_PRSTATUS) => "NT_PRSTATUS (prstatus structure)",
+            ("CORE", NT_FPREGSET) => "NT_FPREGSET (floating point registers)",
+            ("CORE", NT_PRPSINFO) => "NT_PRPSINFO (prpsinfo structure)",
+            ("CORE", NT_TASKSTRUCT) => "NT_TASKSTRUCT (task structure)",
+            ("CORE", NT_AUXV) => "NT_AUXV (auxiliary vector)",
+            ("CORE", NT_SIGINFO) => "NT_SIGINFO (siginfo_t data)",
+            ("CORE", NT_FILE) => "NT_FILE (mapped files)",
+            ("LINUX", NT_PRXFPREG) => "NT_PRXFPREG (user_xfpregs structure)",
+            ("LINUX", NT_X86_XSTATE) => "NT_X86_XSTATE (x86 XSAVE extended state)",
+            ("LINUX", NT_ARM_VFP) => "NT_ARM_VFP (arm VFP registers)",
+            ("LINUX", NT_ARM_TLS) => "NT_ARM_TLS (AArch TLS registers)",
+            ("LINUX", NT_ARM_HW_BREAK) => "NT_ARM_HW_BREAK (AArch hardware breakpoint registers)",
+            ("LINUX", NT_ARM_HW_WATCH) => "NT_ARM_HW_WATCH (AArch hardware watchpoint registers)",
+            ("LINUX", NT_ARM_SYSTEM_CALL) => "NT_ARM_SYSTEM_CALL (AArch system call number)",
+            ("LINUX", NT_ARM_SVE) => "NT_ARM_SVE (AArch SVE registers)",
+            ("LINUX", NT_ARM_PAC_MASK) => "NT_ARM_PAC_MASK (AArch pointer authentication code masks)",
+            _ => return format!("Unknown note type: ({:#010x})",self.n_type),
+        };
+        String::from(name)
+    }
+
+    /// NT_GNU_BUILD_ID的十六进制字符串
+    pub fn build_id(&self) -> Option<String> {
+        if self.name != "GNU" || self.n_type != NT_GNU_BUILD_ID {
+            return None;
+        }
+        Some(self.desc.iter().map(|b| format!("{:02x}",b)).collect())
+    }
+
+    /// NT_GNU_PROPERTY_TYPE_0中的(属性类型, 数据)列表, 每项按8字节对齐
+    pub fn gnu_properties(&self) -> Vec<(u32, &[u8])> {
+        let mut properties = Vec::new();
+        if self.name != "GNU" || self.n_type != NT_GNU_PROPERTY_TYPE_0 {
+            return properties;
+        }
+        let mut pos = 0;
+        while pos + 8 <= self.desc.len() {
+            let pr_type = u32::from_le_bytes(self.desc[pos..pos + 4].try_into().unwrap());
+            let pr_datasz = u32::from_le_bytes(self.desc[pos + 4..pos + 8].try_into().unwrap()) as usize;
+            let Some(data) = self.desc.get(pos + 8..pos + 8 + pr_datasz) else { break };
+            properties.push((pr_type, data));
+            pos = align_up(pos + 8 + pr_datasz, 8);
+        }
+        properties
+    }
+
+    /// X86_FEATURE_1_AND或AARCH64_FEATURE_1_AND属性的值
+    pub fn gnu_property_u32(&self, pr_type:u32) -> Option<u32> {
+        self.gnu_properties().into_iter()
+            .find(|&(property, data)| property == pr_type && data.len() >= 4)
+            .map(|(_, data)| u32::from_le_bytes(data[0..4].try_into().unwrap()))
+    }
+}
+
+impl std::fmt::Display for ElfNote {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f,"  {:<20} {:#010x}\t{}",self.name,self.desc.len(),self.type_name())?;
+        if let Some(build_id) = self.build_id() {
+            write!(f,"\n    Build ID: {}",build_id)?;
+        }
+        Ok(())
+    }
+}
+
+fn align_up(value:usize, align:usize) -> usize {
+    value.div_ceil(align) * align
+}
+
+/// 解析注释节或PT_NOTE段的内容, align为节或段的对齐(4或8)
+pub fn parse_notes(data:&[u8], align:u64) -> io::Result<Vec<ElfNote>> {
+    let align = if align == 8 { 8 } else { 4 };
+    let truncated = || io::Error::new(io::ErrorKind::InvalidData, "truncated note");
+    let mut notes = Vec::new();
+    let mut pos = 0;
+    while pos + 12 <= data.len() {
+        let word = |pos:usize| u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap()) as usize;
+        let namesz = word(pos);
+        let descsz = word(pos + 4);
+        let n_type = word(pos + 8) as u32;
+        let name_start = pos + 12;
+        let desc_start = name_start.checked_add(namesz).map(|end| align_up(end, align)).ok_or_else(truncated)?;
+        let desc_end = desc_start.checked_add(descsz).ok_or_else(truncated)?;
+        let name = data.get(name_start..name_start + namesz).ok_or_else(truncated)?;
+        let desc = data.get(desc_start..desc_end).ok_or_else(truncated)?;
+        let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+        notes.push(ElfNote { name:String::from_utf8_lossy(&name[..len]).into_owned(), n_type, desc:desc.to_vec() });
+        pos = align_up(desc_end, align);
+    }
+    Ok(notes)
+}
+
+/// 读取所有PT_NOTE段中的注释, core文件没有节头, 只能通过段读取
+pub fn parse_note_segments<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<ElfNote>> {
+    let mut notes = Vec::new();
+    for segment in parse_segments(elf)? {
+        if !matches!(segment.p_type, SegmentType::NOTE) {
+            continue;
+        }
+        let data = read_segment_data(elf, &segment)?;
+        notes.extend(parse_notes(&data, segment.p_align)?);
+    }
+    Ok(notes)
+}
+
+/// 读取所有SHT_NOTE节中的注释, 返回(节名, 注释)
+pub fn parse_note_sections<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<(String, Vec<ElfNote>)>> {
+    let mut notes = Vec::new();
+    for section in parse_sections(elf)? {
+        if section.sh_type != SHT_NOTE {
+            continue;
+        }
+        let data = read_section_data(elf, &section)?;
+        notes.push((section.name.clone(), parse_notes(&data, section.sh_addralign)?));
+    }
+    Ok(notes)
+}
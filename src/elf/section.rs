@@ -1,26 +1,92 @@
 use std::fmt::Formatter;
-use std::fs::File;
 use std::io;
 use std::io::{Read, Seek};
-use crate::elf::header::parse_header;
+use std::ops::Range;
+use crate::elf::bounds::{entry_size, file_len, invalid, overlaps, read_at, string_at, table_range};
+use crate::elf::header::{parse_header, ElfHeader};
+use crate::elf::segment::program_header_table;
+use crate::json::impl_to_json;
 
+pub const SHT_STRTAB:u32 = 3;
+pub const SHT_RELA:u32 = 4;
+pub const SHT_NOBITS:u32 = 8;
+pub const SHT_REL:u32 = 9;
+pub const SHT_GROUP:u32 = 17;
+pub const SHF_ALLOC:u64 = 0x2;
+pub const SHF_TLS:u64 = 0x400;
+pub const SHF_COMPRESSED:u64 = 0x800;
+/// ELF64和ELF32节头的大小, e_shentsize不能比它小
+pub const SHDR_SIZE:u64 = 64;
+pub const SHDR32_SIZE:u64 = 40;
+
+/// 节类型的名字, 与readelf -S的Type一栏相同
+pub fn section_type_name(sh_type:u32) -> String {
+    let name = match sh_type {
+        0 => "NULL",
+        1 => "PROGBITS",
+        2 => "SYMTAB",
+        3 => "STRTAB",
+        4 => "RELA",
+        5 => "HASH",
+        6 => "DYNAMIC",
+        7 => "NOTE",
+        8 => "NOBITS",
+        9 => "REL",
+        10 => "SHLIB",
+        11 => "DYNSYM",
+        14 => "INIT_ARRAY",
+        15 => "FINI_ARRAY",
+        16 => "PREINIT_ARRAY",
+        17 => "GROUP",
+        18 => "SYMTAB SECTION INDICES",
+        19 => "RELR",
+        0x6ffffff5 => "GNU_ATTRIBUTES",
+        0x6ffffff6 => "GNU_HASH",
+        0x6ffffff7 => "GNU_LIBLIST",
+        0x6ffffffd => "VERDEF",
+        0x6ffffffe => "VERNEED",
+        0x6fffffff => "VERSYM",
+        0x70000001 => "X86_64_UNWIND",
+        _ => return format!("{:#x}",sh_type),
+    };
+    String::from(name)
+}
+
+/// 节标志的字母表示, 与readelf -S的Flg一栏相同
+pub fn section_flags_to_string(sh_flags:u64) -> String {
+    [(0x1, 'W'), (0x2, 'A'), (0x4, 'X'), (0x10, 'M'), (0x20, 'S'), (0x40, 'I'), (0x80, 'L'), (0x100, 'O'), (0x200, 'G'), (0x400, 'T'), (SHF_COMPRESSED, 'C'), (0x80000000, 'E')]
+        .iter()
+        .filter(|(flag, _)| sh_flags & flag != 0)
+        .map(|(_, letter)| *letter)
+        .collect()
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ElfSection {
-    index:u16,
-    name:String,
-    sh_name:u32,
-    sh_type:u32,
-    sh_flags:u64,
-    sh_addr:u64,
-    sh_offset:u64,
-    sh_size:u64,
-    sh_link:u32,
-    sh_info:u32,
-    sh_addralign:u64,
-    sh_entsize:u64,
+    pub index:u16,
+    pub name:String,
+    pub sh_name:u32,
+    pub sh_type:u32,
+    pub sh_flags:u64,
+    pub sh_addr:u64,
+    pub sh_offset:u64,
+    pub sh_size:u64,
+    pub sh_link:u32,
+    pub sh_info:u32,
+    pub sh_addralign:u64,
+    pub sh_entsize:u64,
 }
 
+impl_to_json!(ElfSection { index, name, sh_name, sh_type, sh_flags, sh_addr, sh_offset, sh_size, sh_link, sh_info, sh_addralign, sh_entsize });
+
 impl From<Vec<u8>> for ElfSection {
     fn from(data: Vec<u8>) -> Self {
+        Self::from(data.as_slice())
+    }
+}
+
+impl From<&[u8]> for ElfSection {
+    fn from(data: &[u8]) -> Self {
         Self {
             index:0,
             name:String::new(),
@@ -48,32 +114,78 @@ impl std::fmt::Display for ElfSection {
     }
 }
 
-pub fn parse_sections(elf:&mut File) -> io::Result<Vec<ElfSection>> {
+pub fn sections_title() -> String {
+    format!("{:15}{:20}{:16}\t{:16}\t{:16}","Index", "Name", "Address", "Offset","Size")
+}
+
+/// 节头表在长度为len的文件中的范围, 检查e_shentsize并且不超出文件
+pub fn section_header_table(header:&ElfHeader, len:u64) -> io::Result<Range<u64>> {
+    if header.e_shnum == 0 {
+        return Ok(header.e_shoff..header.e_shoff);
+    }
+    let entsize = entry_size(header, "e_shentsize", header.e_shentsize, [SHDR32_SIZE, SHDR_SIZE])?;
+    table_range(len, header.e_shoff, header.e_shnum as u64, entsize, "section header table")
+}
+
+/// 解析节头表和节名; 节头表超出文件, 与文件头或段头表重叠, 或节头小于64字节时返回错误
+pub fn parse_sections<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<ElfSection>> {
     let header = parse_header(elf)?;
-    let mut sections = Vec::new();
-    elf.seek(io::SeekFrom::Start(header.e_shoff + (header.e_shstrndx as u64 * header.e_shentsize as u64)))?;
-    elf.seek(io::SeekFrom::Current(24))?;
-    let mut sh_offset = [0u8;8];
-    let mut sh_size = [0u8;8];
-    elf.read_exact(&mut sh_offset)?;
-    elf.read_exact(&mut sh_size)?;
-    let size: u64 = u64::from_le_bytes(sh_size);
-    elf.seek(io::SeekFrom::Start(u64::from_le_bytes(sh_offset)))?;
-    let mut tables = vec![0u8;size as usize];
-    elf.read_exact(&mut tables)?;
-    elf.seek(io::SeekFrom::Start(header.e_shoff))?;
-    println!("{:15}{:20}{:16}\t{:16}\t{:16}","Index", "Name", "Address", "Offset","Size");
-    for i in 0..header.e_shnum {
-        let mut data = vec![0u8;header.e_shentsize as usize];
-        elf.read_exact(&mut data)?;
-        let mut section = ElfSection::from(data);
-        section.index = i;
-        let mut sh_name = section.sh_name;
-        while tables[sh_name as usize] != 0 {
-            section.name.push(tables[sh_name as usize] as char);
-            sh_name += 1;
-        }
-        sections.push(section);
+    if header.e_shnum == 0 {
+        return Ok(Vec::new());
+    }
+    let len = file_len(elf)?;
+    let table = section_header_table(&header, len)?;
+    let segments = program_header_table(&header, len);
+    if segments.is_ok_and(|segments| overlaps(&table, &segments)) {
+        return Err(invalid(String::from("section header table overlaps the program header table")));
+    }
+    let data = read_at(elf, table.start, table.end - table.start, "section header table")?;
+    let mut sections:Vec<ElfSection> = data.chunks_exact(header.e_shentsize as usize)
+        .enumerate()
+        .map(|(i, entry)| {
+            // 比标准结构大的节头只解析前64个字节
+            let mut section = ElfSection::from(&entry[..SHDR_SIZE as usize]);
+            section.index = i as u16;
+            section
+        })
+        .collect();
+    let names = match sections.get(header.e_shstrndx as usize) {
+        Some(strtab) => read_section_data(elf, strtab)?,
+        None => Vec::new(),
+    };
+    for section in &mut sections {
+        section.name = string_at(&names, section.sh_name as usize).unwrap_or_default().into_owned();
     }
     Ok(sections)
-}
\ No newline at end of file
+}
+
+/// 按名字查找节, 如`.debug_line`
+pub fn find_section<'a>(sections:&'a [ElfSection], name:&str) -> Option<&'a ElfSection> {
+    sections.iter().find(|section| section.name == name)
+}
+
+/// 运行时占用内存并且地址范围包含vaddr的节, 包括.bss等NOBITS节;
+/// .tbss只是每个线程的TLS模板, 地址与其后的节重叠, 不参与查找
+pub fn section_at_vaddr(sections:&[ElfSection], vaddr:u64) -> Option<&ElfSection> {
+    sections.iter()
+        .filter(|section| section.sh_flags & SHF_ALLOC != 0 && !(section.sh_type == SHT_NOBITS && section.sh_flags & SHF_TLS != 0))
+        .find(|section| vaddr >= section.sh_addr && vaddr - section.sh_addr < section.sh_size)
+}
+
+/// 文件内容包含offset的节, NOBITS节在文件中没有内容
+pub fn section_at_offset(sections:&[ElfSection], offset:u64) -> Option<&ElfSection> {
+    sections.iter()
+        .filter(|section| section.sh_type != SHT_NOBITS)
+        .find(|section| offset >= section.sh_offset && offset - section.sh_offset < section.sh_size)
+}
+
+/// 读取节的原始内容, NOBITS节(如.bss)在文件中没有内容, 返回空
+pub fn read_section_data<R:Read + Seek>(elf:&mut R, section:&ElfSection) -> io::Result<Vec<u8>> {
+    if section.sh_type == SHT_NOBITS {
+        return Ok(Vec::new());
+    }
+    if section.sh_flags & SHF_COMPRESSED != 0 {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, format!("compressed section {} is not supported", section.name)));
+    }
+    read_at(elf, section.sh_offset, section.sh_size, &format!("section {}",section.name))
+}
@@ -1,21 +1,29 @@
 use std::fmt::Formatter;
-use std::fs::File;
 use std::io;
 use std::io::{Read, Seek};
-use crate::elf::header::parse_header;
+use std::ops::Range;
+use crate::elf::bounds::{entry_size, file_len, invalid, read_at, table_range};
+use crate::elf::header::{parse_header, ElfHeader};
+use crate::json::impl_to_json;
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ElfSegment {
-    p_type:SegmentType,
-    p_flags:SegmentFlags,
-    p_offset:u64,
-    p_vaddr:u64,
-    p_paddr:u64,
-    p_filesz:u64,
-    p_memsz:u64,
-    p_align:u64,
+    pub p_type:SegmentType,
+    pub p_flags:SegmentFlags,
+    pub p_offset:u64,
+    pub p_vaddr:u64,
+    pub p_paddr:u64,
+    pub p_filesz:u64,
+    pub p_memsz:u64,
+    pub p_align:u64,
 }
 
-enum SegmentFlags {
+impl_to_json!(ElfSegment { p_type, p_flags, p_offset, p_vaddr, p_paddr, p_filesz, p_memsz, p_align });
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[allow(clippy::upper_case_acronyms)]
+pub enum SegmentFlags {
     NULL,
     X,
     W,
@@ -27,8 +35,12 @@ enum SegmentFlags {
     UNKNOWN
 }
 
+impl_to_json!(enum SegmentFlags);
+
+
 impl SegmentFlags {
-    fn to_string(&self) -> String {
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
         match self {
             SegmentFlags::NULL => {String::from("NULL")},
             SegmentFlags::X => {String::from("X")},
@@ -58,7 +70,10 @@ impl SegmentFlags {
     }
 }
 
-enum SegmentType {
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+pub enum SegmentType {
     NULL,
     LOAD,
     DYNAMIC,
@@ -66,17 +81,23 @@ enum SegmentType {
     NOTE,
     SHLIB,
     PHDR,
+    TLS,
     GNU_EH_FRAME = 0x6474e550,
     GNU_STACK = 0x6474e551,
     GNU_RELRO = 0x6474e552,
     GNU_PROPERTY = 0x6474e553,
+    GNU_SFRAME = 0x6474e554,
     LOPROC = 0x70000000,
     HIPROC = 0x7fffffff,
     UNKNOWN
 }
 
+impl_to_json!(enum SegmentType);
+
+
 impl SegmentType {
-    fn to_string(&self) -> String {
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
         match self {
             SegmentType::NULL => {String::from("NULL")},
             SegmentType::LOAD => {String::from("LOAD")},
@@ -85,10 +106,12 @@ impl SegmentType {
             SegmentType::NOTE => {String::from("NOTE")},
             SegmentType::SHLIB => {String::from("SHLIB")},
             SegmentType::PHDR => {String::from("PHDR")},
+            SegmentType::TLS => {String::from("TLS")},
             SegmentType::GNU_EH_FRAME => {String::from("GNU_EH_FRAME")},
             SegmentType::GNU_STACK => {String::from("GNU_STACK")},
             SegmentType::GNU_RELRO => {String::from("GNU_RELRO")},
             SegmentType::GNU_PROPERTY => {String::from("GNU_PROPERTY")},
+            SegmentType::GNU_SFRAME => {String::from("GNU_SFRAME")},
             SegmentType::LOPROC => {String::from("LOPROC")},
             SegmentType::HIPROC => {String::from("HIPROC")},
             SegmentType::UNKNOWN => {String::from("UNKNOWN")}
@@ -105,10 +128,12 @@ impl SegmentType {
             4 => {SegmentType::NOTE},
             5 => {SegmentType::SHLIB},
             6 => {SegmentType::PHDR},
+            7 => {SegmentType::TLS},
             0x6474e553 => {SegmentType::GNU_PROPERTY},
             0x6474e550 => {SegmentType::GNU_EH_FRAME},
             0x6474e551 => {SegmentType::GNU_STACK},
             0x6474e552 => {SegmentType::GNU_RELRO},
+            0x6474e554 => {SegmentType::GNU_SFRAME},
             0x70000000 => {SegmentType::LOPROC},
             0x7fffffff => {SegmentType::HIPROC},
             _ => {SegmentType::UNKNOWN}
@@ -137,19 +162,105 @@ impl From<Vec<u8>> for ElfSegment {
     }
 }
 
-pub fn parse_segments(elf:&mut File) -> io::Result<Vec<ElfSegment>> {
+pub fn segments_title(header:&ElfHeader) -> String {
+    format!("Entry point:{:#x}\nThere are {} program headers, starting at offset {}:\n{:<15}{:<18}\t{:<18}\t{:<18}\t{:<18}\t{:<18}\tFlags",
+            header.e_entry,header.e_phnum,header.e_phoff,"Type", "Offset", "VirtualAddr", "PhysAddr","FileSiz","MemSiz")
+}
+
+/// ELF64和ELF32段头的大小, e_phentsize不能比它小
+pub const PHDR_SIZE:u64 = 56;
+pub const PHDR32_SIZE:u64 = 32;
+
+/// 段头表在长度为len的文件中的范围, 检查e_phentsize并且不超出文件
+pub fn program_header_table(header:&ElfHeader, len:u64) -> io::Result<Range<u64>> {
+    if header.e_phnum == 0 {
+        return Ok(header.e_phoff..header.e_phoff);
+    }
+    let entsize = entry_size(header, "e_phentsize", header.e_phentsize, [PHDR32_SIZE, PHDR_SIZE])?;
+    table_range(len, header.e_phoff, header.e_phnum as u64, entsize, "program header table")
+}
+
+/// 把段头表的内容按e_phentsize切分并解析, 比标准结构大的表项只解析前56个字节
+pub fn segments_from_table(header:&ElfHeader, data:&[u8]) -> Vec<ElfSegment> {
+    data.chunks_exact(header.e_phentsize as usize).map(|entry| ElfSegment::from(entry[..PHDR_SIZE as usize].to_vec())).collect()
+}
+
+/// 解析段头表; 段头表超出文件, 与文件头重叠或段头小于56字节时返回错误
+pub fn parse_segments<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<ElfSegment>> {
     let header = parse_header(elf)?;
-    let mut segments = Vec::new();
-    println!("Entry point:{:#x}",header.e_entry);
-    println!("There are {} program headers, starting at offset {}:",header.e_phnum,header.e_phoff);
-    println!("{:<15}{:<18}\t{:<18}\t{:<18}\t{:<18}\t{:<18}\t{}","Type", "Offset", "VirtualAddr", "PhysAddr","FileSiz","MemSiz","Flags");
+    if header.e_phnum == 0 {
+        return Ok(Vec::new());
+    }
+    let len = file_len(elf)?;
     // 从文件头开始偏移e_phoff个字节
-    elf.seek(io::SeekFrom::Start(header.e_phoff))?;
-    for _ in 0..header.e_phnum {
-        let mut data = vec![0u8;header.e_phentsize as usize];
-        elf.read_exact(&mut data)?;
-        let segment = ElfSegment::from(data);
-        segments.push(segment);
+    let table = program_header_table(&header, len)?;
+    let data = read_at(elf, table.start, table.end - table.start, "program header table")?;
+    Ok(segments_from_table(&header, &data))
+}
+
+/// 读取段在文件中的内容(p_filesz个字节)
+pub fn read_segment_data<R:Read + Seek>(elf:&mut R, segment:&ElfSegment) -> io::Result<Vec<u8>> {
+    read_at(elf, segment.p_offset, segment.p_filesz, &format!("{} segment",segment.p_type.to_string()))
+}
+
+/// 内存范围[p_vaddr, p_vaddr + p_memsz)包含vaddr的PT_LOAD段, 包括p_filesz之后只在内存中的部分(如.bss)
+pub fn load_segment_at_vaddr(segments:&[ElfSegment], vaddr:u64) -> Option<(usize, &ElfSegment)> {
+    segments.iter().enumerate()
+        .filter(|(_, segment)| matches!(segment.p_type, SegmentType::LOAD))
+        .find(|(_, segment)| vaddr >= segment.p_vaddr && vaddr - segment.p_vaddr < segment.p_memsz)
+}
+
+/// 文件内容[p_offset, p_offset + p_filesz)包含offset的PT_LOAD段
+pub fn load_segment_at_offset(segments:&[ElfSegment], offset:u64) -> Option<(usize, &ElfSegment)> {
+    segments.iter().enumerate()
+        .filter(|(_, segment)| matches!(segment.p_type, SegmentType::LOAD))
+        .find(|(_, segment)| offset >= segment.p_offset && offset - segment.p_offset < segment.p_filesz)
+}
+
+/// 把虚拟地址转换为文件偏移, 地址不在任何PT_LOAD段的文件内容中时返回None
+pub fn vaddr_to_offset(segments:&[ElfSegment], vaddr:u64) -> Option<u64> {
+    segments.iter()
+        .filter(|segment| matches!(segment.p_type, SegmentType::LOAD))
+        .find(|segment| vaddr >= segment.p_vaddr && vaddr - segment.p_vaddr < segment.p_filesz)
+        .and_then(|segment| segment.p_offset.checked_add(vaddr - segment.p_vaddr))
+}
+
+/// 把文件偏移转换为虚拟地址, 偏移不在任何PT_LOAD段的文件内容中时返回None
+pub fn offset_to_vaddr(segments:&[ElfSegment], offset:u64) -> Option<u64> {
+    load_segment_at_offset(segments, offset).and_then(|(_, segment)| segment.p_vaddr.checked_add(offset - segment.p_offset))
+}
+
+/// read_vaddr一次最多读取的字节数, p_memsz可以远大于文件, 以0填充的部分不受文件大小限制
+pub const MAX_VADDR_READ:u64 = 64 << 20;
+
+/// 按加载器映射的结果读取虚拟地址[vaddr, vaddr + len)的内容, 范围可以跨越多个PT_LOAD段
+///
+/// 段的p_filesz之后到p_memsz的部分(如.bss)以0填充; 范围中有不属于任何PT_LOAD段的地址时返回错误,
+/// 先检查整个范围再读取, 不会按无效的长度分配内存; len超过MAX_VADDR_READ时返回错误
+pub fn read_vaddr<R:Read + Seek>(elf:&mut R, segments:&[ElfSegment], vaddr:u64, len:u64) -> io::Result<Vec<u8>> {
+    if len > MAX_VADDR_READ {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("range {:#x}+{:#x} is larger than {:#x} bytes",vaddr,len,MAX_VADDR_READ)));
+    }
+    let end = vaddr.checked_add(len).ok_or_else(|| invalid(format!("range {:#x}+{:#x} overflows",vaddr,len)))?;
+    // 每一块是(段, 块的起始地址, 块的结束地址)
+    let mut pieces = Vec::new();
+    let mut address = vaddr;
+    while address < end {
+        let (_, segment) = load_segment_at_vaddr(segments, address)
+            .ok_or_else(|| invalid(format!("address {:#x} is not mapped by any LOAD segment",address)))?;
+        let piece_end = end.min(segment.p_vaddr.saturating_add(segment.p_memsz));
+        pieces.push((segment, address, piece_end));
+        address = piece_end;
     }
-    Ok(segments)
-}
\ No newline at end of file
+    let mut data = Vec::new();
+    data.try_reserve_exact(len as usize).map_err(|e| io::Error::new(io::ErrorKind::OutOfMemory, format!("range {:#x}+{:#x}: {}",vaddr,len,e)))?;
+    for (segment, start, piece_end) in pieces {
+        let file_end = piece_end.min(segment.p_vaddr.saturating_add(segment.p_filesz));
+        if start < file_end {
+            let offset = segment.p_offset.checked_add(start - segment.p_vaddr).ok_or_else(|| invalid(format!("file offset of address {:#x} overflows",start)))?;
+            data.extend(read_at(elf, offset, file_end - start, &format!("contents of address {:#x}",start))?);
+        }
+        data.resize(data.len() + (piece_end - start.max(file_end)) as usize, 0);
+    }
+    Ok(data)
+}
@@ -0,0 +1,172 @@
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::elf::bounds::string_at;
+use crate::elf::section::{parse_sections, read_section_data, ElfSection};
+use crate::json::impl_to_json;
+
+pub const SHT_SYMTAB:u32 = 2;
+pub const SHT_DYNSYM:u32 = 11;
+
+pub const STB_LOCAL:u8 = 0;
+pub const STB_GLOBAL:u8 = 1;
+pub const STB_WEAK:u8 = 2;
+pub const STB_GNU_UNIQUE:u8 = 10;
+
+pub const STT_NOTYPE:u8 = 0;
+pub const STT_OBJECT:u8 = 1;
+pub const STT_FUNC:u8 = 2;
+pub const STT_SECTION:u8 = 3;
+pub const STT_FILE:u8 = 4;
+pub const STT_COMMON:u8 = 5;
+pub const STT_TLS:u8 = 6;
+pub const STT_GNU_IFUNC:u8 = 10;
+
+pub const SHN_UNDEF:u16 = 0;
+pub const SHN_ABS:u16 = 0xfff1;
+pub const SHN_COMMON:u16 = 0xfff2;
+
+/// 64位符号表项的大小
+const SYMBOL_SIZE:usize = 24;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ElfSymbol {
+    pub index:usize,
+    pub name:String,
+    pub st_name:u32,
+    pub st_info:u8,
+    pub st_other:u8,
+    pub st_shndx:u16,
+    pub st_value:u64,
+    pub st_size:u64,
+}
+
+impl_to_json!(ElfSymbol { index, name, st_name, st_info, st_other, st_shndx, st_value, st_size });
+
+impl From<Vec<u8>> for ElfSymbol {
+    fn from(data: Vec<u8>) -> Self {
+        Self {
+            index:0,
+            name:String::new(),
+            st_name: u32::from_ne_bytes(data[0..4].try_into().unwrap()),
+            st_info: data[4],
+            st_other: data[5],
+            st_shndx: u16::from_ne_bytes(data[6..8].try_into().unwrap()),
+            st_value: u64::from_ne_bytes(data[8..16].try_into().unwrap()),
+            st_size: u64::from_ne_bytes(data[16..24].try_into().unwrap()),
+        }
+    }
+}
+
+impl ElfSymbol {
+    pub fn bind(&self) -> u8 {
+        self.st_info >> 4
+    }
+
+    pub fn symbol_type(&self) -> u8 {
+        self.st_info & 0xf
+    }
+
+    pub fn visibility(&self) -> u8 {
+        self.st_other & 0x3
+    }
+
+    pub fn is_undefined(&self) -> bool {
+        self.st_shndx == SHN_UNDEF
+    }
+
+    pub fn is_function(&self) -> bool {
+        matches!(self.symbol_type(), STT_FUNC | STT_GNU_IFUNC)
+    }
+
+    /// 其他对象可以绑定的定义: 已定义的GLOBAL/WEAK/UNIQUE符号, 不包括节和文件符号
+    pub fn is_exported(&self) -> bool {
+        self.index != 0
+            && !self.is_undefined()
+            && matches!(self.bind(), STB_GLOBAL | STB_WEAK | STB_GNU_UNIQUE)
+            && !matches!(self.symbol_type(), STT_SECTION | STT_FILE)
+    }
+
+    /// 地址是否落在该符号的范围内, 大小为0的符号只匹配起始地址
+    pub fn contains(&self, address:u64) -> bool {
+        if self.st_size == 0 {
+            address == self.st_value
+        } else {
+            address >= self.st_value && address - self.st_value < self.st_size
+        }
+    }
+}
+
+pub fn bind_to_string(bind:u8) -> String {
+    match bind {
+        STB_LOCAL => String::from("LOCAL"),
+        STB_GLOBAL => String::from("GLOBAL"),
+        STB_WEAK => String::from("WEAK"),
+        STB_GNU_UNIQUE => String::from("UNIQUE"),
+        _ => format!("<{}>",bind),
+    }
+}
+
+pub fn type_to_string(symbol_type:u8) -> String {
+    match symbol_type {
+        STT_NOTYPE => String::from("NOTYPE"),
+        STT_OBJECT => String::from("OBJECT"),
+        STT_FUNC => String::from("FUNC"),
+        STT_SECTION => String::from("SECTION"),
+        STT_FILE => String::from("FILE"),
+        STT_COMMON => String::from("COMMON"),
+        STT_TLS => String::from("TLS"),
+        STT_GNU_IFUNC => String::from("IFUNC"),
+        _ => format!("<{}>",symbol_type),
+    }
+}
+
+fn shndx_to_string(shndx:u16) -> String {
+    match shndx {
+        SHN_UNDEF => String::from("UND"),
+        SHN_ABS => String::from("ABS"),
+        SHN_COMMON => String::from("COM"),
+        _ => shndx.to_string(),
+    }
+}
+
+impl std::fmt::Display for ElfSymbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f,"{:>6}: {:016x} {:>5} {:<7} {:<6} {:>3} {}",self.index,self.st_value,self.st_size,type_to_string(self.symbol_type()),bind_to_string(self.bind()),shndx_to_string(self.st_shndx),self.name)
+    }
+}
+
+/// 读取给定符号表节中的所有符号, 符号名来自sh_link指向的字符串表
+pub fn read_symbol_table<R:Read + Seek>(elf:&mut R, sections:&[ElfSection], table:&ElfSection) -> io::Result<Vec<ElfSymbol>> {
+    let data = read_section_data(elf, table)?;
+    let strtab = match sections.get(table.sh_link as usize) {
+        Some(section) => read_section_data(elf, section)?,
+        None => Vec::new(),
+    };
+    let mut symbols = Vec::new();
+    for (i, entry) in data.chunks_exact(SYMBOL_SIZE).enumerate() {
+        let mut symbol = ElfSymbol::from(entry.to_vec());
+        symbol.index = i;
+        symbol.name = string_at(&strtab, symbol.st_name as usize).unwrap_or_default().into_owned();
+        symbols.push(symbol);
+    }
+    Ok(symbols)
+}
+
+fn parse_symbols_of_type<R:Read + Seek>(elf:&mut R, sh_type:u32) -> io::Result<Vec<ElfSymbol>> {
+    let sections = parse_sections(elf)?;
+    match sections.iter().find(|section| section.sh_type == sh_type) {
+        Some(table) => read_symbol_table(elf, &sections, table),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// 解析.symtab, 被strip的文件返回空
+pub fn parse_symbols<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<ElfSymbol>> {
+    parse_symbols_of_type(elf, SHT_SYMTAB)
+}
+
+/// 解析动态符号表.dynsym
+pub fn parse_dynamic_symbols<R:Read + Seek>(elf:&mut R) -> io::Result<Vec<ElfSymbol>> {
+    parse_symbols_of_type(elf, SHT_DYNSYM)
+}
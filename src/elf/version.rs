@@ -0,0 +1,229 @@
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::elf::bounds::string_at;
+use crate::elf::section::{parse_sections, read_section_data, ElfSection};
+use crate::json::impl_to_json;
+
+pub const SHT_GNU_VERDEF:u32 = 0x6ffffffd;
+pub const SHT_GNU_VERNEED:u32 = 0x6ffffffe;
+pub const SHT_GNU_VERSYM:u32 = 0x6fffffff;
+
+/// versym中的特殊索引
+pub const VER_NDX_LOCAL:u16 = 0;
+pub const VER_NDX_GLOBAL:u16 = 1;
+/// versym中表示非默认版本(foo@VER而不是foo@@VER)的位
+pub const VERSYM_HIDDEN:u16 = 0x8000;
+
+pub const VER_FLG_BASE:u16 = 0x1;
+pub const VER_FLG_WEAK:u16 = 0x2;
+
+/// .gnu.version_d中的一个版本定义, names[0]是版本名, 其余是父版本
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VersionDefinition {
+    pub index:u16,
+    pub flags:u16,
+    pub hash:u32,
+    pub names:Vec<String>,
+}
+
+impl_to_json!(VersionDefinition { index, flags, hash, names });
+
+/// .gnu.version_r中对某个库的一个版本需求
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VersionRequirement {
+    pub index:u16,
+    pub flags:u16,
+    pub hash:u32,
+    pub name:String,
+}
+
+impl_to_json!(VersionRequirement { index, flags, hash, name });
+
+/// .gnu.version_r中对一个库的所有版本需求
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VersionNeed {
+    pub file:String,
+    pub requirements:Vec<VersionRequirement>,
+}
+
+impl_to_json!(VersionNeed { file, requirements });
+
+/// 一个动态符号的版本
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SymbolVersion {
+    pub name:String,
+    /// 非默认版本, 只能被显式要求该版本的引用绑定
+    pub hidden:bool,
+    /// 版本需求所在的库, 只有未定义符号才有
+    pub file:Option<String>,
+}
+
+impl_to_json!(SymbolVersion { name, hidden, file });
+
+/// 文件中的全部版本信息, versym与.dynsym一一对应
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VersionInfo {
+    pub versym:Vec<u16>,
+    pub definitions:Vec<VersionDefinition>,
+    pub needs:Vec<VersionNeed>,
+}
+
+impl_to_json!(VersionInfo { versym, definitions, needs });
+
+fn u16_at(data:&[u8], pos:usize) -> io::Result<u16> {
+    data.get(pos..pos + 2).map(|b| u16::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "truncated version section"))
+}
+
+fn u32_at(data:&[u8], pos:usize) -> io::Result<u32> {
+    data.get(pos..pos + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "truncated version section"))
+}
+
+/// 解析Elf64_Verdef链表, 每项后面跟着vd_cnt个Elf64_Verdaux
+fn parse_definitions(data:&[u8], strtab:&[u8], count:u32) -> io::Result<Vec<VersionDefinition>> {
+    let mut definitions = Vec::new();
+    let mut pos = 0usize;
+    for _ in 0..count {
+        let flags = u16_at(data, pos + 2)?;
+        let index = u16_at(data, pos + 4)?;
+        let aux_count = u16_at(data, pos + 6)?;
+        let hash = u32_at(data, pos + 8)?;
+        let mut aux = pos + u32_at(data, pos + 12)? as usize;
+        let mut names = Vec::new();
+        for _ in 0..aux_count {
+            names.push(string_at(strtab, u32_at(data, aux)? as usize).unwrap_or_default().into_owned());
+            aux += u32_at(data, aux + 4)? as usize;
+        }
+        definitions.push(VersionDefinition { index, flags, hash, names });
+        let next = u32_at(data, pos + 16)? as usize;
+        if next == 0 {
+            break;
+        }
+        pos += next;
+    }
+    Ok(definitions)
+}
+
+/// 解析Elf64_Verneed链表, 每项后面跟着vn_cnt个Elf64_Vernaux
+fn parse_needs(data:&[u8], strtab:&[u8], count:u32) -> io::Result<Vec<VersionNeed>> {
+    let mut needs = Vec::new();
+    let mut pos = 0usize;
+    for _ in 0..count {
+        let aux_count = u16_at(data, pos + 2)?;
+        let file = string_at(strtab, u32_at(data, pos + 4)? as usize).unwrap_or_default().into_owned();
+        let mut aux = pos + u32_at(data, pos + 8)? as usize;
+        let mut requirements = Vec::new();
+        for _ in 0..aux_count {
+            requirements.push(VersionRequirement {
+                hash:u32_at(data, aux)?,
+                flags:u16_at(data, aux + 4)?,
+                index:u16_at(data, aux + 6)?,
+                name:string_at(strtab, u32_at(data, aux + 8)? as usize).unwrap_or_default().into_owned(),
+            });
+            let next = u32_at(data, aux + 12)? as usize;
+            if next == 0 {
+                break;
+            }
+            aux += next;
+        }
+        needs.push(VersionNeed { file, requirements });
+        let next = u32_at(data, pos + 12)? as usize;
+        if next == 0 {
+            break;
+        }
+        pos += next;
+    }
+    Ok(needs)
+}
+
+impl VersionInfo {
+    /// 第index个动态符号的版本, 没有版本信息或是LOCAL/GLOBAL时返回None
+    pub fn symbol_version(&self, index:usize) -> Option<SymbolVersion> {
+        let value = *self.versym.get(index)?;
+        let hidden = value & VERSYM_HIDDEN != 0;
+        let version = value & !VERSYM_HIDDEN;
+        if version == VER_NDX_LOCAL || version == VER_NDX_GLOBAL {
+            return None;
+        }
+        if let Some(definition) = self.definitions.iter().find(|definition| definition.index == version) {
+            return Some(SymbolVersion { name:definition.names.first().cloned().unwrap_or_default(), hidden, file:None });
+        }
+        self.needs.iter().find_map(|need| {
+            need.requirements.iter()
+                .find(|requirement| requirement.index == version)
+                .map(|requirement| SymbolVersion { name:requirement.name.clone(), hidden, file:Some(need.file.clone()) })
+        })
+    }
+}
+
+/// 带版本的符号名, 默认版本用@@, 非默认版本用@, 与readelf --dyn-syms相同
+pub fn versioned_name(name:&str, version:Option<&SymbolVersion>) -> String {
+    match version {
+        Some(version) => format!("{}{}{}",name,if version.hidden { "@" } else { "@@" },version.name),
+        None => name.to_string(),
+    }
+}
+
+fn find_by_type(sections:&[ElfSection], sh_type:u32) -> Option<&ElfSection> {
+    sections.iter().find(|section| section.sh_type == sh_type)
+}
+
+/// 解析.gnu.version, .gnu.version_d和.gnu.version_r, 文件没有符号版本时各项为空
+pub fn parse_versions<R:Read + Seek>(elf:&mut R) -> io::Result<VersionInfo> {
+    let sections = parse_sections(elf)?;
+    let mut info = VersionInfo { versym:Vec::new(), definitions:Vec::new(), needs:Vec::new() };
+    if let Some(section) = find_by_type(&sections, SHT_GNU_VERSYM) {
+        info.versym = read_section_data(elf, section)?.chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect();
+    }
+    // 版本节的sh_link指向字符串表, sh_info是项数
+    let strtab = |elf:&mut R, section:&ElfSection| -> io::Result<Vec<u8>> {
+        match sections.get(section.sh_link as usize) {
+            Some(strtab) => read_section_data(elf, strtab),
+            None => Ok(Vec::new()),
+        }
+    };
+    if let Some(section) = find_by_type(&sections, SHT_GNU_VERDEF) {
+        let data = read_section_data(elf, section)?;
+        info.definitions = parse_definitions(&data, &strtab(elf, section)?, section.sh_info)?;
+    }
+    if let Some(section) = find_by_type(&sections, SHT_GNU_VERNEED) {
+        let data = read_section_data(elf, section)?;
+        info.needs = parse_needs(&data, &strtab(elf, section)?, section.sh_info)?;
+    }
+    Ok(info)
+}
+
+impl std::fmt::Display for VersionInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if !self.definitions.is_empty() {
+            writeln!(f,"Version definition section '.gnu.version_d' contains {} entries:",self.definitions.len())?;
+            for definition in &self.definitions {
+                let flags = if definition.flags & VER_FLG_BASE != 0 { "BASE" } else if definition.flags & VER_FLG_WEAK != 0 { "WEAK" } else { "none" };
+                writeln!(f,"  Rev: 1  Flags: {}  Index: {}  Cnt: {}  Name: {}",flags,definition.index,definition.names.len(),definition.names.first().map(String::as_str).unwrap_or(""))?;
+                for (i, parent) in definition.names.iter().enumerate().skip(1) {
+                    writeln!(f,"  Parent {}: {}",i,parent)?;
+                }
+            }
+            writeln!(f)?;
+        }
+        if !self.needs.is_empty() {
+            writeln!(f,"Version needs section '.gnu.version_r' contains {} entries:",self.needs.len())?;
+            for need in &self.needs {
+                writeln!(f,"  Version: 1  File: {}  Cnt: {}",need.file,need.requirements.len())?;
+                for requirement in &need.requirements {
+                    let flags = if requirement.flags & VER_FLG_WEAK != 0 { "WEAK" } else { "none" };
+                    writeln!(f,"    Name: {}  Flags: {}  Version: {}",requirement.name,flags,requirement.index)?;
+                }
+            }
+            writeln!(f)?;
+        }
+        if self.definitions.is_empty() && self.needs.is_empty() {
+            writeln!(f,"No version information found in this file.")?;
+        }
+        Ok(())
+    }
+}
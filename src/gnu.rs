@@ -0,0 +1,406 @@
+use std::io;
+use std::io::{Read, Seek, Write};
+use crate::elf::dynamic::{dynamic_value, parse_dynamic, DF_1_PIE, DT_FLAGS_1};
+use crate::elf::header::{parse_header, ElfHeader, HeaderType, MachineType};
+use crate::elf::section::{parse_sections, section_flags_to_string, section_type_name, ElfSection, SHF_ALLOC, SHF_TLS, SHT_NOBITS};
+use crate::elf::segment::{parse_segments, read_segment_data, ElfSegment, SegmentFlags, SegmentType};
+
+const SHF_GNU_RETAIN:u64 = 0x200000;
+const SHF_GNU_MBIND:u64 = 0x01000000;
+const SHF_X86_64_LARGE:u64 = 0x10000000;
+const SHF_MASKOS:u64 = 0x0ff00000;
+const SHF_MASKPROC:u64 = 0xf0000000;
+const ELFOSABI_NONE:u8 = 0;
+const ELFOSABI_GNU:u8 = 3;
+const ELFOSABI_FREEBSD:u8 = 9;
+
+/// 与binutils readelf -h/-S/-l相同格式的输出, 供解析readelf输出的脚本直接切换使用;
+/// wide对应readelf的-W, 节名和类型不截断, 每个表项只占一行
+pub struct GnuOptions {
+    pub wide:bool,
+    /// 与-h一起输出时, 节表和段表前不再重复文件类型, 入口地址和表项数量
+    pub after_header:bool,
+}
+
+fn class_name(class:u8) -> String {
+    match class {
+        0 => String::from("none"),
+        1 => String::from("ELF32"),
+        2 => String::from("ELF64"),
+        _ => format!("<unknown: {:x}>",class),
+    }
+}
+
+fn data_name(data:u8) -> String {
+    match data {
+        0 => String::from("none"),
+        1 => String::from("2's complement, little endian"),
+        2 => String::from("2's complement, big endian"),
+        _ => format!("<unknown: {:x}>",data),
+    }
+}
+
+fn osabi_name(osabi:u8) -> String {
+    let name = match osabi {
+        0 => "UNIX - System V",
+        1 => "UNIX - HP-UX",
+        2 => "UNIX - NetBSD",
+        3 => "UNIX - GNU",
+        6 => "UNIX - Solaris",
+        7 => "UNIX - AIX",
+        8 => "UNIX - IRIX",
+        9 => "UNIX - FreeBSD",
+        10 => "UNIX - TRU64",
+        11 => "Novell - Modesto",
+        12 => "UNIX - OpenBSD",
+        13 => "VMS - OpenVMS",
+        14 => "HP - Non-Stop Kernel",
+        15 => "AROS",
+        16 => "FenixOS",
+        17 => "Nuxi CloudABI",
+        18 => "Stratus Technologies OpenVOS",
+        _ => return format!("<unknown: {:x}>",osabi),
+    };
+    String::from(name)
+}
+
+fn machine_name(machine:&MachineType) -> &'static str {
+    match machine {
+        MachineType::NONE => "None",
+        MachineType::M32 => "WE32100",
+        MachineType::SPARC => "Sparc",
+        MachineType::Intel386 => "Intel 80386",
+        MachineType::Motorola68K => "MC68000",
+        MachineType::Motorola88K => "MC88000",
+        MachineType::Intel860 => "Intel 80860",
+        MachineType::MIPSBigEndian => "MIPS R3000",
+        MachineType::MIPSRs4BigEndian => "MIPS R4000 big-endian",
+        MachineType::HP_PA_RISC => "HPPA",
+        MachineType::NCUBE => "nCUBE",
+        MachineType::FujitsuVPP500 => "Fujitsu VPP500",
+        MachineType::SPARC32Plus => "Sparc v8+",
+        MachineType::Intel960 => "Intel 80960",
+        MachineType::PowerPC => "PowerPC",
+        MachineType::PowerPC64 => "PowerPC64",
+        MachineType::IBM_S390 => "IBM S/390",
+        MachineType::NECV800 => "Renesas V850 (using RH850 ABI)",
+        MachineType::FujitsuFR20 => "Fujitsu FR20",
+        MachineType::TRWRH32 => "TRW RH32",
+        MachineType::MotorolaRCE => "MCORE",
+        MachineType::AdvancedRISC => "ARM",
+        MachineType::DigitalAlpha => "Digital Alpha (old)",
+        MachineType::HitachiSH => "Renesas / SuperH SH",
+        MachineType::SunSparcV9 => "Sparc v9",
+        MachineType::SiemensTriCore => "Siemens Tricore",
+        MachineType::ARC => "ARC",
+        MachineType::RenesasH8400 => "Renesas H8/300",
+        MachineType::RenesasH8400H => "Renesas H8/300H",
+        MachineType::RenesasH8S => "Renesas H8S",
+        MachineType::RenesasH8500 => "Renesas H8/500",
+        MachineType::IntelIA64 => "Intel IA-64",
+        MachineType::MIPSX => "Stanford MIPS-X",
+        MachineType::MotorolaColdFire => "Motorola Coldfire",
+        MachineType::Motorola68HC12 => "Motorola MC68HC12 Microcontroller",
+        MachineType::MitsubishiMMA => "Fujitsu Multimedia Accelerator",
+        MachineType::SiemensPCP => "Siemens PCP",
+        MachineType::NationalCompactRISC => "Sony nCPU embedded RISC processor",
+        MachineType::AMD29K => "Denso NDR1 microprocesspr",
+        MachineType::MotorolaStarCore => "Motorola Star*Core processor",
+        MachineType::ToyotaME16 => "Toyota ME16 processor",
+        MachineType::STMicroelectronicsST100 => "STMicroelectronics ST100 processor",
+        MachineType::AdvancedLogicCorpTinyJ => "Advanced Logic Corp. TinyJ embedded processor",
+        MachineType::AMDX86_64 => "Advanced Micro Devices X86-64",
+        MachineType::AArch64 => "AArch64",
+        MachineType::RISCV => "RISC-V",
+        MachineType::RESERVED => "<unknown>",
+    }
+}
+
+/// readelf根据动态段的DF_1_PIE区分位置无关可执行文件和共享库
+fn file_type<R:Read + Seek>(elf:&mut R, header:&ElfHeader) -> String {
+    let name = match header.e_type {
+        HeaderType::NONE => "NONE (None)",
+        HeaderType::REL => "REL (Relocatable file)",
+        HeaderType::EXEC => "EXEC (Executable file)",
+        HeaderType::DYN => {
+            let dynamic = parse_dynamic(elf).unwrap_or_default();
+            match dynamic_value(&dynamic, DT_FLAGS_1).is_some_and(|flags| flags & DF_1_PIE != 0) {
+                true => "DYN (Position-Independent Executable file)",
+                false => "DYN (Shared object file)",
+            }
+        }
+        HeaderType::CORE => "CORE (Core file)",
+        HeaderType::LO_PROC => "Processor Specific: (ff00)",
+        HeaderType::HI_PROC => "Processor Specific: (ffff)",
+        HeaderType::UNKNOWN => "<unknown>",
+    };
+    String::from(name)
+}
+
+/// 没有名字的节类型按所在的保留区间显示
+fn section_type(sh_type:u32) -> String {
+    let name = section_type_name(sh_type);
+    if !name.starts_with("0x") {
+        return name;
+    }
+    match sh_type {
+        0x60000000..=0x6fffffff => format!("LOOS+{:#x}",sh_type - 0x60000000),
+        0x70000000..=0x7fffffff => format!("LOPROC+{:#x}",sh_type - 0x70000000),
+        0x80000000..=0xffffffff => format!("LOUSER+{:#x}",sh_type - 0x80000000),
+        _ => format!("<unknown>: {:x}",sh_type),
+    }
+}
+
+/// 按标志位从低到高逐个显示: ELFOSABI_GNU/FREEBSD下SHF_GNU_RETAIN为R, x86-64的SHF_X86_64_LARGE为l,
+/// 其余的操作系统和处理器专用标志分别只显示一次o和p, 未知标志显示一次x
+fn section_flags(sh_flags:u64, header:&ElfHeader) -> String {
+    let osabi = header.e_ident[7];
+    let mut remaining = sh_flags;
+    let mut letters = String::new();
+    while remaining != 0 {
+        let flag = remaining & remaining.wrapping_neg();
+        remaining &= !flag;
+        let generic = section_flags_to_string(flag);
+        let letter = match flag {
+            _ if !generic.is_empty() => generic,
+            SHF_GNU_RETAIN if matches!(osabi, ELFOSABI_GNU | ELFOSABI_FREEBSD) => String::from("R"),
+            SHF_GNU_MBIND if matches!(osabi, ELFOSABI_NONE | ELFOSABI_GNU) => String::from("D"),
+            SHF_X86_64_LARGE if matches!(header.e_machine, MachineType::AMDX86_64) => String::from("l"),
+            _ if flag & SHF_MASKOS != 0 => {
+                remaining &= !SHF_MASKOS;
+                String::from("o")
+            }
+            _ if flag & SHF_MASKPROC != 0 => {
+                remaining &= !SHF_MASKPROC;
+                String::from("p")
+            }
+            _ => {
+                remaining &= SHF_MASKOS | SHF_MASKPROC;
+                String::from("x")
+            }
+        };
+        letters.push_str(&letter);
+    }
+    letters
+}
+
+/// 非宽模式下超过17个字符的节名截断为12个字符加[...]
+fn section_name(name:&str, wide:bool) -> String {
+    if wide || name.chars().count() <= 17 {
+        return format!("{:<17}",name);
+    }
+    format!("{}[...]",name.chars().take(12).collect::<String>())
+}
+
+fn segment_type(p_type:&SegmentType) -> String {
+    match p_type {
+        SegmentType::LOPROC => String::from("LOPROC+0"),
+        SegmentType::HIPROC => String::from("LOPROC+0xfffffff"),
+        _ => p_type.to_string(),
+    }
+}
+
+fn segment_flags(p_flags:&SegmentFlags) -> String {
+    let (r, w, x) = match p_flags {
+        SegmentFlags::NULL | SegmentFlags::UNKNOWN => (false, false, false),
+        SegmentFlags::X => (false, false, true),
+        SegmentFlags::W => (false, true, false),
+        SegmentFlags::WX => (false, true, true),
+        SegmentFlags::R => (true, false, false),
+        SegmentFlags::RX => (true, false, true),
+        SegmentFlags::RW => (true, true, false),
+        SegmentFlags::RWX => (true, true, true),
+    };
+    [(r, 'R'), (w, 'W'), (x, 'E')].iter().map(|(set, c)| if *set { *c } else { ' ' }).collect()
+}
+
+/// 与binutils的ELF_SECTION_IN_SEGMENT_STRICT相同的判断, 用于Section to Segment mapping
+fn section_in_segment(section:&ElfSection, segment:&ElfSegment) -> bool {
+    let tls = section.sh_flags & SHF_TLS != 0;
+    let alloc = section.sh_flags & SHF_ALLOC != 0;
+    let nobits = section.sh_type == SHT_NOBITS;
+    let p_type = &segment.p_type;
+    // .tbss只在PT_TLS中占用空间
+    if tls && nobits && !matches!(p_type, SegmentType::TLS) {
+        return false;
+    }
+    let size = section.sh_size;
+    let tls_allowed = match tls {
+        true => matches!(p_type, SegmentType::TLS | SegmentType::GNU_RELRO | SegmentType::LOAD),
+        false => !matches!(p_type, SegmentType::TLS | SegmentType::PHDR),
+    };
+    // PT_LOAD等段只包含SHF_ALLOC的节
+    let alloc_allowed = alloc || !matches!(p_type, SegmentType::LOAD | SegmentType::DYNAMIC | SegmentType::GNU_EH_FRAME | SegmentType::GNU_STACK | SegmentType::GNU_RELRO | SegmentType::GNU_SFRAME);
+    let in_file = nobits || (section.sh_offset >= segment.p_offset
+        && section.sh_offset - segment.p_offset <= segment.p_filesz.wrapping_sub(1)
+        && (section.sh_offset - segment.p_offset).wrapping_add(size) <= segment.p_filesz);
+    let in_memory = !alloc || (section.sh_addr >= segment.p_vaddr
+        && section.sh_addr - segment.p_vaddr <= segment.p_memsz.wrapping_sub(1)
+        && (section.sh_addr - segment.p_vaddr).wrapping_add(size) <= segment.p_memsz);
+    // PT_DYNAMIC和PT_NOTE的开头和结尾不能是空节
+    let not_empty_edge = !matches!(p_type, SegmentType::DYNAMIC | SegmentType::NOTE)
+        || size != 0
+        || segment.p_memsz == 0
+        || ((nobits || (section.sh_offset > segment.p_offset && section.sh_offset - segment.p_offset < segment.p_filesz))
+            && (!alloc || (section.sh_addr > segment.p_vaddr && section.sh_addr - segment.p_vaddr < segment.p_memsz)));
+    tls_allowed && alloc_allowed && in_file && in_memory && not_empty_edge
+}
+
+fn read_interpreter<R:Read + Seek>(elf:&mut R, segment:&ElfSegment) -> io::Result<String> {
+    let data = read_segment_data(elf, segment)?;
+    let len = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    Ok(String::from_utf8_lossy(&data[..len]).into_owned())
+}
+
+/// readelf -h
+pub fn write_file_header<R:Read + Seek, W:Write + ?Sized>(elf:&mut R, out:&mut W) -> io::Result<()> {
+    let header = parse_header(elf)?;
+    let ident = &header.e_ident;
+    writeln!(out,"ELF Header:")?;
+    write!(out,"  Magic:   ")?;
+    for byte in ident {
+        write!(out,"{:02x} ",byte)?;
+    }
+    writeln!(out)?;
+    let version = match ident[6] {
+        1 => String::from("1 (current)"),
+        0 => String::from("0"),
+        version => format!("{} <unknown>",version),
+    };
+    let fields = [
+        ("Class:", class_name(ident[4])),
+        ("Data:", data_name(ident[5])),
+        ("Version:", version),
+        ("OS/ABI:", osabi_name(ident[7])),
+        ("ABI Version:", ident[8].to_string()),
+        ("Type:", file_type(elf, &header)),
+        ("Machine:", String::from(machine_name(&header.e_machine))),
+        ("Version:", format!("{:#x}",header.e_version)),
+        ("Entry point address:", format!("{:#x}",header.e_entry)),
+        ("Start of program headers:", format!("{} (bytes into file)",header.e_phoff)),
+        ("Start of section headers:", format!("{} (bytes into file)",header.e_shoff)),
+        ("Flags:", format!("{:#x}",header.e_flags)),
+        ("Size of this header:", format!("{} (bytes)",header.e_ehsize)),
+        ("Size of program headers:", format!("{} (bytes)",header.e_phentsize)),
+        ("Number of program headers:", header.e_phnum.to_string()),
+        ("Size of section headers:", format!("{} (bytes)",header.e_shentsize)),
+        ("Number of section headers:", header.e_shnum.to_string()),
+        ("Section header string table index:", header.e_shstrndx.to_string()),
+    ];
+    for (name, value) in fields {
+        writeln!(out,"  {:<35}{}",name,value)?;
+    }
+    Ok(())
+}
+
+/// readelf -S, 节表后附带标志说明
+pub fn write_section_headers<R:Read + Seek, W:Write + ?Sized>(elf:&mut R, out:&mut W, options:&GnuOptions) -> io::Result<()> {
+    let header = parse_header(elf)?;
+    if header.e_shnum == 0 {
+        writeln!(out)?;
+        return writeln!(out,"There are no sections in this file.");
+    }
+    let sections = parse_sections(elf)?;
+    if !options.after_header {
+        match header.e_shnum {
+            1 => writeln!(out,"There is 1 section header, starting at offset {:#x}:",header.e_shoff)?,
+            count => writeln!(out,"There are {} section headers, starting at offset {:#x}:",count,header.e_shoff)?,
+        }
+    }
+    writeln!(out)?;
+    writeln!(out,"{}",if sections.len() > 1 { "Section Headers:" } else { "Section Header:" })?;
+    if options.wide {
+        writeln!(out,"  [Nr] Name              Type            Address          Off    Size   ES Flg Lk Inf Al")?;
+    } else {
+        writeln!(out,"  [Nr] Name              Type             Address           Offset")?;
+        writeln!(out,"       Size              EntSize          Flags  Link  Info  Align")?;
+    }
+    for section in &sections {
+        let name = section_name(&section.name, options.wide);
+        let flags = section_flags(section.sh_flags, &header);
+        if options.wide {
+            writeln!(out,"  [{:>2}] {} {:<15} {:016x} {:06x} {:06x} {:02x} {:>3} {:>2} {:>3} {:>2}",
+                     section.index,name,section_type(section.sh_type),section.sh_addr,section.sh_offset,section.sh_size,section.sh_entsize,flags,section.sh_link,section.sh_info,section.sh_addralign)?;
+        } else {
+            let mut sh_type = section_type(section.sh_type);
+            sh_type.truncate(15);
+            writeln!(out,"  [{:>2}] {} {:<15}  {:016x}  {:08x}",section.index,name,sh_type,section.sh_addr,section.sh_offset)?;
+            writeln!(out,"       {:016x}  {:016x} {:>3}      {:>2}   {:>3}     {}",section.sh_size,section.sh_entsize,flags,section.sh_link,section.sh_info,section.sh_addralign)?;
+        }
+    }
+    writeln!(out,"Key to Flags:")?;
+    writeln!(out,"  W (write), A (alloc), X (execute), M (merge), S (strings), I (info),")?;
+    writeln!(out,"  L (link order), O (extra OS processing required), G (group), T (TLS),")?;
+    writeln!(out,"  C (compressed), x (unknown), o (OS specific), E (exclude),")?;
+    write!(out,"  ")?;
+    if matches!(header.e_ident[7], ELFOSABI_GNU | ELFOSABI_FREEBSD) {
+        write!(out,"R (retain), ")?;
+    }
+    match header.e_machine {
+        MachineType::AMDX86_64 => writeln!(out,"D (mbind), l (large), p (processor specific)"),
+        MachineType::AdvancedRISC => writeln!(out,"D (mbind), y (purecode), p (processor specific)"),
+        _ => writeln!(out,"D (mbind), p (processor specific)"),
+    }
+}
+
+/// readelf -l, 包括程序解释器和Section to Segment mapping
+pub fn write_program_headers<R:Read + Seek, W:Write + ?Sized>(elf:&mut R, out:&mut W, options:&GnuOptions) -> io::Result<()> {
+    let header = parse_header(elf)?;
+    if header.e_phnum == 0 {
+        writeln!(out)?;
+        return writeln!(out,"There are no program headers in this file.");
+    }
+    let segments = parse_segments(elf)?;
+    if !options.after_header {
+        writeln!(out)?;
+        writeln!(out,"Elf file type is {}",file_type(elf, &header))?;
+        writeln!(out,"Entry point {:#x}",header.e_entry)?;
+        match header.e_phnum {
+            1 => writeln!(out,"There is 1 program header, starting at offset {}",header.e_phoff)?,
+            count => writeln!(out,"There are {} program headers, starting at offset {}",count,header.e_phoff)?,
+        }
+    }
+    writeln!(out)?;
+    writeln!(out,"{}",if segments.len() > 1 { "Program Headers:" } else { "Program Header:" })?;
+    if options.wide {
+        writeln!(out,"  Type           Offset   VirtAddr           PhysAddr           FileSiz  MemSiz   Flg Align")?;
+    } else {
+        writeln!(out,"  Type           Offset             VirtAddr           PhysAddr")?;
+        writeln!(out,"                 FileSiz            MemSiz              Flags  Align")?;
+    }
+    for segment in &segments {
+        let mut p_type = segment_type(&segment.p_type);
+        p_type.truncate(14);
+        let flags = segment_flags(&segment.p_flags);
+        if options.wide {
+            // readelf用%#lx输出对齐, 0不带0x前缀
+            let align = match segment.p_align {
+                0 => String::from("0"),
+                align => format!("{:#x}",align),
+            };
+            writeln!(out,"  {:<14} {:#08x} {:#018x} {:#018x} {:#08x} {:#08x} {} {}",
+                     p_type,segment.p_offset,segment.p_vaddr,segment.p_paddr,segment.p_filesz,segment.p_memsz,flags,align)?;
+        } else {
+            writeln!(out,"  {:<14} {:#018x} {:#018x} {:#018x}",p_type,segment.p_offset,segment.p_vaddr,segment.p_paddr)?;
+            writeln!(out,"                 {:#018x} {:#018x}  {}    {:#x}",segment.p_filesz,segment.p_memsz,flags,segment.p_align)?;
+        }
+        if matches!(segment.p_type, SegmentType::INTERP) {
+            writeln!(out,"      [Requesting program interpreter: {}]",read_interpreter(elf, segment)?)?;
+        }
+    }
+    if header.e_shnum == 0 {
+        return Ok(());
+    }
+    let sections = parse_sections(elf)?;
+    writeln!(out)?;
+    writeln!(out," Section to Segment mapping:")?;
+    writeln!(out,"  Segment Sections...")?;
+    for (i, segment) in segments.iter().enumerate() {
+        write!(out,"   {:02}     ",i)?;
+        for section in sections.iter().skip(1).filter(|section| section_in_segment(section, segment)) {
+            write!(out,"{} ",section.name)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::elf::dynamic::*;
+use crate::elf::header::{parse_header, HeaderType, MachineType};
+use crate::elf::note::*;
+use crate::elf::section::{find_section, parse_sections};
+use crate::elf::segment::{parse_segments, SegmentFlags, SegmentType};
+use crate::elf::symbol::{parse_dynamic_symbols, parse_symbols, ElfSymbol};
+use crate::json::impl_to_json;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[allow(non_camel_case_types)]
+pub enum CheckStatus {
+    PASS,
+    FAIL,
+    /// 不适用于该文件(如非x86架构的CET检查)
+    SKIP,
+}
+
+impl_to_json!(enum CheckStatus);
+
+
+impl CheckStatus {
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        match self {
+            CheckStatus::PASS => String::from("PASS"),
+            CheckStatus::FAIL => String::from("FAIL"),
+            CheckStatus::SKIP => String::from("SKIP"),
+        }
+    }
+}
+
+/// 一项加固检查的结果, name是稳定的机器可读标识
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Check {
+    pub name:&'static str,
+    pub status:CheckStatus,
+    pub detail:String,
+}
+
+impl_to_json!(Check { name, status, detail });
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HardeningReport {
+    pub checks:Vec<Check>,
+}
+
+impl_to_json!(HardeningReport { checks });
+
+impl HardeningReport {
+    /// 没有任何一项检查失败
+    pub fn passed(&self) -> bool {
+        !self.checks.iter().any(|check| matches!(check.status, CheckStatus::FAIL))
+    }
+}
+
+impl std::fmt::Display for HardeningReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for check in &self.checks {
+            writeln!(f,"{:<10} {:<5}{}",check.name,check.status.to_string(),check.detail)?;
+        }
+        Ok(())
+    }
+}
+
+fn check(name:&'static str, status:CheckStatus, detail:impl Into<String>) -> Check {
+    Check { name, status, detail:detail.into() }
+}
+
+fn has_flag(entries:&[ElfDynamic], tag:u64, flag:u64) -> bool {
+    dynamic_value(entries, tag).is_some_and(|value| value & flag != 0)
+}
+
+/// GNU属性注释, 优先从节读取, 没有节头时从PT_NOTE段读取
+fn gnu_feature_1_and<R:Read + Seek>(elf:&mut R, pr_type:u32) -> io::Result<Option<u32>> {
+    let mut notes:Vec<ElfNote> = parse_note_sections(elf).unwrap_or_default().into_iter()
+        .flat_map(|(_, notes)| notes)
+        .collect();
+    if notes.is_empty() {
+        notes = parse_note_segments(elf)?;
+    }
+    Ok(notes.iter().find_map(|note| note.gnu_property_u32(pr_type)))
+}
+
+/// 检查文件的RELRO, NX, PIE, 栈保护, FORTIFY, RPATH/RUNPATH, CET/BTI和strip状态
+pub fn check_hardening<R:Read + Seek>(elf:&mut R) -> io::Result<HardeningReport> {
+    let header = parse_header(elf)?;
+    let segments = parse_segments(elf)?;
+    let sections = parse_sections(elf).unwrap_or_default();
+    let dynamic = parse_dynamic(elf)?;
+    let symtab = parse_symbols(elf).unwrap_or_default();
+    let dynsym = parse_dynamic_symbols(elf).unwrap_or_default();
+    let symbols:Vec<&ElfSymbol> = dynsym.iter().chain(symtab.iter()).collect();
+    let relocatable = matches!(header.e_type, HeaderType::REL);
+    let mut checks = Vec::new();
+
+    let relro = segments.iter().any(|segment| matches!(segment.p_type, SegmentType::GNU_RELRO));
+    let bind_now = dynamic_value(&dynamic, DT_BIND_NOW).is_some()
+        || has_flag(&dynamic, DT_FLAGS, DF_BIND_NOW)
+        || has_flag(&dynamic, DT_FLAGS_1, DF_1_NOW);
+    checks.push(match (relocatable, relro, bind_now) {
+        (true, _, _) => check("relro", CheckStatus::SKIP, "relocatable object"),
+        (_, true, true) => check("relro", CheckStatus::PASS, "full RELRO (PT_GNU_RELRO + BIND_NOW)"),
+        (_, true, false) => check("relro", CheckStatus::FAIL, "partial RELRO (PT_GNU_RELRO without BIND_NOW)"),
+        (_, false, _) => check("relro", CheckStatus::FAIL, "no PT_GNU_RELRO"),
+    });
+
+    let stack = segments.iter().find(|segment| matches!(segment.p_type, SegmentType::GNU_STACK));
+    checks.push(match stack {
+        _ if relocatable => check("nx", CheckStatus::SKIP, "relocatable object"),
+        Some(segment) if matches!(segment.p_flags, SegmentFlags::X | SegmentFlags::WX | SegmentFlags::RX | SegmentFlags::RWX) => {
+            check("nx", CheckStatus::FAIL, format!("PT_GNU_STACK is executable ({})",segment.p_flags.to_string()))
+        }
+        Some(segment) => check("nx", CheckStatus::PASS, format!("PT_GNU_STACK is not executable ({})",segment.p_flags.to_string())),
+        None => check("nx", CheckStatus::FAIL, "no PT_GNU_STACK, the stack is executable by default"),
+    });
+
+    let interp = segments.iter().any(|segment| matches!(segment.p_type, SegmentType::INTERP));
+    checks.push(match header.e_type {
+        HeaderType::DYN if has_flag(&dynamic, DT_FLAGS_1, DF_1_PIE) => check("pie", CheckStatus::PASS, "ET_DYN with DF_1_PIE"),
+        HeaderType::DYN if interp => check("pie", CheckStatus::PASS, "ET_DYN with PT_INTERP"),
+        HeaderType::DYN => check("pie", CheckStatus::SKIP, "shared object"),
+        HeaderType::EXEC => check("pie", CheckStatus::FAIL, "ET_EXEC, loaded at a fixed address"),
+        _ => check("pie", CheckStatus::SKIP, "not an executable"),
+    });
+
+    let canary = symbols.iter().find(|symbol| symbol.name.starts_with("__stack_chk_fail") || symbol.name == "__stack_chk_guard");
+    checks.push(match canary {
+        Some(symbol) => check("canary", CheckStatus::PASS, format!("uses {}",symbol.name)),
+        None => check("canary", CheckStatus::FAIL, "no __stack_chk_fail reference"),
+    });
+
+    let mut fortified:Vec<&str> = symbols.iter()
+        .filter(|symbol| symbol.name.starts_with("__") && symbol.name.ends_with("_chk") && !symbol.name.starts_with("__stack_chk"))
+        .map(|symbol| symbol.name.as_str())
+        .collect();
+    fortified.sort();
+    fortified.dedup();
+    checks.push(if fortified.is_empty() {
+        check("fortify", CheckStatus::FAIL, "no *_chk functions used")
+    } else {
+        check("fortify", CheckStatus::PASS, format!("{} fortified functions: {}",fortified.len(),fortified.join(", ")))
+    });
+
+    let rpath = dynamic_strings(&dynamic, DT_RPATH);
+    let runpath = dynamic_strings(&dynamic, DT_RUNPATH);
+    checks.push(match (rpath.is_empty(), runpath.is_empty()) {
+        (true, true) => check("rpath", CheckStatus::PASS, "no RPATH/RUNPATH"),
+        (false, _) => check("rpath", CheckStatus::FAIL, format!("RPATH {}",rpath.join(":"))),
+        (true, false) => check("rpath", CheckStatus::FAIL, format!("RUNPATH {}",runpath.join(":"))),
+    });
+
+    match header.e_machine {
+        MachineType::AMDX86_64 | MachineType::Intel386 => {
+            let features = gnu_feature_1_and(elf, GNU_PROPERTY_X86_FEATURE_1_AND)?.unwrap_or(0);
+            let ibt = features & GNU_PROPERTY_X86_FEATURE_1_IBT != 0;
+            let shstk = features & GNU_PROPERTY_X86_FEATURE_1_SHSTK != 0;
+            let detail = format!("IBT {}, SHSTK {}",if ibt { "enabled" } else { "disabled" },if shstk { "enabled" } else { "disabled" });
+            checks.push(check("cet", if ibt && shstk { CheckStatus::PASS } else { CheckStatus::FAIL }, detail));
+        }
+        MachineType::AArch64 => {
+            let features = gnu_feature_1_and(elf, GNU_PROPERTY_AARCH64_FEATURE_1_AND)?.unwrap_or(0);
+            let bti = features & GNU_PROPERTY_AARCH64_FEATURE_1_BTI != 0;
+            let pac = features & GNU_PROPERTY_AARCH64_FEATURE_1_PAC != 0;
+            let detail = format!("BTI {}, PAC {}",if bti { "enabled" } else { "disabled" },if pac { "enabled" } else { "disabled" });
+            checks.push(check("bti", if bti { CheckStatus::PASS } else { CheckStatus::FAIL }, detail));
+        }
+        _ => checks.push(check("cet", CheckStatus::SKIP, "control-flow protection is only checked on x86 and AArch64")),
+    }
+
+    let debug = find_section(&sections, ".debug_info").is_some();
+    checks.push(match (symtab.is_empty(), debug) {
+        (true, false) => check("stripped", CheckStatus::PASS, "no .symtab"),
+        (true, true) => check("stripped", CheckStatus::FAIL, "no .symtab, but debug information is present"),
+        (false, false) => check("stripped", CheckStatus::FAIL, format!(".symtab with {} symbols",symtab.len())),
+        (false, true) => check("stripped", CheckStatus::FAIL, format!(".symtab with {} symbols and debug information",symtab.len())),
+    });
+
+    Ok(HardeningReport { checks })
+}
@@ -0,0 +1,251 @@
+use std::fmt::Formatter;
+
+/// 不依赖第三方库的JSON值, 对象保留插入顺序以保证输出稳定
+///
+/// 库中的类型通过ToJson转换为JSON, 结构与开启serde特性后`#[derive(Serialize)]`的输出相同:
+/// 结构体按字段名输出, 数值不做格式化, 无数据的枚举输出变体名
+pub enum JsonValue {
+    NULL,
+    BOOL(bool),
+    NUMBER(u64),
+    INTEGER(i64),
+    STRING(String),
+    ARRAY(Vec<JsonValue>),
+    OBJECT(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    pub fn object<'a>(fields:impl IntoIterator<Item = (&'a str, JsonValue)>) -> Self {
+        JsonValue::OBJECT(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+
+    fn write(&self, f:&mut Formatter<'_>, pretty:bool, depth:usize) -> std::fmt::Result {
+        // 美化输出时每层缩进两个空格
+        let newline = |f:&mut Formatter<'_>, depth:usize| -> std::fmt::Result {
+            if pretty {
+                write!(f,"\n{:indent$}","",indent = depth * 2)?;
+            }
+            Ok(())
+        };
+        match self {
+            JsonValue::NULL => write!(f,"null"),
+            JsonValue::BOOL(value) => write!(f,"{}",value),
+            JsonValue::NUMBER(value) => write!(f,"{}",value),
+            JsonValue::INTEGER(value) => write!(f,"{}",value),
+            JsonValue::STRING(value) => write_string(f, value),
+            JsonValue::ARRAY(values) if values.is_empty() => write!(f,"[]"),
+            JsonValue::ARRAY(values) => {
+                write!(f,"[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f,",")?;
+                    }
+                    newline(f, depth + 1)?;
+                    value.write(f, pretty, depth + 1)?;
+                }
+                newline(f, depth)?;
+                write!(f,"]")
+            }
+            JsonValue::OBJECT(fields) if fields.is_empty() => write!(f,"{{}}"),
+            JsonValue::OBJECT(fields) => {
+                write!(f,"{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f,",")?;
+                    }
+                    newline(f, depth + 1)?;
+                    write_string(f, key)?;
+                    write!(f,"{}",if pretty { ": " } else { ":" })?;
+                    value.write(f, pretty, depth + 1)?;
+                }
+                newline(f, depth)?;
+                write!(f,"}}")
+            }
+        }
+    }
+}
+
+fn write_string(f:&mut Formatter<'_>, value:&str) -> std::fmt::Result {
+    write!(f,"\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => write!(f,"\\\"")?,
+            '\\' => write!(f,"\\\\")?,
+            '\n' => write!(f,"\\n")?,
+            '\r' => write!(f,"\\r")?,
+            '\t' => write!(f,"\\t")?,
+            c if (c as u32) < 0x20 => write!(f,"\\u{:04x}",c as u32)?,
+            c => write!(f,"{}",c)?,
+        }
+    }
+    write!(f,"\"")
+}
+
+/// `{}`输出紧凑的单行JSON, `{:#}`输出带缩进的多行JSON
+impl std::fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write(f, f.alternate(), 0)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::BOOL(value)
+    }
+}
+
+impl From<u64> for JsonValue {
+    fn from(value: u64) -> Self {
+        JsonValue::NUMBER(value)
+    }
+}
+
+impl From<u32> for JsonValue {
+    fn from(value: u32) -> Self {
+        JsonValue::NUMBER(value as u64)
+    }
+}
+
+impl From<u16> for JsonValue {
+    fn from(value: u16) -> Self {
+        JsonValue::NUMBER(value as u64)
+    }
+}
+
+impl From<usize> for JsonValue {
+    fn from(value: usize) -> Self {
+        JsonValue::NUMBER(value as u64)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::STRING(value.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::STRING(value)
+    }
+}
+
+impl<T:Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(JsonValue::NULL, Into::into)
+    }
+}
+
+impl<T:Into<JsonValue>> From<Vec<T>> for JsonValue {
+    fn from(values: Vec<T>) -> Self {
+        JsonValue::ARRAY(values.into_iter().map(Into::into).collect())
+    }
+}
+
+/// 转换为JSON值, 用于各个视图的--output json
+pub trait ToJson {
+    fn to_json(&self) -> JsonValue;
+}
+
+/// 为结构体按字段生成ToJson, 或为无数据的枚举按变体名生成ToJson(需要Debug)
+macro_rules! impl_to_json {
+    (enum $type:ty) => {
+        impl $crate::json::ToJson for $type {
+            fn to_json(&self) -> $crate::json::JsonValue {
+                $crate::json::JsonValue::STRING(format!("{:?}",self))
+            }
+        }
+    };
+    ($type:ty { $($field:ident),* $(,)? }) => {
+        impl $crate::json::ToJson for $type {
+            fn to_json(&self) -> $crate::json::JsonValue {
+                $crate::json::JsonValue::object([$((stringify!($field), $crate::json::ToJson::to_json(&self.$field))),*])
+            }
+        }
+    };
+}
+pub(crate) use impl_to_json;
+
+macro_rules! impl_to_json_unsigned {
+    ($($type:ty),*) => {
+        $(impl ToJson for $type {
+            fn to_json(&self) -> JsonValue {
+                JsonValue::NUMBER(*self as u64)
+            }
+        })*
+    };
+}
+
+macro_rules! impl_to_json_signed {
+    ($($type:ty),*) => {
+        $(impl ToJson for $type {
+            fn to_json(&self) -> JsonValue {
+                JsonValue::INTEGER(*self as i64)
+            }
+        })*
+    };
+}
+
+impl_to_json_unsigned!(u8, u16, u32, u64, usize);
+impl_to_json_signed!(i8, i16, i32, i64);
+
+impl ToJson for bool {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::BOOL(*self)
+    }
+}
+
+impl ToJson for char {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::STRING(self.to_string())
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::STRING(self.to_string())
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::STRING(self.clone())
+    }
+}
+
+impl<T:ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+impl<T:ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_ref().map_or(JsonValue::NULL, ToJson::to_json)
+    }
+}
+
+impl<T:ToJson> ToJson for [T] {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::ARRAY(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T:ToJson, const N:usize> ToJson for [T;N] {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T:ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+
+/// 元组与serde一样输出为数组
+impl<A:ToJson, B:ToJson> ToJson for (A, B) {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::ARRAY(vec![self.0.to_json(), self.1.to_json()])
+    }
+}
@@ -3,4 +3,37 @@ pub mod elf {
     pub mod section;
 
     pub mod segment;
-}
\ No newline at end of file
+    pub mod symbol;
+    pub mod archive;
+    pub mod note;
+    pub mod core;
+    pub mod dynamic;
+    pub mod version;
+    pub mod bounds;
+    pub mod mapped;
+    pub mod file;
+    pub mod hash;
+}
+
+pub mod dwarf {
+    pub mod reader;
+    pub mod constants;
+    pub mod sections;
+    pub mod line;
+    pub mod abbrev;
+    pub mod info;
+    pub mod ranges;
+    pub mod frame;
+}
+
+pub mod abi;
+pub mod address;
+pub mod addr2line;
+pub mod check;
+pub mod deps;
+pub mod diff;
+pub mod gnu;
+pub mod hardening;
+pub mod json;
+pub mod size;
+pub mod unresolved;
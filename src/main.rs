@@ -1,13 +1,707 @@
+use std::collections::{BTreeMap, HashSet};
 use std::fs::File;
-use easyreadelf::elf;
-use clap::{App, Arg};
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use easyreadelf::{abi, addr2line, address, check, deps, diff, dwarf, elf, gnu, hardening, size, unresolved};
+use easyreadelf::addr2line::AddressRef;
+use easyreadelf::elf::file::ElfFile;
+use easyreadelf::json::{JsonValue, ToJson};
+use clap::{App, Arg, ArgMatches};
 
 fn usage() {
-    println!("Usage: easy-readelf [OPTION]... FILE");
+    println!("Usage: easy-readelf [OPTION]... FILE...");
     println!("OPTIONS:");
+    println!("\t-a(--all)\tEquivalent to -h -s -l -d --symbols --version-info -n");
+    println!("\t-e(--headers)\tEquivalent to -h -s -l");
+    println!("\t-c(--archive-index)\tTo read the symbol index of a static archive");
     println!("\t-h(--header)\tTo read the elf header");
-    println!("\t-s(--sections)\tTo read the elf section table");
+    println!("\t-s(-S, --sections)\tTo read the elf section table");
     println!("\t-l(--segments)\tTo read the elf program header table");
+    println!("\t-d(--dynamic)\tTo read the dynamic section");
+    println!("\t--symbols\tTo read the .symtab and .dynsym symbol tables");
+    println!("\t-I(--histogram)\tTo show the bucket list length histogram of the .hash and .gnu.hash tables");
+    println!("\t--version-info\tTo read the symbol version definitions and requirements");
+    println!("\t-n(--notes)\tTo read the notes");
+    println!("\t--hardening\tTo check RELRO, NX, PIE, stack canary, FORTIFY, RPATH, CET/BTI and stripping");
+    println!("\t--check\tTo report structural problems (sections past EOF or overlapping, misaligned or unsorted PT_LOAD, invalid sh_link/sh_info), exiting non-zero if any are found");
+    println!("\t--core\tTo read the threads, signal, auxiliary vector and mapped files of a core dump");
+    println!("\t--debug-dump=decodedline\tTo read the decoded DWARF line table");
+    println!("\t--debug-dump=info\tTo read the DWARF debugging information entries");
+    println!("\t--debug-dump=frames-interp\tTo read the .eh_frame unwind tables and .eh_frame_hdr");
+    println!("\t--dump-vaddr=ADDR:LEN\tTo dump LEN bytes at virtual address ADDR as the loader maps them, zero-filling .bss");
+    println!("\t-r(--recursive)\tTo scan directories recursively, skipping files that are not ELF files or static archives");
+    println!("\t-L(--follow-symlinks)\tTo follow symbolic links found while scanning directories");
+    println!("\t-j(--jobs) N\tTo parse N files concurrently (0 for one per CPU), the output keeps the order of the files");
+    println!("\t--report-skipped\tTo report the skipped files on stderr");
+    println!("\t--output text|json\tTo print the views as JSON, one object per file keyed by view name");
+    println!("\t--output gnu [-W]\tTo print the -h, -s and -l views in the layout of binutils readelf -h/-S/-l, -W for wide mode");
+    println!("Several views can be combined, they are printed in the order above for each file.");
+    println!("SUBCOMMANDS:");
+    println!("\tdeps [--sysroot DIR] [--library-path PATHS] FILE\tTo resolve the shared library dependency tree without running the file");
+    println!("\tcheck-symbols [--sysroot DIR] [--library-path PATHS] FILE [LIB]...\tTo report undefined dynamic symbols that no dependency exports at the required version");
+    println!("\tdiff [--output text|json] OLD NEW\tTo compare the headers, sections, program headers, dynamic entries, exported symbols and notes of two files");
+    println!("\tabi [--output text|json] OLD NEW\tTo classify the ABI changes between two versions of a shared library, exiting non-zero on breakage");
+    println!("\tsize [-d segments|sections|symbols|compileunits] [-n N] [--diff BASE] FILE\tTo attribute file and VM size to segments, sections, symbols or compile units");
+    println!("\tlocate [--offset] FILE VALUE...\tTo map virtual addresses (or file offsets) to the other coordinate, segment, section and symbol");
+    println!("\taddr2line [-r] [--addresses-file LOG] FILE [ADDR]...\tTo translate addresses (0x.., module+0x.. or symbol+0x..) into function, inline chain and file:line");
+}
+
+/// 由--output, -W和--debug-dump决定的输出方式
+struct ViewOptions {
+    json:bool,
+    /// --output gnu时-h/-s/-l按GNU readelf的格式输出, 其余视图按文本格式输出
+    gnu:Option<gnu::GnuOptions>,
+    /// --debug-dump的类型
+    debug_dump:String,
+    /// --dump-vaddr的起始地址和长度
+    dump_vaddr:(u64, u64),
+}
+
+/// 对每个输入文件相同的处理方式
+struct FileOptions<'a> {
+    selected:Vec<&'a str>,
+    archive_index:bool,
+    /// 在每个文件的输出前标注文件名
+    show_name:bool,
+    /// 在err中报告跳过的非ELF文件
+    report:bool,
+    views:ViewOptions,
+}
+
+/// 一个视图的结果: 文本格式写入out, JSON格式返回要输出的值, 由调用者统一输出;
+/// passed为false表示检查类视图(--check)发现了问题, 调用者把文件计入失败汇总
+struct Shown {
+    value:Option<JsonValue>,
+    passed:bool,
+}
+
+impl Shown {
+    fn text() -> Shown {
+        Shown { value:None, passed:true }
+    }
+
+    fn json(value:JsonValue) -> Shown {
+        Shown { value:Some(value), passed:true }
+    }
+}
+
+type View = fn(&ElfFile, &ViewOptions, &mut dyn Write) -> io::Result<Shown>;
+
+fn show_header(file:&ElfFile, options:&ViewOptions, out:&mut dyn Write) -> io::Result<Shown> {
+    if options.gnu.is_some() {
+        gnu::write_file_header(&mut file.cursor(), out)?;
+        return Ok(Shown::text());
+    }
+    let header = file.header();
+    if options.json {
+        return Ok(Shown::json(header.to_json()));
+    }
+    writeln!(out,"{}",header)?;
+    Ok(Shown::text())
+}
+
+fn show_sections(file:&ElfFile, options:&ViewOptions, out:&mut dyn Write) -> io::Result<Shown> {
+    if let Some(gnu_options) = &options.gnu {
+        gnu::write_section_headers(&mut file.cursor(), out, gnu_options)?;
+        return Ok(Shown::text());
+    }
+    let sections = file.sections()?;
+    if options.json {
+        return Ok(Shown::json(sections.to_json()));
+    }
+    writeln!(out,"{}",elf::section::sections_title())?;
+    for section in sections {
+        writeln!(out,"{}",section)?;
+    }
+    Ok(Shown::text())
+}
+
+fn show_segments(file:&ElfFile, options:&ViewOptions, out:&mut dyn Write) -> io::Result<Shown> {
+    if let Some(gnu_options) = &options.gnu {
+        gnu::write_program_headers(&mut file.cursor(), out, gnu_options)?;
+        return Ok(Shown::text());
+    }
+    let segments = file.segments()?;
+    if options.json {
+        return Ok(Shown::json(segments.to_json()));
+    }
+    writeln!(out,"{}",elf::segment::segments_title(file.header()))?;
+    for segment in segments {
+        writeln!(out,"{}",segment)?;
+    }
+    Ok(Shown::text())
+}
+
+fn show_symbols(file:&ElfFile, options:&ViewOptions, out:&mut dyn Write) -> io::Result<Shown> {
+    let symtab = file.symbols()?;
+    let dynsym = file.dynamic_symbols()?;
+    if options.json {
+        return Ok(Shown::json(JsonValue::object([("symtab", symtab.to_json()), ("dynsym", dynsym.to_json())])));
+    }
+    for (name, symbols) in [(".dynsym", dynsym), (".symtab", symtab)] {
+        if symbols.is_empty() {
+            continue;
+        }
+        writeln!(out)?;
+        writeln!(out,"Symbol table '{}' contains {} entries:",name,symbols.len())?;
+        writeln!(out,"   Num: Value             Size Type    Bind   Ndx Name")?;
+        for symbol in symbols {
+            writeln!(out,"{}",symbol)?;
+        }
+    }
+    Ok(Shown::text())
+}
+
+fn show_histogram(file:&ElfFile, options:&ViewOptions, out:&mut dyn Write) -> io::Result<Shown> {
+    let tables = elf::hash::parse_hash_tables(&mut file.cursor())?;
+    if options.json {
+        return Ok(Shown::json(tables.histograms().to_json()));
+    }
+    write!(out,"{}",tables)?;
+    Ok(Shown::text())
+}
+
+fn show_dynamic(file:&ElfFile, options:&ViewOptions, out:&mut dyn Write) -> io::Result<Shown> {
+    let entries = elf::dynamic::parse_dynamic(&mut file.cursor())?;
+    if options.json {
+        return Ok(Shown::json(entries.to_json()));
+    }
+    if entries.is_empty() {
+        writeln!(out,"There is no dynamic section in this file.")?;
+        return Ok(Shown::text());
+    }
+    writeln!(out,"Dynamic section contains {} entries:",entries.len())?;
+    writeln!(out,"  {:<18} {:<20} Name/Value","Tag","Type")?;
+    for entry in &entries {
+        writeln!(out,"{}",entry)?;
+    }
+    Ok(Shown::text())
+}
+
+/// 优先按注释节显示, 没有节表时按PT_NOTE段显示
+fn show_notes(file:&ElfFile, options:&ViewOptions, out:&mut dyn Write) -> io::Result<Shown> {
+    let mut sections = Vec::new();
+    for section in file.sections()?.iter().filter(|section| section.sh_type == elf::note::SHT_NOTE) {
+        sections.push((section.name.as_str(), elf::note::parse_notes(file.section_data(section)?, section.sh_addralign)?));
+    }
+    let mut groups:Vec<(&str, &[elf::note::ElfNote])> = sections.iter().map(|(name, notes)| (*name, notes.as_slice())).collect();
+    if groups.is_empty() && !file.notes()?.is_empty() {
+        groups.push(("PT_NOTE", file.notes()?));
+    }
+    if options.json {
+        return Ok(Shown::json(JsonValue::ARRAY(groups.iter().map(|(section, notes)| JsonValue::object([
+            ("section", section.to_json()),
+            ("notes", notes.to_json()),
+        ])).collect())));
+    }
+    for (section, notes) in groups {
+        writeln!(out)?;
+        writeln!(out,"Displaying notes found in: {}",section)?;
+        writeln!(out,"  Owner                Data size \tDescription")?;
+        for note in notes {
+            writeln!(out,"{}",note)?;
+        }
+    }
+    Ok(Shown::text())
+}
+
+fn show_hardening(file:&ElfFile, options:&ViewOptions, out:&mut dyn Write) -> io::Result<Shown> {
+    let report = hardening::check_hardening(&mut file.cursor())?;
+    if options.json {
+        return Ok(Shown::json(report.to_json()));
+    }
+    write!(out,"{}",report)?;
+    Ok(Shown::text())
+}
+
+/// 发现问题时文件计入失败汇总, 以非零状态退出
+fn show_check(file:&ElfFile, options:&ViewOptions, out:&mut dyn Write) -> io::Result<Shown> {
+    let report = check::check_file(&mut file.cursor())?;
+    let passed = report.passed();
+    if options.json {
+        return Ok(Shown { value:Some(report.to_json()), passed });
+    }
+    write!(out,"{}",report)?;
+    Ok(Shown { value:None, passed })
+}
+
+fn show_versions(file:&ElfFile, options:&ViewOptions, out:&mut dyn Write) -> io::Result<Shown> {
+    let versions = elf::version::parse_versions(&mut file.cursor())?;
+    if options.json {
+        return Ok(Shown::json(versions.to_json()));
+    }
+    write!(out,"{}",versions)?;
+    Ok(Shown::text())
+}
+
+fn show_core(file:&ElfFile, options:&ViewOptions, out:&mut dyn Write) -> io::Result<Shown> {
+    let core = elf::core::parse_core(&mut file.cursor())?;
+    if options.json {
+        return Ok(Shown::json(core.to_json()));
+    }
+    write!(out,"{}",core)?;
+    Ok(Shown::text())
+}
+
+fn show_debug_dump(file:&ElfFile, options:&ViewOptions, out:&mut dyn Write) -> io::Result<Shown> {
+    match options.debug_dump.as_str() {
+        "info" => {
+            writeln!(out,"Contents of the .debug_info section:")?;
+            writeln!(out)?;
+            for unit in file.units()? {
+                writeln!(out,"{}",unit)?;
+            }
+        }
+        "frames-interp" => {
+            match file.section_by_name(".eh_frame")? {
+                Some(section) => {
+                    let eh_frame = dwarf::frame::EhFrame::parse(file.section_data(section)?, section.sh_addr, file.header().e_machine)?;
+                    writeln!(out,"Contents of the .eh_frame section:")?;
+                    writeln!(out)?;
+                    write!(out,"{}",eh_frame)?;
+                    if let Some(section) = file.section_by_name(".eh_frame_hdr")? {
+                        let hdr = dwarf::frame::EhFrameHdr::parse(file.section_data(section)?, section.sh_addr)?;
+                        writeln!(out,"Contents of the .eh_frame_hdr section:")?;
+                        writeln!(out)?;
+                        writeln!(out,"{}",hdr)?;
+                        for problem in dwarf::frame::check_eh_frame_hdr(&hdr, &eh_frame) {
+                            writeln!(out,"  Warning: {}",problem)?;
+                        }
+                    }
+                }
+                None => writeln!(out,"There is no .eh_frame section in this file.")?,
+            }
+        }
+        _ => {
+            writeln!(out,"Contents of the .debug_line section:")?;
+            writeln!(out)?;
+            for program in file.line_programs()? {
+                writeln!(out,"{}",program)?;
+            }
+        }
+    }
+    Ok(Shown::text())
+}
+
+/// 按readelf -x的格式输出, 每行16个字节, 分为4组
+fn write_hex_dump(out:&mut dyn Write, address:u64, data:&[u8]) -> io::Result<()> {
+    for (i, line) in data.chunks(16).enumerate() {
+        write!(out,"  {:#010x} ",address.wrapping_add(i as u64 * 16))?;
+        for group in 0..4 {
+            for index in group * 4..group * 4 + 4 {
+                match line.get(index) {
+                    Some(byte) => write!(out,"{:02x}",byte)?,
+                    None => write!(out,"  ")?,
+                }
+            }
+            write!(out," ")?;
+        }
+        let text:String = line.iter().map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' }).collect();
+        writeln!(out,"{}",text)?;
+    }
+    Ok(())
+}
+
+fn show_dump_vaddr(file:&ElfFile, options:&ViewOptions, out:&mut dyn Write) -> io::Result<Shown> {
+    let (address, len) = options.dump_vaddr;
+    let data = file.read_vaddr(address, len)?;
+    if options.json {
+        let hex:String = data.iter().map(|byte| format!("{:02x}",byte)).collect();
+        return Ok(Shown::json(JsonValue::object([("address", address.to_json()), ("size", len.to_json()), ("data", hex.to_json())])));
+    }
+    writeln!(out,"Hex dump of virtual addresses {:#x}-{:#x}:",address,address + len)?;
+    write_hex_dump(out, address, &data)?;
+    Ok(Shown::text())
+}
+
+/// 解析0x开头的十六进制数或十进制数
+fn parse_number(text:&str) -> Option<u64> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(digits) => u64::from_str_radix(digits, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// 可以组合的视图, 按readelf的输出顺序排列; 名字同时是命令行参数名, JSON中的键把-换成_
+fn views() -> [(&'static str, View); 13] {
+    [
+        ("header", show_header),
+        ("sections", show_sections),
+        ("segments", show_segments),
+        ("dynamic", show_dynamic),
+        ("symbols", show_symbols),
+        ("histogram", show_histogram),
+        ("version-info", show_versions),
+        ("notes", show_notes),
+        ("hardening", show_hardening),
+        ("check", show_check),
+        ("core", show_core),
+        ("debug-dump", show_debug_dump),
+        ("dump-vaddr", show_dump_vaddr),
+    ]
+}
+
+/// -a和-e展开后的视图
+const ALL_VIEWS:[&str; 7] = ["header", "sections", "segments", "dynamic", "symbols", "version-info", "notes"];
+const HEADER_VIEWS:[&str; 3] = ["header", "sections", "segments"];
+
+/// 依次输出选中的视图, 文本格式下视图之间空一行; 返回JSON格式下各视图的值, 以及是否所有检查都通过
+fn show_views(file:&ElfFile, selected:&[&str], options:&ViewOptions, out:&mut dyn Write) -> io::Result<(Vec<(String, JsonValue)>, bool)> {
+    let mut values = Vec::new();
+    let mut passed = true;
+    let chosen = views().into_iter().filter(|(name, _)| selected.contains(name));
+    for (i, (name, view)) in chosen.enumerate() {
+        if i > 0 && !options.json && options.gnu.is_none() {
+            writeln!(out)?;
+        }
+        let shown = view(file, options, out)?;
+        if let Some(value) = shown.value {
+            values.push((name.replace('-', "_"), value));
+        }
+        passed &= shown.passed;
+    }
+    Ok((values, passed))
+}
+
+/// --check没有通过的文件在失败汇总中的说明
+const CHECK_FAILED:&str = "check found problems";
+
+/// 一个要处理的文件, explicit表示直接在命令行中给出, 不是ELF文件时作为错误而不是跳过
+struct Input {
+    path:PathBuf,
+    explicit:bool,
+}
+
+/// 展开命令行中的路径: 目录只在--recursive时按名字顺序递归展开;
+/// 扫描中遇到的符号链接只在--follow-symlinks时跟随, 同一个目标只处理一次
+fn collect_inputs(paths:&[&str], recursive:bool, follow:bool, report:bool, failures:&mut Vec<(String, String)>) -> Vec<Input> {
+    let mut inputs = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending:Vec<(PathBuf, bool)> = paths.iter().rev().map(|path| (PathBuf::from(path), true)).collect();
+    while let Some((path, explicit)) = pending.pop() {
+        let metadata = match explicit || follow {
+            true => std::fs::metadata(&path),
+            false => std::fs::symlink_metadata(&path),
+        };
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                failures.push((path.display().to_string(), e.to_string()));
+                continue;
+            }
+        };
+        if !visited.insert(std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone())) {
+            continue;
+        }
+        if metadata.is_dir() {
+            if !recursive {
+                failures.push((path.display().to_string(), String::from("is a directory, use --recursive to scan it")));
+                continue;
+            }
+            let entries = std::fs::read_dir(&path).and_then(|entries| entries.map(|entry| entry.map(|entry| entry.path())).collect::<io::Result<Vec<_>>>());
+            match entries {
+                Ok(mut entries) => {
+                    entries.sort();
+                    pending.extend(entries.into_iter().rev().map(|entry| (entry, false)));
+                }
+                Err(e) => failures.push((path.display().to_string(), e.to_string())),
+            }
+        } else if metadata.is_file() {
+            inputs.push(Input { path, explicit });
+        } else if report {
+            let kind = if metadata.file_type().is_symlink() { "symbolic link" } else { "not a regular file" };
+            eprintln!("{}: {}, skipped",path.display(),kind);
+        }
+    }
+    inputs
+}
+
+/// 一个文件的处理结果
+struct FileResult {
+    /// JSON格式下要输出的值
+    value:Option<JsonValue>,
+    /// 计入失败汇总中总数的文件数, 处理了成员的静态库按成员计, 跳过的非ELF文件不计
+    files:usize,
+    /// 出错后跳过的静态库成员和没有通过--check的文件(或成员)`(path, error)`
+    failures:Vec<(String, String)>,
+}
+
+impl FileResult {
+    fn new(value:Option<JsonValue>, files:usize) -> FileResult {
+        FileResult { value, files, failures:Vec::new() }
+    }
+}
+
+/// 对ELF文件直接输出各个视图, 对静态库先输出符号索引(-c)再依次输出每个成员, 某个成员出错时在err中报告并继续处理下一个成员;
+/// 扫描到的非ELF文件跳过, 提示信息写入err;
+/// JSON格式下的值为{"file", 视图...}, 静态库为{"file", "archive_index", "members": [{"name", 视图...}]}
+fn run_file(input:&Input, file_options:&FileOptions, out:&mut dyn Write, err:&mut dyn Write) -> io::Result<FileResult> {
+    let FileOptions { selected, archive_index, show_name, report, views:options } = file_options;
+    let path = input.path.display().to_string();
+    // 通过内存映射读取, 视图共用ElfFile中缓存的节表, 段表, 符号表和DWARF
+    let map = elf::mapped::MappedFile::open(&input.path)?;
+    let mut fields = vec![(String::from("file"), JsonValue::from(path.as_str()))];
+    if !elf::archive::is_archive(&mut map.cursor())? {
+        if !elf::header::is_elf(&mut map.cursor())? {
+            if input.explicit {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "not an ELF file or static archive"));
+            }
+            if *report {
+                writeln!(err,"{}: not an ELF file, skipped",path)?;
+            }
+            return Ok(FileResult::new(None, 0));
+        }
+        if *archive_index {
+            writeln!(err,"{}: not an archive, ignoring --archive-index",path)?;
+        }
+        if *show_name && !options.json {
+            writeln!(out)?;
+            writeln!(out,"File: {}",path)?;
+        }
+        let (values, passed) = show_views(&ElfFile::from_mapped(map)?, selected, options, out)?;
+        fields.extend(values);
+        let mut result = FileResult::new(options.json.then_some(JsonValue::OBJECT(fields)), 1);
+        if !passed {
+            result.failures.push((path, String::from(CHECK_FAILED)));
+        }
+        return Ok(result);
+    }
+    let mut file = map.cursor();
+    let archive = elf::archive::parse_archive(&mut file)?;
+    if *archive_index {
+        if options.json {
+            fields.push((String::from("archive_index"), archive.to_json()));
+        } else {
+            writeln!(out,"Archive: {}",path)?;
+            write!(out,"{}",archive)?;
+        }
+    }
+    if selected.is_empty() {
+        return Ok(FileResult::new(options.json.then_some(JsonValue::OBJECT(fields)), 1));
+    }
+    let dir = input.path.parent().unwrap_or(Path::new(""));
+    let mut members = Vec::new();
+    let mut failures = Vec::new();
+    for member in &archive.members {
+        if !options.json {
+            writeln!(out)?;
+            writeln!(out,"File: {}({})",path,member.name)?;
+        }
+        let mut contents = vec![(String::from("name"), member.name.to_json())];
+        let shown = archive.read_member(&mut file, dir, member)
+            .and_then(ElfFile::from_bytes)
+            .and_then(|member_file| show_views(&member_file, selected, options, out));
+        let name = format!("{}({})",path,member.name);
+        match shown {
+            Ok((values, passed)) => {
+                contents.extend(values);
+                if !passed {
+                    failures.push((name, String::from(CHECK_FAILED)));
+                }
+            }
+            Err(e) => {
+                writeln!(err,"{}: {}",name,e)?;
+                contents.push((String::from("error"), e.to_string().into()));
+                failures.push((name, e.to_string()));
+            }
+        }
+        members.push(JsonValue::OBJECT(contents));
+    }
+    fields.push((String::from("members"), JsonValue::ARRAY(members)));
+    let value = options.json.then_some(JsonValue::OBJECT(fields));
+    Ok(FileResult { value, files:archive.members.len().max(1), failures })
+}
+
+/// 用jobs个线程并行处理文件: 每个文件的输出和提示信息先写入缓冲区, 再按输入顺序交给done, 结果与串行处理相同;
+/// run与串行处理时相同(run_file), 每个文件的ElfFile在处理它的线程中构造, 解析结果的缓存不跨线程共享
+fn run_parallel<F, D>(inputs:&[Input], jobs:usize, run:F, mut done:D)
+where
+    F:Fn(&Input, &mut dyn Write, &mut dyn Write) -> io::Result<FileResult> + Sync,
+    D:FnMut(&Input, Vec<u8>, Vec<u8>, io::Result<FileResult>),
+{
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(inputs.len()) {
+            let sender = sender.clone();
+            let (next, run) = (&next, &run);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(input) = inputs.get(index) else { break };
+                let (mut output, mut messages) = (Vec::new(), Vec::new());
+                let result = run(input, &mut output, &mut messages);
+                if sender.send((index, output, messages, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+        // 先完成的文件暂存, 直到排在它前面的文件都已输出
+        let mut pending = BTreeMap::new();
+        let mut current = 0;
+        for (index, output, messages, result) in receiver {
+            pending.insert(index, (output, messages, result));
+            while let Some((output, messages, result)) = pending.remove(&current) {
+                done(&inputs[current], output, messages, result);
+                current += 1;
+            }
+        }
+    });
+}
+
+/// 由--sysroot和--library-path(默认取LD_LIBRARY_PATH)构造库搜索配置
+fn search_config(matches:&ArgMatches) -> deps::SearchConfig {
+    let library_path = match matches.value_of("library-path") {
+        Some(paths) => Some(paths.to_string()),
+        None => std::env::var("LD_LIBRARY_PATH").ok(),
+    };
+    deps::SearchConfig::new(matches.value_of("sysroot").map(PathBuf::from), library_path.as_deref())
+}
+
+/// 子命令出错时与处理多个文件时相同, 在stderr输出`path: error`并以1退出, 不panic
+fn or_exit<T>(path:&str, result:io::Result<T>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("{}: {}",path,e);
+        std::process::exit(1);
+    })
+}
+
+/// 打开两个文件并比较, 比较出错时两个文件名都输出
+fn compare<T>(matches:&ArgMatches, compare:impl FnOnce(&mut File, &mut File) -> io::Result<T>) -> T {
+    let (old_path, new_path) = (matches.value_of("old").unwrap(), matches.value_of("new").unwrap());
+    let mut old = or_exit(old_path, File::open(old_path));
+    let mut new = or_exit(new_path, File::open(new_path));
+    or_exit(&format!("{} and {}",old_path,new_path), compare(&mut old, &mut new))
+}
+
+fn run_deps(matches:&ArgMatches) {
+    let config = search_config(matches);
+    let path = matches.value_of("file").unwrap();
+    let tree = or_exit(path, deps::resolve_dependencies(Path::new(path), &config));
+    print!("{}",tree);
+    let problems = tree.problems();
+    if !problems.is_empty() {
+        println!();
+        println!("{} unresolved dependencies:",problems.len());
+        for dependency in problems {
+            println!("    {}",dependency.name);
+        }
+    }
+}
+
+/// 有未解析的符号或缺失的库时以非零状态退出, 便于在CI中使用
+fn run_check_symbols(matches:&ArgMatches) {
+    let config = search_config(matches);
+    let libraries:Vec<PathBuf> = matches.values_of("libraries").map(|values| values.map(PathBuf::from).collect()).unwrap_or_default();
+    let path = matches.value_of("file").unwrap();
+    let check = or_exit(path, unresolved::check_symbols(Path::new(path), &libraries, &config));
+    print!("{}",check);
+    if !check.passed() {
+        std::process::exit(1);
+    }
+}
+
+fn run_diff(matches:&ArgMatches) {
+    let diff = compare(matches, diff::diff_elf);
+    match matches.value_of("output") {
+        Some("json") => println!("{:#}",diff.to_json()),
+        _ => {
+            println!("--- {}",matches.value_of("old").unwrap());
+            println!("+++ {}",matches.value_of("new").unwrap());
+            print!("{}",diff);
+        }
+    }
+}
+
+/// 有破坏性变化时以非零状态退出
+fn run_abi(matches:&ArgMatches) {
+    let report = compare(matches, abi::check_abi);
+    match matches.value_of("output") {
+        Some("json") => println!("{:#}",report.to_json()),
+        _ => print!("{}",report),
+    }
+    if report.is_breaking() {
+        std::process::exit(1);
+    }
+}
+
+fn run_size(matches:&ArgMatches) {
+    let source = size::DataSource::from(matches.value_of("data-source").unwrap()).unwrap();
+    let limit = matches.value_of("limit").unwrap();
+    let limit = limit.parse::<usize>().unwrap_or_else(|_| {
+        eprintln!("Invalid number of rows {}",limit);
+        std::process::exit(2);
+    });
+    let report = |path:&str| -> size::SizeReport {
+        let mut elf = or_exit(path, File::open(path));
+        or_exit(path, size::size_report(&mut elf, &source))
+    };
+    let new = report(matches.value_of("file").unwrap());
+    match matches.value_of("diff") {
+        Some(base) => {
+            let mut diff = size::diff_sizes(&report(base), &new);
+            diff.truncate(limit);
+            print!("{}",diff);
+        }
+        None => {
+            let mut new = new;
+            new.truncate(limit);
+            print!("{}",new);
+        }
+    }
+}
+
+fn run_addr2line(matches:&ArgMatches) {
+    let path = matches.value_of("file").unwrap();
+    let mut elf = or_exit(path, File::open(path));
+    let symbolizer = or_exit(path, addr2line::Symbolizer::load(&mut elf));
+    let dynamic = or_exit(path, elf::dynamic::parse_dynamic(&mut elf));
+    let soname = elf::dynamic::dynamic_strings(&dynamic, elf::dynamic::DT_SONAME).first().copied();
+    let module = addr2line::ModuleNames::new(path, soname);
+    let relative = matches.is_present("relative");
+    let mut addresses = Vec::new();
+    if let Some(values) = matches.values_of("addresses") {
+        for value in values {
+            match addr2line::parse_address(value, &module) {
+                Some(address) => addresses.push(address),
+                None => eprintln!("Invalid address or address in another module: {}", value),
+            }
+        }
+    }
+    if let Some(path) = matches.value_of("addresses-file") {
+        let text = or_exit(path, std::fs::read_to_string(path));
+        addresses.extend(addr2line::extract_addresses(&text, &module));
+    }
+    for address in addresses {
+        match address {
+            AddressRef::ABSOLUTE(address) if !relative => print!("{}", symbolizer.symbolize(address)),
+            AddressRef::ABSOLUTE(offset) | AddressRef::RELATIVE(offset) => print!("{}", symbolizer.symbolize_relative(offset)),
+            AddressRef::SYMBOL(name, offset) => match symbolizer.symbol_address(&name) {
+                Some(address) => print!("{}", symbolizer.symbolize(address.wrapping_add(offset))),
+                None => eprintln!("Unknown symbol: {}", name),
+            },
+        }
+    }
+}
+
+fn run_locate(matches:&ArgMatches) {
+    let path = matches.value_of("file").unwrap();
+    let mut elf = or_exit(path, File::open(path));
+    let map = or_exit(path, address::AddressMap::load(&mut elf));
+    let offsets = matches.is_present("offset");
+    let module = addr2line::ModuleNames::new(path, None);
+    for value in matches.values_of("values").unwrap() {
+        match addr2line::parse_address(value, &module) {
+            Some(AddressRef::ABSOLUTE(value)) => {
+                println!("{:#x}:",value);
+                print!("{}",if offsets { map.locate_offset(value) } else { map.locate_vaddr(value) });
+            }
+            _ => eprintln!("Invalid address: {}", value),
+        }
+    }
 }
 
 fn main() {
@@ -16,42 +710,383 @@ fn main() {
         .author("TurtleRuss")
         .about("A simple readelf implementation")
         .args(&[
+            Arg::with_name("all")
+                .short('a')
+                .long("all")
+                .help("Equivalent to -h -s -l -d --symbols --version-info -n")
+                .required(false),
+            Arg::with_name("headers")
+                .short('e')
+                .long("headers")
+                .help("Equivalent to -h -s -l")
+                .required(false),
             Arg::with_name("header")
                 .short('h')
                 .long("header")
+                .alias("file-header")
                 .help("To read the elf header")
-                .required(false)
-                .takes_value(true),
+                .required(false),
             Arg::with_name("sections")
                 .short('s')
                 .long("sections")
+                .short_alias('S')
+                .alias("section-headers")
                 .help("To read the elf section table")
-                .required(false)
-                .takes_value(true),
+                .required(false),
             Arg::with_name("segments")
                 .short('l')
                 .long("segments")
+                .alias("program-headers")
                 .help("To read the elf program header table")
+                .required(false),
+            Arg::with_name("symbols")
+                .long("symbols")
+                .help("To read the .symtab and .dynsym symbol tables")
+                .required(false),
+            Arg::with_name("histogram")
+                .short('I')
+                .long("histogram")
+                .help("To show the bucket list length histogram of the .hash and .gnu.hash tables")
+                .required(false),
+            Arg::with_name("dynamic")
+                .short('d')
+                .long("dynamic")
+                .help("To read the dynamic section")
+                .required(false),
+            Arg::with_name("notes")
+                .short('n')
+                .long("notes")
+                .help("To read the notes")
+                .required(false),
+            Arg::with_name("hardening")
+                .long("hardening")
+                .help("To check RELRO, NX, PIE, stack canary, FORTIFY, RPATH, CET/BTI and stripping")
+                .required(false),
+            Arg::with_name("check")
+                .long("check")
+                .help("To report structural problems in the file, exiting non-zero if any are found")
+                .required(false),
+            Arg::with_name("archive-index")
+                .short('c')
+                .long("archive-index")
+                .help("To read the symbol index of a static archive")
+                .required(false),
+            Arg::with_name("version-info")
+                .long("version-info")
+                .help("To read the symbol version definitions and requirements")
+                .required(false),
+            Arg::with_name("core")
+                .long("core")
+                .help("To read the threads, signal, auxiliary vector and mapped files of a core dump")
+                .required(false),
+            Arg::with_name("debug-dump")
+                .long("debug-dump")
+                .help("To dump DWARF debug information (decodedline, info, frames-interp)")
+                .required(false)
+                .takes_value(true)
+                .possible_values(["decodedline", "info", "frames-interp"]),
+            Arg::with_name("dump-vaddr")
+                .long("dump-vaddr")
+                .help("To dump LEN bytes at virtual address ADDR as the loader maps them, zero-filling .bss (ADDR:LEN)")
+                .required(false)
+                .takes_value(true)
+                .value_name("ADDR:LEN"),
+            Arg::with_name("output")
+                .long("output")
+                .help("Output format of the views, gnu matches the layout of binutils readelf -h/-S/-l")
                 .required(false)
-                .takes_value(true),
-        ]).get_matches();
-    if matches.is_present("header") {
-        let mut elf = File::open(matches.value_of("header").unwrap()).expect("Failed to open file");
-        let header = elf::header::parse_header(&mut elf).unwrap();
-        println!("{}",header);
-    }else if matches.is_present("sections") {
-        let mut elf = File::open(matches.value_of("sections").unwrap()).expect("Failed to open file");
-        elf::section::parse_sections(&mut elf).
-            expect("Failed to parse sections")
-            .iter()
-            .for_each(|section| println!("{}",section));
-    }else if matches.is_present("segments") {
-        let mut elf = File::open(matches.value_of("segments").unwrap()).expect("Failed to open file");
-        elf::segment::parse_segments(&mut elf).
-            expect("Failed to parse segments").
-            iter().
-            for_each(|segment| println!("{}",segment));
-    }else {
+                .takes_value(true)
+                .possible_values(["text", "json", "gnu"])
+                .default_value("text"),
+            Arg::with_name("wide")
+                .short('W')
+                .long("wide")
+                .help("Allow --output gnu lines to exceed 80 characters, like readelf -W")
+                .required(false),
+            Arg::with_name("recursive")
+                .short('r')
+                .long("recursive")
+                .help("Scan directories recursively")
+                .required(false),
+            Arg::with_name("follow-symlinks")
+                .short('L')
+                .long("follow-symlinks")
+                .help("Follow symbolic links found while scanning directories")
+                .required(false),
+            Arg::with_name("report-skipped")
+                .long("report-skipped")
+                .help("Report non-ELF files and other entries skipped while scanning directories")
+                .required(false),
+            Arg::with_name("jobs")
+                .short('j')
+                .long("jobs")
+                .help("Number of files to parse concurrently, 0 for one per CPU")
+                .takes_value(true)
+                .required(false),
+            Arg::with_name("files")
+                .help("The elf files, static archives or directories to read")
+                .required(false)
+                .multiple_values(true)
+                .index(1),
+        ])
+        .subcommand(App::new("deps")
+            .about("Resolve the shared library dependency tree without running the file")
+            .args(&[
+                Arg::with_name("sysroot")
+                    .long("sysroot")
+                    .help("Resolve absolute paths, ld.so.conf and the interpreter inside this directory")
+                    .takes_value(true),
+                Arg::with_name("library-path")
+                    .long("library-path")
+                    .help("Colon-separated directories to use instead of LD_LIBRARY_PATH")
+                    .takes_value(true),
+                Arg::with_name("file")
+                    .help("The elf file to read")
+                    .required(true)
+                    .index(1),
+            ]))
+        .subcommand(App::new("check-symbols")
+            .about("Report undefined dynamic symbols that no dependency exports at the required version")
+            .args(&[
+                Arg::with_name("sysroot")
+                    .long("sysroot")
+                    .help("Resolve absolute paths, ld.so.conf and the interpreter inside this directory")
+                    .takes_value(true),
+                Arg::with_name("library-path")
+                    .long("library-path")
+                    .help("Colon-separated directories to use instead of LD_LIBRARY_PATH")
+                    .takes_value(true),
+                Arg::with_name("file")
+                    .help("The executable or shared object to check")
+                    .required(true)
+                    .index(1),
+                Arg::with_name("libraries")
+                    .help("Additional shared libraries to put in the lookup scope")
+                    .multiple_values(true)
+                    .index(2),
+            ]))
+        .subcommand(App::new("diff")
+            .about("Compare the structure of two elf files")
+            .args(&[
+                Arg::with_name("output")
+                    .long("output")
+                    .help("Output format")
+                    .takes_value(true)
+                    .possible_values(["text", "json"])
+                    .default_value("text"),
+                Arg::with_name("old")
+                    .help("The original elf file")
+                    .required(true)
+                    .index(1),
+                Arg::with_name("new")
+                    .help("The elf file to compare against")
+                    .required(true)
+                    .index(2),
+            ]))
+        .subcommand(App::new("abi")
+            .about("Check the ABI compatibility of two versions of a shared library")
+            .args(&[
+                Arg::with_name("output")
+                    .long("output")
+                    .help("Output format")
+                    .takes_value(true)
+                    .possible_values(["text", "json"])
+                    .default_value("text"),
+                Arg::with_name("old")
+                    .help("The previously released library")
+                    .required(true)
+                    .index(1),
+                Arg::with_name("new")
+                    .help("The new library")
+                    .required(true)
+                    .index(2),
+            ]))
+        .subcommand(App::new("size")
+            .about("Attribute file size and VM size to segments, sections, symbols or compile units")
+            .args(&[
+                Arg::with_name("data-source")
+                    .short('d')
+                    .long("data-source")
+                    .help("What to attribute the size to")
+                    .takes_value(true)
+                    .possible_values(["segments", "sections", "symbols", "compileunits"])
+                    .default_value("sections"),
+                Arg::with_name("limit")
+                    .short('n')
+                    .help("Show only the largest N rows, 0 for all")
+                    .takes_value(true)
+                    .default_value("20"),
+                Arg::with_name("diff")
+                    .long("diff")
+                    .help("Show the size changes relative to this file")
+                    .takes_value(true),
+                Arg::with_name("file")
+                    .help("The elf file to read")
+                    .required(true)
+                    .index(1),
+            ]))
+        .subcommand(App::new("locate")
+            .about("Map virtual addresses or file offsets to the other coordinate, segment, section and symbol")
+            .args(&[
+                Arg::with_name("offset")
+                    .short('o')
+                    .long("offset")
+                    .help("Treat the values as file offsets instead of virtual addresses"),
+                Arg::with_name("file")
+                    .help("The elf file to read")
+                    .required(true)
+                    .index(1),
+                Arg::with_name("values")
+                    .help("Virtual addresses or file offsets (0x1234 or 1234, hexadecimal)")
+                    .required(true)
+                    .multiple_values(true)
+                    .index(2),
+            ]))
+        .subcommand(App::new("addr2line")
+            .about("Translate addresses into function names, inline chains and source lines")
+            .args(&[
+                Arg::with_name("relative")
+                    .short('r')
+                    .long("relative")
+                    .help("Treat addresses as offsets from the module load base (PIE/shared objects)"),
+                Arg::with_name("addresses-file")
+                    .long("addresses-file")
+                    .help("Read addresses from a file such as a crash log")
+                    .takes_value(true),
+                Arg::with_name("file")
+                    .help("The elf file to read")
+                    .required(true)
+                    .index(1),
+                Arg::with_name("addresses")
+                    .help("Addresses to translate (0x1234, or module+0x1234 for module-relative offsets)")
+                    .multiple_values(true)
+                    .index(2),
+            ]))
+        .get_matches();
+    if let Some(matches) = matches.subcommand_matches("deps") {
+        run_deps(matches);
+        return;
+    }
+    if let Some(matches) = matches.subcommand_matches("check-symbols") {
+        run_check_symbols(matches);
+        return;
+    }
+    if let Some(matches) = matches.subcommand_matches("diff") {
+        run_diff(matches);
+        return;
+    }
+    if let Some(matches) = matches.subcommand_matches("abi") {
+        run_abi(matches);
+        return;
+    }
+    if let Some(matches) = matches.subcommand_matches("size") {
+        run_size(matches);
+        return;
+    }
+    if let Some(matches) = matches.subcommand_matches("locate") {
+        run_locate(matches);
+        return;
+    }
+    if let Some(matches) = matches.subcommand_matches("addr2line") {
+        run_addr2line(matches);
+        return;
+    }
+    let files:Vec<&str> = matches.values_of("files").map(|values| values.collect()).unwrap_or_default();
+    let selected:Vec<&str> = views().iter()
+        .map(|(name, _)| *name)
+        .filter(|name| matches.is_present(name)
+            || (matches.is_present("all") && ALL_VIEWS.contains(name))
+            || (matches.is_present("headers") && HEADER_VIEWS.contains(name)))
+        .collect();
+    let archive_index = matches.is_present("archive-index");
+    if files.is_empty() || (selected.is_empty() && !archive_index) {
         usage();
+        return;
+    }
+    let json = matches.value_of("output") == Some("json");
+    if json && selected.contains(&"debug-dump") {
+        eprintln!("--output json is not supported for --debug-dump");
+        std::process::exit(2);
+    }
+    let dump_vaddr = match matches.value_of("dump-vaddr") {
+        Some(range) => match range.split_once(':').and_then(|(address, len)| parse_number(address).zip(parse_number(len))) {
+            Some((address, len)) if address.checked_add(len).is_some() => (address, len),
+            _ => {
+                eprintln!("Invalid --dump-vaddr range {}, expected ADDR:LEN such as 0x4010:16",range);
+                std::process::exit(2);
+            }
+        },
+        None => (0, 0),
+    };
+    let jobs = match matches.value_of("jobs") {
+        Some("0") => std::thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+        Some(jobs) => jobs.parse::<usize>().unwrap_or_else(|_| {
+            eprintln!("Invalid number of jobs {}",jobs);
+            std::process::exit(2);
+        }),
+        None => 1,
+    };
+    let recursive = matches.is_present("recursive");
+    let report = matches.is_present("report-skipped");
+    let mut failures = Vec::new();
+    let inputs = collect_inputs(&files, recursive, matches.is_present("follow-symlinks"), report, &mut failures);
+    let mut total = failures.len();
+    let gnu = (matches.value_of("output") == Some("gnu")).then(|| gnu::GnuOptions {
+        wide:matches.is_present("wide"),
+        after_header:selected.contains(&"header"),
+    });
+    let file_options = FileOptions {
+        show_name:recursive || inputs.len() > 1,
+        selected,
+        archive_index,
+        report,
+        views:ViewOptions { json, gnu, debug_dump:matches.value_of("debug-dump").unwrap_or_default().to_string(), dump_vaddr },
+    };
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut values = Vec::new();
+    let mut finish = |input:&Input, result:io::Result<FileResult>| match result {
+        Ok(result) => {
+            values.extend(result.value);
+            total += result.files;
+            failures.extend(result.failures);
+        }
+        Err(e) => {
+            let path = input.path.display().to_string();
+            if json {
+                values.push(JsonValue::object([("file", path.as_str().into()), ("error", e.to_string().into())]));
+            }
+            total += 1;
+            failures.push((path, e.to_string()));
+        }
+    };
+    let run = |input:&Input, output:&mut dyn Write, messages:&mut dyn Write| run_file(input, &file_options, output, messages);
+    if jobs <= 1 {
+        for input in &inputs {
+            finish(input, run(input, &mut out, &mut io::stderr()));
+        }
+    } else {
+        run_parallel(&inputs, jobs, run, |input, output, messages, result| {
+            out.write_all(&output).expect("Failed to write output");
+            io::stderr().write_all(&messages).expect("Failed to write output");
+            finish(input, result);
+        });
+    }
+    let show_name = file_options.show_name;
+    if json {
+        match show_name {
+            false if values.len() == 1 => writeln!(out,"{:#}",values.remove(0)),
+            _ => writeln!(out,"{:#}",JsonValue::ARRAY(values)),
+        }.expect("Failed to write output");
+    }
+    out.flush().expect("Failed to write output");
+    // 所有文件处理完后汇总失败的文件, 以非零状态退出
+    if !failures.is_empty() {
+        eprintln!();
+        eprintln!("{} of {} files failed:",failures.len(),total);
+        for (path, error) in &failures {
+            eprintln!("    {}: {}",path,error);
+        }
+        std::process::exit(1);
     }
 }
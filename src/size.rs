@@ -0,0 +1,384 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Formatter;
+use std::io;
+use std::io::{Read, Seek};
+use crate::dwarf::info::parse_units;
+use crate::dwarf::ranges::die_ranges;
+use crate::dwarf::sections::DwarfSections;
+use crate::elf::header::{parse_header, ElfHeader};
+use crate::elf::section::{parse_sections, ElfSection, SHF_ALLOC, SHT_NOBITS};
+use crate::elf::segment::{parse_segments, ElfSegment, SegmentType};
+use crate::elf::symbol::{parse_dynamic_symbols, parse_symbols, STT_FILE, STT_SECTION};
+
+const SHF_EXECINSTR:u64 = 0x4;
+
+/// 按什么单位统计大小
+#[allow(non_camel_case_types)]
+pub enum DataSource {
+    SEGMENTS,
+    SECTIONS,
+    SYMBOLS,
+    COMPILE_UNITS,
+}
+
+impl DataSource {
+    pub fn from(name:&str) -> Option<Self> {
+        match name {
+            "segments" => Some(DataSource::SEGMENTS),
+            "sections" => Some(DataSource::SECTIONS),
+            "symbols" => Some(DataSource::SYMBOLS),
+            "compileunits" => Some(DataSource::COMPILE_UNITS),
+            _ => None,
+        }
+    }
+
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        match self {
+            DataSource::SEGMENTS => String::from("segments"),
+            DataSource::SECTIONS => String::from("sections"),
+            DataSource::SYMBOLS => String::from("symbols"),
+            DataSource::COMPILE_UNITS => String::from("compileunits"),
+        }
+    }
+}
+
+/// 一项占用的文件大小和加载到内存后的大小
+pub struct SizeEntry {
+    pub name:String,
+    pub file_size:u64,
+    pub vm_size:u64,
+}
+
+/// 所有项的大小之和等于文件大小和所有PT_LOAD段的内存大小
+pub struct SizeReport {
+    pub entries:Vec<SizeEntry>,
+    pub file_total:u64,
+    pub vm_total:u64,
+}
+
+/// 一项在两个文件中的大小, 只在一边出现时另一边为None
+pub struct SizeDelta {
+    pub name:String,
+    pub old:Option<(u64, u64)>,
+    pub new:Option<(u64, u64)>,
+}
+
+pub struct SizeDiff {
+    pub entries:Vec<SizeDelta>,
+    pub old_total:(u64, u64),
+    pub new_total:(u64, u64),
+}
+
+struct Layout {
+    header:ElfHeader,
+    sections:Vec<ElfSection>,
+    segments:Vec<ElfSegment>,
+    file_total:u64,
+}
+
+impl Layout {
+    fn loads(&self) -> impl Iterator<Item = &ElfSegment> {
+        self.segments.iter().filter(|segment| matches!(segment.p_type, SegmentType::LOAD))
+    }
+
+    /// 文件中[offset, offset + size)是否被某个PT_LOAD段加载
+    fn is_mapped(&self, offset:u64, size:u64) -> bool {
+        self.loads().any(|segment| offset >= segment.p_offset && offset.saturating_add(size) <= segment.p_offset.saturating_add(segment.p_filesz))
+    }
+
+    /// 没有PT_LOAD段的文件(如.o)以所有SHF_ALLOC节的大小之和作为内存大小
+    fn vm_total(&self) -> u64 {
+        if self.loads().next().is_some() {
+            self.loads().map(|segment| segment.p_memsz).fold(0, u64::saturating_add)
+        } else {
+            self.sections.iter().filter(|section| section.sh_flags & SHF_ALLOC != 0).map(|section| section.sh_size).fold(0, u64::saturating_add)
+        }
+    }
+
+    fn section_sizes(section:&ElfSection) -> (u64, u64) {
+        let file_size = if section.sh_type == SHT_NOBITS { 0 } else { section.sh_size };
+        let vm_size = if section.sh_flags & SHF_ALLOC != 0 { section.sh_size } else { 0 };
+        (file_size, vm_size)
+    }
+
+    /// 文件头, 段表和节表本身
+    fn header_entries(&self) -> Vec<SizeEntry> {
+        let header = &self.header;
+        let mut entries = Vec::new();
+        for (name, offset, size) in [
+            ("[ELF Header]", 0, header.e_ehsize as u64),
+            ("[Program Headers]", header.e_phoff, header.e_phnum as u64 * header.e_phentsize as u64),
+            ("[Section Headers]", header.e_shoff, header.e_shnum as u64 * header.e_shentsize as u64),
+        ] {
+            if size > 0 {
+                let vm_size = if self.is_mapped(offset, size) { size } else { 0 };
+                entries.push(SizeEntry { name:String::from(name), file_size:size, vm_size });
+            }
+        }
+        entries
+    }
+
+    fn section_entries(&self) -> Vec<SizeEntry> {
+        let mut entries = self.header_entries();
+        for section in self.sections.iter().filter(|section| section.index != 0) {
+            let (file_size, vm_size) = Self::section_sizes(section);
+            entries.push(SizeEntry { name:section.name.clone(), file_size, vm_size });
+        }
+        entries
+    }
+
+    fn segment_entries(&self) -> Vec<SizeEntry> {
+        self.loads().enumerate()
+            .map(|(i, segment)| SizeEntry {
+                name:format!("LOAD #{} [{}]",i,segment.p_flags.to_string()),
+                file_size:segment.p_filesz,
+                vm_size:segment.p_memsz,
+            })
+            .collect()
+    }
+
+    /// 把节的大小分给其中的各部分(节序号, 名字, 大小), 重叠的部分只计一次, 剩余的记为[section 节名]
+    fn refine(&self, pieces:Vec<(usize, String, u64)>) -> Vec<SizeEntry> {
+        let mut remaining:Vec<u64> = self.sections.iter().map(|section| section.sh_size).collect();
+        let mut entries = self.header_entries();
+        for (index, name, size) in pieces {
+            let Some(section) = self.sections.get(index) else { continue };
+            let size = size.min(remaining[index]);
+            if size == 0 {
+                continue;
+            }
+            remaining[index] -= size;
+            let (file_size, vm_size) = Self::section_sizes(section);
+            entries.push(SizeEntry {
+                name,
+                file_size:if file_size > 0 { size } else { 0 },
+                vm_size:if vm_size > 0 { size } else { 0 },
+            });
+        }
+        for section in self.sections.iter().filter(|section| section.index != 0) {
+            let size = remaining[section.index as usize];
+            if size > 0 {
+                let (file_size, vm_size) = Self::section_sizes(section);
+                entries.push(SizeEntry {
+                    name:format!("[section {}]",section.name),
+                    file_size:if file_size > 0 { size } else { 0 },
+                    vm_size:if vm_size > 0 { size } else { 0 },
+                });
+            }
+        }
+        entries
+    }
+}
+
+/// 有大小的已定义符号, 同一地址的别名只计一次, 同名的静态符号合并
+fn symbol_pieces<R:Read + Seek>(elf:&mut R, layout:&Layout) -> Vec<(usize, String, u64)> {
+    let mut symbols = parse_symbols(elf).unwrap_or_default();
+    if symbols.is_empty() {
+        symbols = parse_dynamic_symbols(elf).unwrap_or_default();
+    }
+    let mut seen = HashSet::new();
+    symbols.iter()
+        .filter(|symbol| symbol.st_size > 0 && !symbol.is_undefined() && !matches!(symbol.symbol_type(), STT_SECTION | STT_FILE))
+        .filter(|symbol| (symbol.st_shndx as usize) < layout.sections.len())
+        .filter(|symbol| seen.insert((symbol.st_shndx, symbol.st_value)))
+        .map(|symbol| (symbol.st_shndx as usize, symbol.name.clone(), symbol.st_size))
+        .collect()
+}
+
+/// 编译单元覆盖的代码地址范围与可执行节的交集
+fn compile_unit_pieces<R:Read + Seek>(elf:&mut R, layout:&Layout) -> io::Result<Vec<(usize, String, u64)>> {
+    let sections = DwarfSections::load(elf)?;
+    let units = parse_units(&sections)?;
+    let code:Vec<&ElfSection> = layout.sections.iter()
+        .filter(|section| section.sh_flags & SHF_ALLOC != 0 && section.sh_flags & SHF_EXECINSTR != 0)
+        .collect();
+    let mut pieces = Vec::new();
+    for unit in &units {
+        let Some(root) = &unit.root else { continue };
+        let name = unit.name().unwrap_or("[unnamed compile unit]").to_string();
+        for (begin, end) in die_ranges(unit, root, &sections).unwrap_or_default() {
+            for section in &code {
+                let start = begin.max(section.sh_addr);
+                let stop = end.min(section.sh_addr.saturating_add(section.sh_size));
+                if start < stop {
+                    pieces.push((section.index as usize, name.clone(), stop - start));
+                }
+            }
+        }
+    }
+    Ok(pieces)
+}
+
+/// 合并同名的项, 把没有归属的文件和内存大小记为[Unmapped]和[Padding], 按大小降序排列
+fn finish(entries:Vec<SizeEntry>, file_total:u64, vm_total:u64) -> SizeReport {
+    let mut merged:Vec<SizeEntry> = Vec::new();
+    let mut positions:HashMap<String, usize> = HashMap::new();
+    for entry in entries {
+        match positions.get(&entry.name) {
+            Some(&i) => {
+                merged[i].file_size = merged[i].file_size.saturating_add(entry.file_size);
+                merged[i].vm_size = merged[i].vm_size.saturating_add(entry.vm_size);
+            }
+            None => {
+                positions.insert(entry.name.clone(), merged.len());
+                merged.push(entry);
+            }
+        }
+    }
+    let file_used = merged.iter().map(|entry| entry.file_size).fold(0, u64::saturating_add);
+    let vm_used = merged.iter().map(|entry| entry.vm_size).fold(0, u64::saturating_add);
+    if file_total > file_used {
+        merged.push(SizeEntry { name:String::from("[Unmapped]"), file_size:file_total - file_used, vm_size:0 });
+    }
+    if vm_total > vm_used {
+        merged.push(SizeEntry { name:String::from("[Padding]"), file_size:0, vm_size:vm_total - vm_used });
+    }
+    merged.sort_by(|a, b| b.file_size.max(b.vm_size).cmp(&a.file_size.max(a.vm_size)).then_with(|| a.name.cmp(&b.name)));
+    SizeReport {
+        file_total:file_total.max(file_used),
+        vm_total:vm_total.max(vm_used),
+        entries:merged,
+    }
+}
+
+/// 把文件大小和内存大小分配到段, 节, 符号或编译单元
+pub fn size_report<R:Read + Seek>(elf:&mut R, source:&DataSource) -> io::Result<SizeReport> {
+    let header = parse_header(elf)?;
+    let layout = Layout {
+        sections:parse_sections(elf).unwrap_or_default(),
+        segments:parse_segments(elf)?,
+        file_total:elf.seek(io::SeekFrom::End(0))?,
+        header,
+    };
+    let entries = match source {
+        DataSource::SEGMENTS => layout.segment_entries(),
+        DataSource::SECTIONS => layout.section_entries(),
+        DataSource::SYMBOLS => layout.refine(symbol_pieces(elf, &layout)),
+        DataSource::COMPILE_UNITS => layout.refine(compile_unit_pieces(elf, &layout)?),
+    };
+    Ok(finish(entries, layout.file_total, layout.vm_total()))
+}
+
+impl SizeReport {
+    /// 只保留前limit项, 其余合并为一项, limit为0时保留全部
+    pub fn truncate(&mut self, limit:usize) {
+        if limit == 0 || self.entries.len() <= limit {
+            return;
+        }
+        let others:Vec<SizeEntry> = self.entries.split_off(limit);
+        self.entries.push(SizeEntry {
+            name:format!("[{} Others]",others.len()),
+            file_size:others.iter().map(|entry| entry.file_size).fold(0, u64::saturating_add),
+            vm_size:others.iter().map(|entry| entry.vm_size).fold(0, u64::saturating_add),
+        });
+    }
+}
+
+/// 比较两个文件中同名项的大小, 按变化量的绝对值降序排列, 没有变化的项不列出
+pub fn diff_sizes(old:&SizeReport, new:&SizeReport) -> SizeDiff {
+    // finish已经按名字合并过同名的项, 这里按名字建立索引
+    let old_sizes:HashMap<&str, (u64, u64)> = old.entries.iter().map(|entry| (entry.name.as_str(), (entry.file_size, entry.vm_size))).collect();
+    let new_names:HashSet<&str> = new.entries.iter().map(|entry| entry.name.as_str()).collect();
+    let mut entries:Vec<SizeDelta> = Vec::new();
+    for entry in &new.entries {
+        let before = old_sizes.get(entry.name.as_str()).copied();
+        entries.push(SizeDelta { name:entry.name.clone(), old:before, new:Some((entry.file_size, entry.vm_size)) });
+    }
+    for entry in old.entries.iter().filter(|entry| !new_names.contains(entry.name.as_str())) {
+        entries.push(SizeDelta { name:entry.name.clone(), old:Some((entry.file_size, entry.vm_size)), new:None });
+    }
+    entries.retain(|entry| entry.file_delta() != 0 || entry.vm_delta() != 0);
+    entries.sort_by(|a, b| {
+        let key = |entry:&SizeDelta| entry.file_delta().unsigned_abs().max(entry.vm_delta().unsigned_abs());
+        key(b).cmp(&key(a)).then_with(|| a.name.cmp(&b.name))
+    });
+    SizeDiff { entries, old_total:(old.file_total, old.vm_total), new_total:(new.file_total, new.vm_total) }
+}
+
+impl SizeDelta {
+    pub fn file_delta(&self) -> i64 {
+        self.new.map_or(0, |(file, _)| file as i64).saturating_sub(self.old.map_or(0, |(file, _)| file as i64))
+    }
+
+    pub fn vm_delta(&self) -> i64 {
+        self.new.map_or(0, |(_, vm)| vm as i64).saturating_sub(self.old.map_or(0, |(_, vm)| vm as i64))
+    }
+}
+
+impl SizeDiff {
+    /// 只保留前limit项, 其余合并为一项, limit为0时保留全部
+    pub fn truncate(&mut self, limit:usize) {
+        if limit == 0 || self.entries.len() <= limit {
+            return;
+        }
+        let others:Vec<SizeDelta> = self.entries.split_off(limit);
+        let sum = |side:fn(&SizeDelta) -> Option<(u64, u64)>| -> (u64, u64) {
+            others.iter().filter_map(side).fold((0, 0), |(file, vm), (f, v)| (file.saturating_add(f), vm.saturating_add(v)))
+        };
+        self.entries.push(SizeDelta { name:format!("[{} Others]",others.len()), old:Some(sum(|entry| entry.old)), new:Some(sum(|entry| entry.new)) });
+    }
+}
+
+/// 1024进制的简短大小, 如512, 12.3Ki, 1.20Mi
+fn human_size(size:u64) -> String {
+    const UNITS:[&str;3] = ["Ki", "Mi", "Gi"];
+    if size < 1024 {
+        return size.to_string();
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if value < 10.0 {
+        format!("{:.2}{}",value,UNITS[unit])
+    } else {
+        format!("{:.1}{}",value,UNITS[unit])
+    }
+}
+
+fn human_delta(delta:i64) -> String {
+    format!("{}{}",if delta < 0 { "-" } else { "+" },human_size(delta.unsigned_abs()))
+}
+
+fn percent(part:u64, total:u64) -> f64 {
+    if total == 0 { 0.0 } else { part as f64 * 100.0 / total as f64 }
+}
+
+/// 相对旧值的变化百分比, 新增或删除的项没有百分比
+fn delta_percent(old:Option<u64>, removed:bool, delta:i64) -> String {
+    match old {
+        _ if delta == 0 => String::from("[ = ]"),
+        _ if removed => String::from("[DEL]"),
+        None | Some(0) => String::from("[NEW]"),
+        Some(old) => format!("{:+.1}%",delta as f64 * 100.0 / old as f64),
+    }
+}
+
+impl std::fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f,"    FILE SIZE        VM SIZE")?;
+        writeln!(f," --------------  --------------")?;
+        for entry in &self.entries {
+            writeln!(f," {:>5.1}% {:>7}  {:>5.1}% {:>7}    {}",percent(entry.file_size, self.file_total),human_size(entry.file_size),percent(entry.vm_size, self.vm_total),human_size(entry.vm_size),entry.name)?;
+        }
+        writeln!(f," {:>5.1}% {:>7}  {:>5.1}% {:>7}    TOTAL",100.0,human_size(self.file_total),100.0,human_size(self.vm_total))
+    }
+}
+
+impl std::fmt::Display for SizeDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f,"    FILE SIZE        VM SIZE")?;
+        writeln!(f," --------------  --------------")?;
+        for entry in &self.entries {
+            let (file_delta, vm_delta) = (entry.file_delta(), entry.vm_delta());
+            let removed = entry.new.is_none();
+            writeln!(f," {:>6} {:>7}  {:>6} {:>7}    {}",delta_percent(entry.old.map(|(file, _)| file), removed, file_delta),human_delta(file_delta),delta_percent(entry.old.map(|(_, vm)| vm), removed, vm_delta),human_delta(vm_delta),entry.name)?;
+        }
+        let file_delta = (self.new_total.0 as i64).saturating_sub(self.old_total.0 as i64);
+        let vm_delta = (self.new_total.1 as i64).saturating_sub(self.old_total.1 as i64);
+        writeln!(f," {:>6} {:>7}  {:>6} {:>7}    TOTAL",delta_percent(Some(self.old_total.0), false, file_delta),human_delta(file_delta),delta_percent(Some(self.old_total.1), false, vm_delta),human_delta(vm_delta))
+    }
+}
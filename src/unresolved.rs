@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::fmt::Formatter;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::deps::{resolve_dependencies, Resolution, SearchConfig};
+use crate::elf::symbol::*;
+use crate::elf::version::{parse_versions, SymbolVersion};
+
+/// 一个对象导出的符号版本
+struct Export {
+    version:Option<String>,
+    hidden:bool,
+}
+
+/// 参与符号查找的一个对象
+struct ScopeObject {
+    path:PathBuf,
+    symbols:Vec<ElfSymbol>,
+    versions:Vec<Option<SymbolVersion>>,
+    /// 对象没有.gnu.version_d, 其符号可以满足任何版本的引用
+    unversioned:bool,
+}
+
+/// 没有任何对象导出的未定义符号
+pub struct UnresolvedSymbol {
+    /// 引用该符号的对象
+    pub object:PathBuf,
+    pub name:String,
+    pub version:Option<String>,
+    /// 版本需求中记录的提供该版本的库
+    pub file:Option<String>,
+    /// 符号存在但版本不满足时, 实际导出的版本
+    pub available:Vec<String>,
+}
+
+pub struct SymbolCheck {
+    /// 全局查找范围, 依次为可执行文件, 显式给出的库, 依赖树中的库和动态链接器
+    pub objects:Vec<PathBuf>,
+    /// 找不到或类型不匹配的依赖, 它们的符号无法检查
+    pub missing:Vec<String>,
+    pub unresolved:Vec<UnresolvedSymbol>,
+}
+
+impl SymbolCheck {
+    pub fn passed(&self) -> bool {
+        self.missing.is_empty() && self.unresolved.is_empty()
+    }
+}
+
+fn load_object(path:&Path) -> io::Result<ScopeObject> {
+    let mut file = File::open(path)?;
+    let symbols = parse_dynamic_symbols(&mut file)?;
+    let info = parse_versions(&mut file)?;
+    let versions = symbols.iter().map(|symbol| info.symbol_version(symbol.index)).collect();
+    Ok(ScopeObject { path:path.to_path_buf(), symbols, versions, unversioned:info.definitions.is_empty() })
+}
+
+/// 版本化的引用只能绑定同名版本(包括非默认版本), 或者没有版本信息的对象中的定义;
+/// 不带版本的引用只能绑定默认版本
+fn satisfies(export:&Export, unversioned:bool, required:Option<&str>) -> bool {
+    match required {
+        Some(required) => export.version.as_deref() == Some(required) || (unversioned && export.version.is_none()),
+        None => !export.hidden,
+    }
+}
+
+/// 检查path及其依赖中的每个未定义动态符号是否有对象按所需版本导出,
+/// libraries是显式给出的额外库, 排在依赖树中的库之前
+pub fn check_symbols(path:&Path, libraries:&[PathBuf], config:&SearchConfig) -> io::Result<SymbolCheck> {
+    let tree = resolve_dependencies(path, config)?;
+    let mut paths = vec![path.to_path_buf()];
+    paths.extend(libraries.iter().cloned());
+    paths.extend(tree.libraries().into_iter().map(Path::to_path_buf));
+    if let Some((_, Resolution::FOUND(interpreter))) = &tree.interpreter {
+        paths.push(interpreter.clone());
+    }
+    let mut seen = Vec::new();
+    paths.retain(|path| {
+        let key = std::fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+        !seen.contains(&key) && { seen.push(key); true }
+    });
+
+    let scope = paths.iter().map(|path| load_object(path)).collect::<io::Result<Vec<_>>>()?;
+    let mut exports:HashMap<&str, Vec<(Export, bool)>> = HashMap::new();
+    for object in &scope {
+        for (symbol, version) in object.symbols.iter().zip(&object.versions) {
+            if !symbol.is_exported() {
+                continue;
+            }
+            let export = Export {
+                version:version.as_ref().map(|version| version.name.clone()),
+                hidden:version.as_ref().is_some_and(|version| version.hidden),
+            };
+            exports.entry(symbol.name.as_str()).or_default().push((export, object.unversioned));
+        }
+    }
+
+    let mut unresolved = Vec::new();
+    for object in &scope {
+        for (symbol, version) in object.symbols.iter().zip(&object.versions) {
+            // 弱引用找不到时为0, 不会导致符号查找错误
+            if symbol.index == 0 || symbol.name.is_empty() || !symbol.is_undefined() || symbol.bind() != STB_GLOBAL {
+                continue;
+            }
+            let required = version.as_ref().map(|version| version.name.as_str());
+            let candidates = exports.get(symbol.name.as_str()).map(Vec::as_slice).unwrap_or(&[]);
+            if candidates.iter().any(|(export, unversioned)| satisfies(export, *unversioned, required)) {
+                continue;
+            }
+            unresolved.push(UnresolvedSymbol {
+                object:object.path.clone(),
+                name:symbol.name.clone(),
+                version:required.map(String::from),
+                file:version.as_ref().and_then(|version| version.file.clone()),
+                available:candidates.iter().map(|(export, _)| match &export.version {
+                    Some(version) => format!("{}{}{}",symbol.name,if export.hidden { "@" } else { "@@" },version),
+                    None => symbol.name.clone(),
+                }).collect(),
+            });
+        }
+    }
+
+    let missing = tree.problems().iter().map(|dependency| dependency.name.clone()).collect();
+    Ok(SymbolCheck { objects:paths, missing, unresolved })
+}
+
+impl std::fmt::Display for UnresolvedSymbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f,"{}: {}",self.object.display(),self.name)?;
+        if let Some(version) = &self.version {
+            write!(f,"@{}",version)?;
+        }
+        if let Some(file) = &self.file {
+            write!(f," (from {})",file)?;
+        }
+        if self.available.is_empty() {
+            write!(f,", not defined by any object")
+        } else {
+            write!(f,", only found as {}",self.available.join(", "))
+        }
+    }
+}
+
+impl std::fmt::Display for SymbolCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f,"Lookup scope ({} objects):",self.objects.len())?;
+        for object in &self.objects {
+            writeln!(f,"    {}",object.display())?;
+        }
+        if !self.missing.is_empty() {
+            writeln!(f,"{} missing libraries:",self.missing.len())?;
+            for name in &self.missing {
+                writeln!(f,"    {}",name)?;
+            }
+        }
+        if self.unresolved.is_empty() {
+            writeln!(f,"All undefined symbols are resolved.")
+        } else {
+            writeln!(f,"{} unresolved symbols:",self.unresolved.len())?;
+            for symbol in &self.unresolved {
+                writeln!(f,"    {}",symbol)?;
+            }
+            Ok(())
+        }
+    }
+}
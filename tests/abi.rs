@@ -0,0 +1,64 @@
+//! abi: tests/fixtures/compare中libcmp-v1.1.elf只增加了版本VERS_1.1和符号sub,
+//! libcmp-v2.elf改了SONAME, 删除了mul并把数据对象table从16字节扩大到32字节
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use easyreadelf::abi::{check_abi, AbiReport};
+
+fn fixture(name:&str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/compare").join(name)
+}
+
+fn abi(old:&str, new:&str) -> AbiReport {
+    check_abi(&mut File::open(fixture(old)).unwrap(), &mut File::open(fixture(new)).unwrap()).unwrap()
+}
+
+fn easyreadelf(args:&[&str], old:&str, new:&str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .arg("abi")
+        .args(args)
+        .arg(fixture(old))
+        .arg(fixture(new))
+        .output()
+        .expect("Failed to run easyreadelf")
+}
+
+#[test]
+fn identical_libraries() {
+    let report = abi("libcmp-v1.elf", "libcmp-v1.elf");
+    assert!(report.changes.is_empty());
+    assert_eq!(report.soname.as_deref(), Some("libcmp.so.1"));
+}
+
+#[test]
+fn compatible_additions() {
+    let output = easyreadelf(&[], "libcmp-v1.elf", "libcmp-v1.1.elf");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "\
+COMPATIBLE version definition added: VERS_1.1
+COMPATIBLE symbol added: sub@@VERS_1.1 (FUNC)
+0 breaking, 2 compatible changes
+");
+}
+
+#[test]
+fn breaking_changes() {
+    let report = abi("libcmp-v1.elf", "libcmp-v2.elf");
+    assert!(report.is_breaking());
+    let changes:Vec<String> = report.changes.iter().map(|change| format!("{} {}",change.severity.to_string(),change.description)).collect();
+    assert_eq!(changes, [
+        "BREAKING SONAME changed: libcmp.so.1 -> libcmp.so.2",
+        "COMPATIBLE needed library added: libdep.so",
+        "COMPATIBLE version definition added: VERS_2",
+        "BREAKING symbol removed: mul@@VERS_1 (FUNC)",
+        "BREAKING data object size changed: table@@VERS_1 16 -> 32 bytes",
+        "COMPATIBLE symbol added: scale@@VERS_2 (FUNC)",
+        "COMPATIBLE symbol added: sub@@VERS_2 (FUNC)",
+    ]);
+    let output = easyreadelf(&["--output", "json"], "libcmp-v1.elf", "libcmp-v2.elf");
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\"soname\": \"libcmp.so.2\""), "{}", stdout);
+    assert!(stdout.contains("\"breaking\": true"), "{}", stdout);
+}
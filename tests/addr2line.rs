@@ -0,0 +1,51 @@
+//! 从崩溃日志中提取地址: 只使用被符号化的文件自己的帧, 每帧一个地址
+
+use easyreadelf::addr2line::{extract_addresses, parse_address, AddressRef, ModuleNames};
+
+fn prog() -> ModuleNames {
+    ModuleNames::new("/tmp/build/prog", Some("libprog.so.1"))
+}
+
+#[test]
+fn backtrace_frames_of_the_file_yield_one_address() {
+    // glibc backtrace_symbols的输出: 有符号时是符号内偏移, 没有符号时是模块内偏移, 都没有时是运行时地址
+    let log = "./prog(main+0x1d) [0x55d0c0a0b1d1]\n./prog(+0x1d) [0x55d0c0a0b1d1]\n/tmp/build/prog() [0x401136]";
+    assert_eq!(extract_addresses(log, &prog()), [
+        AddressRef::SYMBOL(String::from("main"), 0x1d),
+        AddressRef::RELATIVE(0x1d),
+        AddressRef::ABSOLUTE(0x401136),
+    ]);
+}
+
+#[test]
+fn frames_of_other_modules_are_ignored() {
+    let log = "\
+/lib/x86_64-linux-gnu/libc.so.6(__libc_start_main+0x80) [0x7f0000029d90]
+libc.so.6+0x1234
+/opt/other/libprog.so.2+0x10
+#1  0x00007ffff7a2d1ca in __libc_start_call_main () from /lib/x86_64-linux-gnu/libc.so.6
+[0x7f0000001000] segfault at 0
+deadbeef cafe add";
+    assert_eq!(extract_addresses(log, &prog()), []);
+}
+
+#[test]
+fn file_name_path_and_soname_identify_the_file() {
+    let log = "prog+0x80\n/tmp/build/prog+0x90\n/usr/lib/libprog.so.1+0x40\n#0  0x0000555555555131 in main () at t.c:3\nmain+0x8";
+    assert_eq!(extract_addresses(log, &prog()), [
+        AddressRef::RELATIVE(0x80),
+        AddressRef::RELATIVE(0x90),
+        AddressRef::RELATIVE(0x40),
+        AddressRef::ABSOLUTE(0x555555555131),
+        AddressRef::SYMBOL(String::from("main"), 0x8),
+    ]);
+}
+
+#[test]
+fn command_line_addresses() {
+    let module = prog();
+    assert_eq!(parse_address("401136", &module), Some(AddressRef::ABSOLUTE(0x401136)));
+    assert_eq!(parse_address("prog+0x10", &module), Some(AddressRef::RELATIVE(0x10)));
+    assert_eq!(parse_address("main+0x10", &module), Some(AddressRef::SYMBOL(String::from("main"), 0x10)));
+    assert_eq!(parse_address("libc.so.6+0x10", &module), None);
+}
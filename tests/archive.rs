@@ -0,0 +1,77 @@
+//! 静态库的解析和命令行: tests/fixtures/archive中的gnu.a, bsd.a和thin.a都由add.o和mul.o组成,
+//! thin_escape.a是手工构造的thin静态库, 第一个成员的路径指向静态库所在目录之外
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use easyreadelf::elf::archive::{parse_archive, Archive};
+
+fn fixture(name:&str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/archive").join(name)
+}
+
+fn archive(name:&str) -> Archive {
+    parse_archive(&mut File::open(fixture(name)).unwrap()).unwrap()
+}
+
+fn easyreadelf(args:&[&str], file:&Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_easyreadelf")).args(args).arg(file).output().expect("Failed to run easyreadelf")
+}
+
+#[test]
+fn members_and_index_of_each_format() {
+    for name in ["gnu.a", "bsd.a", "thin.a"] {
+        let archive = archive(name);
+        assert_eq!(archive.thin, name == "thin.a", "{}", name);
+        let members:Vec<&str> = archive.members.iter().map(|member| member.name.as_str()).collect();
+        assert_eq!(members, ["add.o", "mul.o"], "{}", name);
+        for (symbol, member) in archive.symbols.iter().zip(["add.o", "mul.o"]) {
+            assert_eq!(archive.member_at(symbol.member_offset).unwrap().name, member, "{}", name);
+        }
+        let symbols:Vec<&str> = archive.symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+        assert_eq!(symbols, ["add", "mul"], "{}", name);
+    }
+}
+
+#[test]
+fn member_contents_match_the_objects() {
+    for name in ["gnu.a", "bsd.a", "thin.a"] {
+        let archive = archive(name);
+        let mut file = File::open(fixture(name)).unwrap();
+        for member in &archive.members {
+            let data = archive.read_member(&mut file, &fixture(""), member).unwrap();
+            assert_eq!(data, std::fs::read(fixture(&member.name)).unwrap(), "{}({})", name, member.name);
+        }
+    }
+}
+
+#[test]
+fn thin_members_stay_in_the_archive_directory() {
+    let archive = archive("thin_escape.a");
+    let mut file = File::open(fixture("thin_escape.a")).unwrap();
+    let escape = &archive.members[0];
+    assert_eq!(escape.name, "../archive/add.o");
+    assert!(archive.read_member(&mut file, &fixture(""), escape).is_err());
+    assert!(archive.read_member(&mut file, &fixture(""), &archive.members[1]).is_ok());
+}
+
+#[test]
+fn bad_member_does_not_stop_the_archive() {
+    let output = easyreadelf(&["-h"], &fixture("thin_escape.a"));
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("thin_escape.a(mul.o)\nMagic:"), "{}", stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("thin_escape.a(../archive/add.o): thin archive member path"), "{}", stderr);
+    assert!(stderr.contains("1 of 2 files failed:"), "{}", stderr);
+}
+
+#[test]
+fn archive_index_listing() {
+    let output = easyreadelf(&["-c"], &fixture("bsd.a"));
+    assert!(output.status.success());
+    let expected = "Index: (2 entries, 0x8 bytes in the symbol table)\n\
+                    Contents of member add.o at offset 0x70\n\tadd\n\
+                    Contents of member mul.o at offset 0x378\n\tmul\n";
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with(expected));
+}
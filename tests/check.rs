@@ -0,0 +1,44 @@
+//! --check的退出状态和失败汇总: tests/corpus/check_broken_layout.elf有多处结构问题, tests/fixtures/compare/libcmp-v1.elf没有问题
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn path(name:&str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(name)
+}
+
+fn easyreadelf(args:&[&str], files:&[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .args(args)
+        .args(files.iter().map(|file| path(file)))
+        .output()
+        .expect("Failed to run easyreadelf")
+}
+
+#[test]
+fn passing_file() {
+    let output = easyreadelf(&["--check"], &["fixtures/compare/libcmp-v1.elf"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "No problems found\n");
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn failing_file_is_in_the_summary() {
+    let output = easyreadelf(&["--check"], &["corpus/check_broken_layout.elf", "fixtures/compare/libcmp-v1.elf"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("11 problems found\n"), "{}", stdout);
+    assert!(stdout.contains("libcmp-v1.elf\nNo problems found\n"), "{}", stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("1 of 2 files failed:\n"), "{}", stderr);
+    assert!(stderr.contains("check_broken_layout.elf: check found problems\n"), "{}", stderr);
+}
+
+#[test]
+fn archive_members_are_checked_separately() {
+    let output = easyreadelf(&["--check", "--output", "json"], &["fixtures/archive/gnu.a"]);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("\"check\"").count(), 2, "{}", stdout);
+}
@@ -0,0 +1,68 @@
+//! 符号版本检查: tests/fixtures/check_symbols/app.elf链接libver-2.elf编译, 引用foo@VERS_2和bar@VERS_1,
+//! 使用DT_RUNPATH $ORIGIN; libver-1.elf只导出foo@@VERS_1和bar@@VERS_1, libver-2.elf导出foo@VERS_1, foo@@VERS_2和bar@@VERS_1,
+//! 两者的SONAME都是libver.so
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use easyreadelf::deps::SearchConfig;
+use easyreadelf::unresolved::check_symbols;
+
+fn fixture(name:&str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/check_symbols").join(name)
+}
+
+/// 把app.so和指定版本的libver.so放到同一个临时目录中
+fn install(library:&str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("easyreadelf-check-symbols-{}-{}",std::process::id(),library));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::copy(fixture("app.elf"), dir.join("app.so")).unwrap();
+    fs::copy(fixture(library), dir.join("libver.so")).unwrap();
+    dir
+}
+
+#[test]
+fn missing_version_is_unresolved() {
+    let dir = install("libver-1.elf");
+    let check = check_symbols(&dir.join("app.so"), &[], &SearchConfig::new(None, None)).unwrap();
+    assert_eq!(check.objects, [dir.join("app.so"), dir.join("libver.so")]);
+    assert!(check.missing.is_empty());
+    assert!(!check.passed());
+    assert_eq!(check.unresolved.len(), 1);
+    let foo = &check.unresolved[0];
+    assert_eq!((foo.name.as_str(), foo.version.as_deref(), foo.file.as_deref()), ("foo", Some("VERS_2"), Some("libver.so")));
+    assert_eq!(foo.available, ["foo@@VERS_1"]);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn matching_versions_pass() {
+    let dir = install("libver-2.elf");
+    let check = check_symbols(&dir.join("app.so"), &[], &SearchConfig::new(None, None)).unwrap();
+    assert!(check.passed());
+    assert!(check.unresolved.is_empty());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn check_symbols_exit_status() {
+    let run = |dir:&Path| Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .arg("check-symbols")
+        .arg(dir.join("app.so"))
+        .output()
+        .expect("Failed to run easyreadelf");
+    let old = install("libver-1.elf");
+    let output = run(&old);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = format!("1 unresolved symbols:\n    {}: foo@VERS_2 (from libver.so), only found as foo@@VERS_1\n",old.join("app.so").display());
+    assert!(stdout.ends_with(&expected), "{}", stdout);
+    fs::remove_dir_all(old).unwrap();
+
+    let new = install("libver-2.elf");
+    let output = run(&new);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with("All undefined symbols are resolved.\n"));
+    fs::remove_dir_all(new).unwrap();
+}
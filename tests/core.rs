@@ -0,0 +1,55 @@
+//! core文件: tests/fixtures/core/crash.core是一个-nostdlib静态程序向自己发送SIGABRT后的core,
+//! 生成时coredump_filter为0, 之后又去掉了所有PT_LOAD段的内容, 只保留PT_NOTE
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use easyreadelf::elf::core::{parse_core, signal_name, CoreDump, AT_ENTRY, AT_PAGESZ, AT_PHNUM};
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/core/crash.core")
+}
+
+fn core() -> CoreDump {
+    parse_core(&mut File::open(fixture()).unwrap()).unwrap()
+}
+
+#[test]
+fn threads_and_signal() {
+    let core = core();
+    assert_eq!(core.threads.len(), 1);
+    let thread = &core.threads[0];
+    assert_eq!((thread.signal, thread.current_signal, thread.pid), (6, 6, 26916));
+    assert_eq!(signal_name(thread.signal), "SIGABRT");
+    assert_eq!(thread.pc(), Some(0x4000ce));
+    let register = |name:&str| thread.registers.iter().find(|(register, _)| *register == name).map(|(_, value)| *value);
+    // kill(getpid(), SIGABRT)的参数和系统调用号
+    assert_eq!((register("rdi"), register("rsi"), register("orig_rax")), (Some(26916), Some(6), Some(62)));
+    let process = core.process.as_ref().unwrap();
+    assert_eq!((process.fname.as_str(), process.psargs.as_str()), ("crash", "./crash"));
+    assert_eq!(core.signal.as_ref().map(|signal| signal.signo), Some(6));
+    assert!(core.fpregset);
+}
+
+#[test]
+fn auxv_and_mappings() {
+    let core = core();
+    assert_eq!(core.auxv_value(AT_PAGESZ), Some(0x1000));
+    assert_eq!(core.auxv_value(AT_PHNUM), Some(2));
+    assert_eq!(core.auxv_value(AT_ENTRY), Some(0x4000b0));
+    assert_eq!(core.page_size, 4096);
+    assert_eq!(core.files.len(), 1);
+    assert_eq!(core.mapping_at(0x4000ce).map(|file| file.path.as_str()), Some("/tmp/fx4/crash"));
+    assert!(core.mapping_at(0x401000).is_none());
+    assert_eq!(core.loads.len(), 6);
+}
+
+#[test]
+fn core_output() {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyreadelf")).arg("--core").arg(fixture()).output().expect("Failed to run easyreadelf");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("  Thread 1 (LWP 26916): signal 6 (SIGABRT) pc=0x4000ce in /tmp/fx4/crash\n"), "{}", stdout);
+    assert!(stdout.contains("  AT_ENTRY              0x4000b0\n"), "{}", stdout);
+    assert!(stdout.contains("  0x0000000000400000 0x0000000000401000 0x0000000000000000 /tmp/fx4/crash\n"), "{}", stdout);
+}
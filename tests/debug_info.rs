@@ -0,0 +1,62 @@
+//! .debug_info的DIE树: tests/fixtures/debug_info/point.elf由
+//! `struct point { int x; int y; }; int norm(struct point p)`编译链接后只保留调试信息(strip --only-keep-debug)
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use easyreadelf::dwarf::constants::{DW_TAG_COMPILE_UNIT, DW_TAG_SUBPROGRAM};
+use easyreadelf::dwarf::info::parse_debug_info;
+
+fn fixture(name:&str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(name)
+}
+
+#[test]
+fn die_tree_dump() {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .arg("--debug-dump=info")
+        .arg(fixture("fixtures/debug_info/point.elf"))
+        .output()
+        .expect("Failed to run easyreadelf");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let dies:Vec<&str> = stdout.lines().filter(|line| line.contains("Abbrev Number")).collect();
+    assert_eq!(dies, [
+        " <0><b>: Abbrev Number: 1 (DW_TAG_compile_unit)",
+        " <1><2d>: Abbrev Number: 2 (DW_TAG_structure_type)",
+        " <2><3a>: Abbrev Number: 3 (DW_TAG_member)",
+        " <2><45>: Abbrev Number: 3 (DW_TAG_member)",
+        " <1><51>: Abbrev Number: 4 (DW_TAG_base_type)",
+        " <1><58>: Abbrev Number: 5 (DW_TAG_subprogram)",
+        " <2><76>: Abbrev Number: 6 (DW_TAG_formal_parameter)",
+    ]);
+    assert!(stdout.contains("    <11>   DW_AT_name        : (indirect string, offset: 0x73): point.c\n"));
+    assert!(stdout.contains("    <3b>   DW_AT_name        : x\n"));
+    assert!(stdout.contains("    <7c>   DW_AT_type        : <0x2d>\n"));
+}
+
+#[test]
+fn die_tree_structure() {
+    let units = parse_debug_info(&mut File::open(fixture("fixtures/debug_info/point.elf")).unwrap()).unwrap();
+    assert_eq!(units.len(), 1);
+    let root = units[0].root.as_ref().unwrap();
+    assert_eq!(root.tag, DW_TAG_COMPILE_UNIT);
+    let children:Vec<Option<&str>> = root.children.iter().map(|die| die.name()).collect();
+    assert_eq!(children, [Some("point"), Some("int"), Some("norm")]);
+    let norm = &root.children[2];
+    assert_eq!(norm.tag, DW_TAG_SUBPROGRAM);
+    assert_eq!(norm.children.len(), 1);
+    assert_eq!(norm.children[0].name(), Some("p"));
+    assert_eq!(root.walk().len(), 7);
+}
+
+/// 100000层嵌套的DIE, 解析和释放都不能栈溢出
+#[test]
+fn deep_nesting() {
+    let units = parse_debug_info(&mut File::open(fixture("corpus/debug_info_deep_nesting.elf")).unwrap()).unwrap();
+    let root = units[0].root.as_ref().unwrap();
+    let dies = root.walk();
+    assert_eq!(dies.len(), 100000);
+    assert_eq!(dies.last().unwrap().depth, 99999);
+    drop(units);
+}
@@ -0,0 +1,110 @@
+//! 依赖解析: tests/fixtures/deps中app.elf的NEEDED为liba.so, libb.so, 使用DT_RPATH /opt/rpath;
+//! liba.elf依赖libpriv.so, libdup.so, libconf.so, 使用DT_RUNPATH $ORIGIN/../private;
+//! libb.elf依赖libshared.so, libmissing.so, 没有RPATH和RUNPATH; leaf.elf没有依赖, 复制为各个叶子库
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use easyreadelf::deps::{resolve_dependencies, Dependency, Resolution, SearchConfig};
+
+fn fixture(name:&str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/deps").join(name)
+}
+
+/// 在临时目录中搭建sysroot, 同名的库放在多个搜索目录中以区分查找顺序
+fn sysroot(name:&str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("easyreadelf-deps-{}-{}",std::process::id(),name));
+    let _ = fs::remove_dir_all(&root);
+    let files = [
+        ("app/app.so", "app.elf"),
+        ("opt/rpath/liba.so", "liba.elf"),
+        ("opt/rpath/libb.so", "libb.elf"),
+        ("opt/rpath/libshared.so", "leaf.elf"),
+        ("ldpath/libb.so", "libb.elf"),
+        ("ldpath/libdup.so", "leaf.elf"),
+        ("opt/private/libpriv.so", "leaf.elf"),
+        ("opt/private/libdup.so", "leaf.elf"),
+        ("conf/libconf.so", "leaf.elf"),
+        ("conf/libshared.so", "leaf.elf"),
+    ];
+    for (path, fixture_name) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::copy(fixture(fixture_name), path).unwrap();
+    }
+    fs::create_dir_all(root.join("etc")).unwrap();
+    fs::write(root.join("etc/ld.so.conf"), "# 测试\n/conf\n").unwrap();
+    root
+}
+
+/// 每个依赖的`name => 相对sysroot的路径 [来源]`, 子依赖缩进
+fn lines(dependencies:&[Dependency], root:&Path, depth:usize, result:&mut Vec<String>) {
+    for dependency in dependencies {
+        let resolution = match &dependency.resolution {
+            Resolution::FOUND(path) => path.strip_prefix(root).unwrap().display().to_string(),
+            Resolution::NOT_FOUND => String::from("not found"),
+            Resolution::MISMATCH(path, reason) => format!("{} ({})",path.display(),reason),
+        };
+        result.push(format!("{:indent$}{} => {} [{}]","",dependency.name,resolution,dependency.source,indent = depth * 2));
+        lines(&dependency.children, root, depth + 1, result);
+    }
+}
+
+#[test]
+fn search_order_and_origin() {
+    let root = sysroot("order");
+    let config = SearchConfig::new(Some(root.clone()), Some("/ldpath"));
+    assert_eq!(config.config_dirs, ["/conf"]);
+    let tree = resolve_dependencies(&root.join("app/app.so"), &config).unwrap();
+    assert!(tree.interpreter.is_none());
+    let mut result = Vec::new();
+    lines(&tree.needed, &root, 0, &mut result);
+    assert_eq!(result, [
+        // RPATH先于LD_LIBRARY_PATH
+        "liba.so => opt/rpath/liba.so [RPATH]",
+        // liba有RUNPATH, 不使用app的RPATH; $ORIGIN是liba所在的目录
+        "  libpriv.so => opt/private/libpriv.so [RUNPATH]",
+        // LD_LIBRARY_PATH先于RUNPATH
+        "  libdup.so => ldpath/libdup.so [LD_LIBRARY_PATH]",
+        "  libconf.so => conf/libconf.so [ld.so.conf]",
+        "libb.so => opt/rpath/libb.so [RPATH]",
+        // libb没有RUNPATH, 使用加载链上app的RPATH
+        "  libshared.so => opt/rpath/libshared.so [RPATH]",
+        "  libmissing.so => not found []",
+    ]);
+    let problems:Vec<&str> = tree.problems().iter().map(|dependency| dependency.name.as_str()).collect();
+    assert_eq!(problems, ["libmissing.so"]);
+    let libraries:Vec<PathBuf> = tree.libraries().iter().map(|path| path.strip_prefix(&root).unwrap().to_path_buf()).collect();
+    assert_eq!(libraries, ["opt/rpath/liba.so", "opt/rpath/libb.so", "opt/private/libpriv.so", "ldpath/libdup.so", "conf/libconf.so", "opt/rpath/libshared.so"].map(PathBuf::from));
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn without_library_path() {
+    let root = sysroot("no-ldpath");
+    let config = SearchConfig::new(Some(root.clone()), None);
+    let tree = resolve_dependencies(&root.join("app/app.so"), &config).unwrap();
+    let liba = &tree.needed[0];
+    let libdup = &liba.children[1];
+    assert_eq!(libdup.source, "RUNPATH");
+    assert!(matches!(&libdup.resolution, Resolution::FOUND(path) if path == &root.join("opt/private/libdup.so")));
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn deps_output() {
+    let root = sysroot("output");
+    let output = Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .args(["deps", "--sysroot"])
+        .arg(&root)
+        .args(["--library-path", "/ldpath"])
+        .arg(root.join("app/app.so"))
+        .output()
+        .expect("Failed to run easyreadelf");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("    libpriv.so => {} [RUNPATH]\n",root.join("opt/private/libpriv.so").display())), "{}", stdout);
+    assert!(stdout.contains("    libmissing.so => not found\n"), "{}", stdout);
+    assert!(stdout.ends_with("\n1 unresolved dependencies:\n    libmissing.so\n"), "{}", stdout);
+    fs::remove_dir_all(root).unwrap();
+}
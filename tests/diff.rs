@@ -0,0 +1,77 @@
+//! diff: 比较tests/fixtures/compare中同一个库的两个版本, v2改了SONAME, 增加了NEEDED, 删除了mul, 增加了sub和scale
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use easyreadelf::diff::{diff_elf, DiffGroup, ElfDiff};
+
+fn fixture(name:&str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/compare").join(name)
+}
+
+fn diff(old:&str, new:&str) -> ElfDiff {
+    diff_elf(&mut File::open(fixture(old)).unwrap(), &mut File::open(fixture(new)).unwrap()).unwrap()
+}
+
+fn group<'a>(diff:&'a ElfDiff, title:&str) -> &'a DiffGroup {
+    diff.groups.iter().find(|group| group.title == title).unwrap()
+}
+
+/// 一组变化的文本形式, 如`+ NEEDED libdep.so`
+fn lines(group:&DiffGroup) -> Vec<String> {
+    group.changes.iter().map(|change| change.to_string()).collect()
+}
+
+#[test]
+fn identical_files() {
+    assert!(diff("libcmp-v1.elf", "libcmp-v1.elf").is_empty());
+}
+
+#[test]
+fn dynamic_strings_are_named_by_tag_and_value() {
+    let diff = diff("libcmp-v1.elf", "libcmp-v2.elf");
+    let lines = lines(group(&diff, "Dynamic section"));
+    assert!(lines.contains(&String::from("- SONAME libcmp.so.1")), "{:?}", lines);
+    assert!(lines.contains(&String::from("+ SONAME libcmp.so.2")), "{:?}", lines);
+    assert!(lines.contains(&String::from("+ NEEDED libdep.so")), "{:?}", lines);
+    assert!(lines.contains(&String::from("~ VERDEFNUM (value: 2 -> 3)")), "{:?}", lines);
+}
+
+#[test]
+fn exported_symbols() {
+    let diff = diff("libcmp-v1.elf", "libcmp-v2.elf");
+    assert_eq!(lines(group(&diff, "Exported symbols")), [
+        "- mul@@VERS_1 (type: FUNC, bind: GLOBAL, size: 6)",
+        "~ table@@VERS_1 (size: 16 -> 32)",
+        "~ add@@VERS_1 (size: 4 -> 19)",
+        "+ VERS_2@@VERS_2 (type: OBJECT, bind: GLOBAL, size: 0)",
+        "+ scale@@VERS_2 (type: FUNC, bind: GLOBAL, size: 61)",
+        "+ sub@@VERS_2 (type: FUNC, bind: GLOBAL, size: 5)",
+    ]);
+}
+
+#[test]
+fn sections_and_numbered_segments() {
+    let diff = diff("libcmp-v1.elf", "libcmp-v2.elf");
+    let sections = lines(group(&diff, "Sections"));
+    assert!(sections.contains(&String::from("~ .text (address: 0x238 -> 0x300, size: 0xa -> 0x55)")), "{:?}", sections);
+    assert!(sections.contains(&String::from("+ .got (type: PROGBITS, flags: WA, address: 0x498, size: 0x8, align: 8, entsize: 0x8)")), "{:?}", sections);
+    let segments:Vec<String> = group(&diff, "Program headers").changes.iter().map(|change| change.name.clone()).collect();
+    assert_eq!(segments, ["LOAD[1]", "LOAD[2]", "DYNAMIC"]);
+}
+
+#[test]
+fn json_output() {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .args(["diff", "--output", "json"])
+        .arg(fixture("libcmp-v1.elf"))
+        .arg(fixture("libcmp-v2.elf"))
+        .output()
+        .expect("Failed to run easyreadelf");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for key in ["\"elf_header\"", "\"sections\"", "\"program_headers\"", "\"dynamic_section\"", "\"exported_symbols\"", "\"notes\""] {
+        assert!(stdout.contains(key), "{}", key);
+    }
+    assert!(stdout.contains("\"name\": \"NEEDED libdep.so\""), "{}", stdout);
+}
@@ -0,0 +1,68 @@
+//! .eh_frame的CFA规则和.eh_frame_hdr检查: tests/fixtures/eh_frame/frames.elf是-O0编译, -nostdlib链接的共享库,
+//! leaf和framed两个函数都建立rbp栈帧, framed还保存了rbx
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use easyreadelf::dwarf::frame::{check_eh_frame_hdr, parse_eh_frame, parse_eh_frame_hdr, CfaRule, RegisterRule};
+
+const RBX:u64 = 3;
+const RBP:u64 = 6;
+const RSP:u64 = 7;
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/eh_frame/frames.elf")
+}
+
+#[test]
+fn cfa_rows_of_each_fde() {
+    let eh_frame = parse_eh_frame(&mut File::open(fixture()).unwrap()).unwrap().unwrap();
+    let framed = eh_frame.fdes().find(|fde| fde.pc_begin == 0x21f).unwrap();
+    assert_eq!(framed.pc_range, 0x34);
+    let rows = eh_frame.unwind_rows(framed).unwrap();
+    let addresses:Vec<u64> = rows.iter().map(|row| row.address).collect();
+    assert_eq!(addresses, [0x21f, 0x220, 0x223, 0x228, 0x252]);
+    let cfas = [(RSP, 8), (RSP, 16), (RBP, 16), (RBP, 16), (RSP, 8)];
+    for (row, (register, offset)) in rows.iter().zip(cfas) {
+        assert!(row.cfa == CfaRule::RegisterOffset(register, offset), "{:#x}", row.address);
+    }
+    assert!(rows[1].registers.get(&RBP) == Some(&RegisterRule::Offset(-16)));
+    assert!(!rows[2].registers.contains_key(&RBX));
+    assert!(rows[3].registers.get(&RBX) == Some(&RegisterRule::Offset(-24)));
+}
+
+#[test]
+fn frames_interp_dump() {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .arg("--debug-dump=frames-interp")
+        .arg(fixture())
+        .output()
+        .expect("Failed to run easyreadelf");
+    assert!(output.status.success());
+    let expected = "\
+00000018 000000000000001c 0000001c FDE cie=00000000 pc=0000000000000210..000000000000021f
+   LOC           CFA      rbp   ra
+0000000000000210 rsp+8    u     c-8
+0000000000000211 rsp+16   c-16  c-8
+0000000000000214 rbp+16   c-16  c-8
+000000000000021e rsp+8    c-16  c-8
+";
+    // 列按宽度对齐, 行尾有空格
+    let stdout:Vec<String> = String::from_utf8_lossy(&output.stdout).lines().map(|line| line.trim_end().to_string()).collect();
+    assert!(stdout.join("\n").contains(expected));
+}
+
+#[test]
+fn eh_frame_hdr_matches_the_fdes() {
+    let mut file = File::open(fixture()).unwrap();
+    let eh_frame = parse_eh_frame(&mut file).unwrap().unwrap();
+    let mut hdr = parse_eh_frame_hdr(&mut file).unwrap().unwrap();
+    assert_eq!(hdr.table, [(0x1f0, 0x2d0), (0x210, 0x290), (0x21f, 0x2b0)]);
+    assert!(check_eh_frame_hdr(&hdr, &eh_frame).is_empty());
+    hdr.table[1].1 = 0x2b0;
+    hdr.table.push((0x300, 0x2a0));
+    assert_eq!(check_eh_frame_hdr(&hdr, &eh_frame), [
+        "entry 0x210 points to FDE at 0x2b0 which starts at 0x21f",
+        "entry 0x300 points to 0x2a0 which is not an FDE",
+    ]);
+}
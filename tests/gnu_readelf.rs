@@ -0,0 +1,65 @@
+//! 与本机安装的binutils readelf逐字节比较--output gnu的输出, 没有安装readelf时跳过
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const MODES:[(&[&str], &[&str]); 9] = [
+    (&["-h"], &["-h"]),
+    (&["-s"], &["-S"]),
+    (&["-l"], &["-l"]),
+    (&["-W", "-s"], &["-W", "-S"]),
+    (&["-W", "-l"], &["-W", "-l"]),
+    (&["-e"], &["-e"]),
+    (&["-W", "-e"], &["-W", "-e"]),
+    (&["-h", "-l"], &["-h", "-l"]),
+    (&["-I"], &["-I"]),
+];
+
+fn readelf(args:&[&str], file:&Path) -> Option<String> {
+    let output = Command::new("readelf").args(args).arg(file).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn easyreadelf(args:&[&str], file:&Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .args(["--output", "gnu"])
+        .args(args)
+        .arg(file)
+        .output()
+        .expect("Failed to run easyreadelf");
+    assert!(output.status.success(), "easyreadelf {:?} {} failed: {}", args, file.display(), String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// 只比较readelf能读取的64位小端文件: 可执行文件, 共享库, 测试程序本身和同时有.hash与.gnu.hash的语料
+fn inputs() -> Vec<PathBuf> {
+    let mut files = vec![PathBuf::from(env!("CARGO_BIN_EXE_easyreadelf")), std::env::current_exe().unwrap()];
+    files.push(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/hash_tables.elf"));
+    let candidates = ["/bin/ls", "/bin/sh", "/usr/bin/env", "/lib64/ld-linux-x86-64.so.2", "/lib/x86_64-linux-gnu/libc.so.6", "/lib/x86_64-linux-gnu/libm.so.6", "/usr/lib/x86_64-linux-gnu/crt1.o"];
+    files.extend(candidates.iter().map(PathBuf::from).filter(|path| path.exists()));
+    files.retain(|file| readelf(&["-h"], file).is_some_and(|header| header.contains("ELF64") && header.contains("little endian")));
+    files
+}
+
+#[test]
+fn matches_gnu_readelf() {
+    if Command::new("readelf").arg("--version").output().is_err() {
+        eprintln!("readelf is not installed, skipping");
+        return;
+    }
+    let mut mismatches = Vec::new();
+    for file in inputs() {
+        for (ours, theirs) in MODES {
+            let Some(expected) = readelf(theirs, &file) else { continue };
+            let actual = easyreadelf(ours, &file);
+            if actual != expected {
+                let line = actual.lines().zip(expected.lines())
+                    .position(|(a, e)| a != e)
+                    .unwrap_or(actual.lines().count().min(expected.lines().count()));
+                mismatches.push(format!("readelf {} {}: first difference at line {}\n  ours:    {:?}\n  readelf: {:?}",
+                                        theirs.join(" "), file.display(), line + 1, actual.lines().nth(line), expected.lines().nth(line)));
+            }
+        }
+    }
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+}
@@ -0,0 +1,48 @@
+//! 加固检查: tests/fixtures/hardening中的两个程序由同一个源文件编译,
+//! hardened.elf使用PIE, full RELRO, -fstack-protector-strong, _FORTIFY_SOURCE=2, -fcf-protection=full并strip,
+//! weak.elf关闭了这些选项, 使用可执行栈和RUNPATH并保留.symtab
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use easyreadelf::hardening::{check_hardening, HardeningReport};
+
+fn fixture(name:&str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/hardening").join(name)
+}
+
+fn report(name:&str) -> HardeningReport {
+    check_hardening(&mut File::open(fixture(name)).unwrap()).unwrap()
+}
+
+/// 每项检查的`name status`
+fn statuses(report:&HardeningReport) -> Vec<String> {
+    report.checks.iter().map(|check| format!("{} {}",check.name,check.status.to_string())).collect()
+}
+
+#[test]
+fn hardened_binary_passes() {
+    let report = report("hardened.elf");
+    assert!(report.passed());
+    assert_eq!(statuses(&report), ["relro PASS", "nx PASS", "pie PASS", "canary PASS", "fortify PASS", "rpath PASS", "cet PASS", "stripped PASS"]);
+    let fortify = report.checks.iter().find(|check| check.name == "fortify").unwrap();
+    assert_eq!(fortify.detail, "2 fortified functions: __printf_chk, __strcpy_chk");
+}
+
+#[test]
+fn weak_binary_fails() {
+    let report = report("weak.elf");
+    assert!(!report.passed());
+    assert_eq!(statuses(&report), ["relro FAIL", "nx FAIL", "pie FAIL", "canary FAIL", "fortify FAIL", "rpath FAIL", "cet FAIL", "stripped FAIL"]);
+    let details:Vec<&str> = report.checks.iter().map(|check| check.detail.as_str()).collect();
+    assert_eq!(&details[..3], ["no PT_GNU_RELRO", "PT_GNU_STACK is executable (RWX)", "ET_EXEC, loaded at a fixed address"]);
+    assert_eq!(details[5], "RUNPATH /opt/greet/lib");
+}
+
+#[test]
+fn hardening_output() {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyreadelf")).arg("--hardening").arg(fixture("weak.elf")).output().expect("Failed to run easyreadelf");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("relro      FAIL no PT_GNU_RELRO\nnx         FAIL PT_GNU_STACK is executable (RWX)\n"), "{}", stdout);
+}
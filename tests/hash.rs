@@ -0,0 +1,49 @@
+//! 通过.hash和.gnu.hash按不带版本的名字查找符号: tests/fixtures/hash/versioned.elf中
+//! foo有隐藏的foo@VERS_1和默认的foo@@VERS_2, bar只有隐藏的bar@VERS_1, baz是默认版本baz@@VERS_1
+
+use std::fs::File;
+use std::path::Path;
+use easyreadelf::elf::hash::{parse_hash_tables, HashTables};
+use easyreadelf::elf::symbol::{STB_LOCAL, STT_FUNC};
+
+fn tables() -> HashTables {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/hash/versioned.elf");
+    parse_hash_tables(&mut File::open(path).unwrap()).unwrap()
+}
+
+/// 两个哈希表找到的符号索引
+fn lookup(tables:&HashTables, name:&str) -> (Option<usize>, Option<usize>) {
+    let (sysv, gnu) = (tables.sysv.as_ref().unwrap(), tables.gnu.as_ref().unwrap());
+    (
+        sysv.lookup(&tables.symbols, &tables.versym, name).map(|symbol| symbol.index),
+        gnu.lookup(&tables.symbols, &tables.versym, name).map(|symbol| symbol.index),
+    )
+}
+
+#[test]
+fn hidden_versions_do_not_match_unversioned_names() {
+    let tables = tables();
+    assert_eq!(tables.versym.len(), tables.symbols.len());
+    assert_eq!(lookup(&tables, "foo"), (Some(2), Some(2)));
+    assert_eq!(lookup(&tables, "bar"), (None, None));
+    assert_eq!(lookup(&tables, "baz"), (Some(6), Some(6)));
+    assert_eq!(tables.lookup("foo").map(|symbol| symbol.index), Some(2));
+    assert!(tables.problems().is_empty(), "{:?}", tables.problems());
+}
+
+#[test]
+fn local_symbols_do_not_bind() {
+    let mut tables = tables();
+    tables.symbols[6].st_info = STB_LOCAL << 4 | STT_FUNC;
+    assert_eq!(lookup(&tables, "baz"), (None, None));
+    assert!(tables.problems().is_empty());
+}
+
+#[test]
+fn without_versym_every_version_matches() {
+    let mut tables = tables();
+    tables.versym.clear();
+    assert_eq!(lookup(&tables, "bar"), (Some(4), Some(4)));
+    // 没有版本信息时哈希链中的第一个foo胜出
+    assert_eq!(tables.lookup("foo").map(|symbol| symbol.index), Some(1));
+}
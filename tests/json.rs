@@ -0,0 +1,66 @@
+//! --output json的字段名和数值格式: 使用tests/fixtures/compare/libcmp-v1.elf和tests/fixtures/hardening/hardened.elf,
+//! 字段名与结构体字段相同, 数值不做格式化, 无数据的枚举输出变体名
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use easyreadelf::elf::dynamic::parse_dynamic;
+use easyreadelf::elf::header::parse_header;
+use easyreadelf::elf::segment::parse_segments;
+use easyreadelf::elf::symbol::parse_dynamic_symbols;
+use easyreadelf::json::ToJson;
+
+fn fixture(name:&str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+fn file() -> File {
+    File::open(fixture("compare/libcmp-v1.elf")).unwrap()
+}
+
+/// 美化输出中第一层对象的字段名
+fn top_level_keys(stdout:&str) -> Vec<&str> {
+    stdout.lines()
+        .filter_map(|line| line.strip_prefix("  \""))
+        .filter_map(|line| line.split('"').next())
+        .collect()
+}
+
+#[test]
+fn structure_field_names() {
+    assert_eq!(parse_header(&mut file()).unwrap().to_json().to_string(), "{\"e_ident\":[127,69,76,70,2,1,1,0,0,0,0,0,0,0,0,0],\
+\"e_type\":\"DYN\",\"e_machine\":\"AMDX86_64\",\"e_version\":1,\"e_entry\":0,\"e_phoff\":64,\"e_shoff\":944,\"e_flags\":0,\
+\"e_ehsize\":64,\"e_phentsize\":56,\"e_phnum\":4,\"e_shentsize\":64,\"e_shnum\":11,\"e_shstrndx\":10}");
+    assert_eq!(parse_segments(&mut file()).unwrap()[2].to_json().to_string(), "{\"p_type\":\"DYNAMIC\",\"p_flags\":\"RW\",\
+\"p_offset\":584,\"p_vaddr\":600,\"p_paddr\":600,\"p_filesz\":240,\"p_memsz\":240,\"p_align\":8}");
+    assert_eq!(parse_dynamic(&mut file()).unwrap()[0].to_json().to_string(), "{\"d_tag\":14,\"d_val\":15,\"string\":\"libcmp.so.1\"}");
+    assert_eq!(parse_dynamic_symbols(&mut file()).unwrap()[1].to_json().to_string(), "{\"index\":1,\"name\":\"mul\",\"st_name\":5,\
+\"st_info\":18,\"st_other\":0,\"st_shndx\":6,\"st_value\":572,\"st_size\":6}");
+}
+
+#[test]
+fn all_views_in_one_object() {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .args(["-a", "--output", "json"])
+        .arg(fixture("compare/libcmp-v1.elf"))
+        .output()
+        .expect("Failed to run easyreadelf");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(top_level_keys(&stdout), ["file", "header", "sections", "segments", "dynamic", "symbols", "version_info", "notes"]);
+    assert!(stdout.contains("\n  \"symbols\": {\n    \"symtab\": [],\n    \"dynsym\": [\n"), "{}", stdout);
+    assert!(stdout.contains("\n  \"version_info\": {\n    \"versym\": [\n"), "{}", stdout);
+    assert!(stdout.contains("\n  \"notes\": []\n"), "{}", stdout);
+}
+
+#[test]
+fn notes_field_names() {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .args(["-n", "--output", "json"])
+        .arg(fixture("hardening/hardened.elf"))
+        .output()
+        .expect("Failed to run easyreadelf");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\"section\": \".note.gnu.property\",\n      \"notes\": [\n        {\n          \"name\": \"GNU\",\n          \"n_type\": 5,\n          \"desc\": [\n"), "{}", stdout);
+}
@@ -0,0 +1,69 @@
+//! 地址换算和locate: tests/fixtures/locate/bss.elf的第二个LOAD段依次是.data中的counter(值为1)和.bss中256字节的buffer,
+//! 段的p_filesz在.data之后结束, p_memsz包括.bss
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use easyreadelf::address::AddressMap;
+use easyreadelf::elf::segment::{offset_to_vaddr, parse_segments, read_vaddr, vaddr_to_offset};
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/locate/bss.elf")
+}
+
+#[test]
+fn bss_addresses_have_no_file_offset() {
+    let map = AddressMap::load(&mut File::open(fixture()).unwrap()).unwrap();
+    let counter = map.locate_vaddr(0x332);
+    assert_eq!((counter.offset, counter.bss), (Some(0x332), false));
+    assert_eq!(counter.symbol, Some((String::from("counter"), 2)));
+    let buffer = map.locate_vaddr(0x3ff);
+    assert_eq!((buffer.offset, buffer.segment, buffer.bss), (None, Some(1), true));
+    assert_eq!(buffer.section, Some((11, String::from(".bss"), 0xbf)));
+    assert_eq!(buffer.symbol, Some((String::from("buffer"), 0xbf)));
+    // p_memsz之后的地址不属于任何段
+    let unmapped = map.locate_vaddr(0x440);
+    assert_eq!((unmapped.segment, unmapped.bss), (None, false));
+}
+
+#[test]
+fn translation_stops_at_p_filesz() {
+    let segments = parse_segments(&mut File::open(fixture()).unwrap()).unwrap();
+    assert_eq!(vaddr_to_offset(&segments, 0x333), Some(0x333));
+    assert_eq!(vaddr_to_offset(&segments, 0x340), None);
+    assert_eq!(offset_to_vaddr(&segments, 0x333), Some(0x333));
+    assert_eq!(offset_to_vaddr(&segments, 0x334), None);
+}
+
+#[test]
+fn reads_across_data_and_bss() {
+    let mut file = File::open(fixture()).unwrap();
+    let segments = parse_segments(&mut file).unwrap();
+    let data = read_vaddr(&mut file, &segments, 0x330, 0x20).unwrap();
+    let mut expected = vec![1, 0, 0, 0];
+    expected.resize(0x20, 0);
+    assert_eq!(data, expected);
+    assert!(read_vaddr(&mut file, &segments, 0x430, 0x20).is_err());
+}
+
+#[test]
+fn locate_output() {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .args(["locate"])
+        .arg(fixture())
+        .args(["0x3ff", "0x2000"])
+        .output()
+        .expect("Failed to run easyreadelf");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "\
+0x3ff:
+    vaddr    0x3ff
+    offset   none (past p_filesz, zero-filled at run time)
+    segment  [1] LOAD
+    section  [11] .bss+0xbf
+    symbol   buffer+0xbf
+0x2000:
+    vaddr    0x2000
+    offset   none (not in a LOAD segment)
+");
+}
@@ -0,0 +1,260 @@
+//! 对tests/corpus中的畸形文件运行所有解析器和命令行, 要求它们返回错误而不是panic或按伪造的大小分配内存
+//!
+//! 语料中的文件由正常的目标文件修改而来, 文件名说明了被破坏的字段;
+//! fuzz/中的cargo-fuzz目标发现的崩溃输入也应该放到这里作为回归用例
+
+use std::io;
+use std::io::Cursor;
+use std::panic::catch_unwind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use easyreadelf::addr2line::Symbolizer;
+use easyreadelf::address::AddressMap;
+use easyreadelf::check::check_file;
+use easyreadelf::dwarf::frame::{parse_eh_frame, parse_eh_frame_hdr};
+use easyreadelf::dwarf::info::parse_debug_info;
+use easyreadelf::dwarf::line::parse_debug_line;
+use easyreadelf::elf::archive::parse_archive;
+use easyreadelf::elf::core::parse_core;
+use easyreadelf::elf::dynamic::parse_dynamic;
+use easyreadelf::elf::file::ElfFile;
+use easyreadelf::elf::hash::parse_hash_tables;
+use easyreadelf::elf::header::parse_header;
+use easyreadelf::elf::mapped::MappedElf;
+use easyreadelf::elf::note::{parse_note_sections, parse_note_segments};
+use easyreadelf::elf::section::{find_section, parse_sections, read_section_data};
+use easyreadelf::elf::segment::{parse_segments, read_segment_data, read_vaddr, SegmentType, MAX_VADDR_READ};
+use easyreadelf::elf::symbol::{parse_dynamic_symbols, parse_symbols};
+use easyreadelf::elf::version::parse_versions;
+use easyreadelf::gnu::{write_file_header, write_program_headers, write_section_headers, GnuOptions};
+use easyreadelf::hardening::check_hardening;
+use easyreadelf::size::{size_report, DataSource};
+
+fn corpus() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut files:Vec<PathBuf> = std::fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+    files.sort();
+    files
+}
+
+/// 与fuzz/fuzz_targets中的目标相同, 结果只要不panic即可
+fn run_parsers(data:&[u8]) {
+    let mut elf = Cursor::new(data);
+    let _ = parse_header(&mut elf);
+    if let Ok(sections) = parse_sections(&mut elf) {
+        for section in &sections {
+            let _ = read_section_data(&mut elf, section);
+        }
+    }
+    if let Ok(segments) = parse_segments(&mut elf) {
+        for segment in &segments {
+            let _ = read_segment_data(&mut elf, segment);
+            let _ = read_vaddr(&mut elf, &segments, segment.p_vaddr.saturating_add(segment.p_filesz).saturating_sub(8), 64);
+        }
+    }
+    let _ = parse_symbols(&mut elf);
+    let _ = parse_dynamic_symbols(&mut elf);
+    let _ = parse_dynamic(&mut elf);
+    let _ = parse_note_segments(&mut elf);
+    let _ = parse_note_sections(&mut elf);
+    let _ = parse_versions(&mut elf);
+    if let Ok(archive) = parse_archive(&mut elf) {
+        if !archive.thin {
+            for member in &archive.members {
+                let _ = archive.read_member(&mut elf, Path::new(""), member);
+            }
+        }
+    }
+    let _ = parse_core(&mut elf);
+    let _ = parse_debug_info(&mut elf);
+    let _ = parse_debug_line(&mut elf);
+    if let Ok(Some(eh_frame)) = parse_eh_frame(&mut elf) {
+        let _ = eh_frame.to_string();
+    }
+    let _ = parse_eh_frame_hdr(&mut elf);
+    let _ = check_hardening(&mut elf);
+    let _ = check_file(&mut elf);
+    if let Ok(tables) = parse_hash_tables(&mut elf) {
+        let _ = tables.lookup("main");
+        let _ = tables.histograms();
+        let _ = tables.problems();
+    }
+    for source in [DataSource::SEGMENTS, DataSource::SECTIONS, DataSource::SYMBOLS, DataSource::COMPILE_UNITS] {
+        let _ = size_report(&mut elf, &source);
+    }
+    if let Ok(symbolizer) = Symbolizer::load(&mut elf) {
+        let _ = symbolizer.symbolize(0x1000);
+    }
+    if let Ok(map) = AddressMap::load(&mut elf) {
+        for value in [0, 0x40, 0x1000, 0x4010, u64::MAX] {
+            let _ = map.locate_vaddr(value);
+            let _ = map.locate_offset(value);
+        }
+    }
+    let options = GnuOptions { wide:true, after_header:false };
+    let _ = write_file_header(&mut elf, &mut io::sink());
+    let _ = write_section_headers(&mut elf, &mut io::sink(), &options);
+    let _ = write_program_headers(&mut elf, &mut io::sink(), &options);
+    if let Ok(mapped) = MappedElf::parse(data) {
+        let _ = mapped.sections();
+        let _ = mapped.segments();
+    }
+    if let Ok(file) = ElfFile::from_bytes(data.to_vec()) {
+        let _ = file.build_id();
+        let _ = file.symbols();
+        let _ = file.units();
+        let _ = file.line_programs();
+        let _ = file.read_vaddr(0x1000, 0x100);
+    }
+}
+
+#[test]
+fn parsers_reject_malformed_files() {
+    let mut panics = Vec::new();
+    for path in corpus() {
+        let data = std::fs::read(&path).unwrap();
+        if catch_unwind(|| run_parsers(&data)).is_err() {
+            panics.push(path.display().to_string());
+        }
+    }
+    assert!(panics.is_empty(), "parsers panicked on:\n{}", panics.join("\n"));
+}
+
+#[test]
+fn cli_survives_malformed_files() {
+    let views:[&[&str]; 13] = [
+        &["-a"],
+        &["-I"],
+        &["--hardening"],
+        &["--check"],
+        &["--core"],
+        &["-c"],
+        &["--debug-dump=info"],
+        &["--debug-dump=decodedline"],
+        &["--debug-dump=frames-interp"],
+        &["--output", "gnu", "-e"],
+        &["--dump-vaddr=0x1000:0x100"],
+        &["--dump-vaddr=0x1000:0x100000000"],
+        &["--output", "json", "-a"],
+    ];
+    let mut panics = Vec::new();
+    for path in corpus() {
+        for args in views {
+            let output = Command::new(env!("CARGO_BIN_EXE_easyreadelf")).args(args).arg(&path).output().unwrap();
+            // 出错的文件以1退出, panic以101退出
+            if output.status.code() != Some(0) && output.status.code() != Some(1) {
+                panics.push(format!("easyreadelf {} {}: {}", args.join(" "), path.display(), String::from_utf8_lossy(&output.stderr)));
+            }
+        }
+    }
+    assert!(panics.is_empty(), "{}", panics.join("\n"));
+}
+
+#[test]
+fn check_reports_structural_problems() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/check_broken_layout.elf");
+    let report = check_file(&mut std::fs::File::open(path).unwrap()).unwrap();
+    let mut kinds:Vec<&str> = report.problems.iter().map(|problem| problem.kind).collect();
+    kinds.dedup();
+    assert_eq!(kinds, ["ehsize", "section-link", "section-info", "section-align", "section-eof", "section-overlap", "load-align", "load-order", "phdr"]);
+
+    // 链接器生成的测试程序本身没有问题
+    let report = check_file(&mut std::fs::File::open(std::env::current_exe().unwrap()).unwrap()).unwrap();
+    assert!(report.passed(), "{}", report);
+}
+
+#[test]
+fn larger_entries_are_accepted() {
+    // e_phentsize为72, e_shentsize为80, 多出的字节被忽略
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/wide_entries.elf");
+    let mut elf = std::fs::File::open(path).unwrap();
+    let segments = parse_segments(&mut elf).unwrap();
+    assert_eq!(segments.len(), 13);
+    assert!(matches!(segments[0].p_type, SegmentType::PHDR));
+    let sections = parse_sections(&mut elf).unwrap();
+    assert_eq!(find_section(&sections, ".text").map(|section| section.sh_offset), Some(0x1040));
+}
+
+#[test]
+fn hash_tables_find_dynamic_symbols() {
+    // gcc -shared -Wl,--hash-style=both生成的共享库, 同时有.hash和.gnu.hash
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/hash_tables.elf");
+    let tables = parse_hash_tables(&mut std::fs::File::open(path).unwrap()).unwrap();
+    let (sysv, gnu) = (tables.sysv.as_ref().unwrap(), tables.gnu.as_ref().unwrap());
+    // tls_value的值为0, 但TLS符号仍然可以绑定
+    for name in ["add", "sub", "name", "counter", "tls_value"] {
+        assert_eq!(sysv.lookup(&tables.symbols, &tables.versym, name).map(|symbol| symbol.name.as_str()), Some(name));
+        assert_eq!(gnu.lookup(&tables.symbols, &tables.versym, name).map(|symbol| symbol.name.as_str()), Some(name));
+    }
+    // 未定义的符号和不存在的名字都找不到
+    for name in ["__cxa_finalize", "missing", ""] {
+        assert!(tables.lookup(name).is_none(), "{}", name);
+    }
+    let counts:Vec<Vec<u64>> = tables.histograms().into_iter().map(|histogram| histogram.counts).collect();
+    assert_eq!(counts, [vec![0, 1, 0, 0, 2], vec![0, 1, 2]]);
+    assert!(tables.problems().is_empty());
+
+    // 改写符号表后没有同步更新的哈希表
+    for name in ["gnu_hash_chain_stale.elf", "hash_bucket_cleared.elf"] {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus").join(name);
+        let report = check_file(&mut std::fs::File::open(path).unwrap()).unwrap();
+        assert!(report.problems.iter().all(|problem| problem.kind == "hash") && !report.passed(), "{}: {}", name, report);
+    }
+}
+
+#[test]
+fn define_file_extends_the_file_table() {
+    // 手写的DWARF 4行号程序, 用DW_LNE_define_file定义2号文件b.c后用DW_LNS_set_file切换到它
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/debug_line_define_file.elf");
+    let programs = parse_debug_line(&mut std::fs::File::open(path).unwrap()).unwrap();
+    let files:Vec<String> = programs[0].locations().into_iter().map(|location| location.file).collect();
+    assert_eq!(files, ["a.c", "b.c", "b.c"]);
+}
+
+#[test]
+fn invalid_section_names_are_replaced() {
+    // .interp的第二个字节被改为0xff, 其他节名不受影响
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/section_name_not_utf8.elf");
+    let data = std::fs::read(path).unwrap();
+    let mapped:Vec<String> = MappedElf::parse(&data).unwrap().sections().unwrap().iter().map(|section| section.name.to_string()).collect();
+    let parsed:Vec<String> = parse_sections(&mut Cursor::new(&data)).unwrap().into_iter().map(|section| section.name).collect();
+    assert_eq!(mapped, parsed);
+    assert_eq!(mapped[1], ".\u{fffd}nterp");
+    assert!(mapped.iter().any(|name| name == ".text"));
+}
+
+#[test]
+fn zero_filled_reads_are_capped() {
+    // PT_LOAD的p_memsz为0x8000000000000000, 整个范围都以0填充, 不能按请求的长度分配内存
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus/load_memsz_overflow.elf");
+    let mut elf = std::fs::File::open(path).unwrap();
+    let segments = parse_segments(&mut elf).unwrap();
+    let load = segments.iter().find(|segment| matches!(segment.p_type, SegmentType::LOAD)).unwrap();
+    let error = read_vaddr(&mut elf, &segments, load.p_vaddr, 1 << 40).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(read_vaddr(&mut elf, &segments, load.p_vaddr, MAX_VADDR_READ).map(|data| data.len() as u64).ok(), Some(MAX_VADDR_READ));
+}
+
+#[test]
+fn mapped_section_headers_match_parse_sections() {
+    // ElfFile的节表来自MappedElf::section_headers, 对畸形文件也要与parse_sections的结果和错误相同
+    let summary = |sections:io::Result<Vec<easyreadelf::elf::section::ElfSection>>| sections
+        .map(|sections| sections.into_iter().map(|section| (section.index, section.name, section.sh_type, section.sh_offset, section.sh_size)).collect::<Vec<_>>())
+        .map_err(|e| e.to_string());
+    for path in corpus() {
+        let data = std::fs::read(&path).unwrap();
+        let Ok(elf) = MappedElf::parse(&data) else { continue };
+        assert_eq!(summary(elf.section_headers()), summary(parse_sections(&mut Cursor::new(&data))), "{}", path.display());
+    }
+}
+
+#[test]
+fn parallel_output_matches_serial() {
+    // -j的工作线程与串行处理使用同一个run_file, 输出(包括出错的文件)按输入顺序写出
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let run = |jobs:&str| Command::new(env!("CARGO_BIN_EXE_easyreadelf")).args(["-j", jobs, "-r", "-a", "--hardening"]).arg(&dir).output().unwrap();
+    let (serial, parallel) = (run("1"), run("4"));
+    assert_eq!(serial.status.code(), parallel.status.code());
+    assert_eq!(String::from_utf8_lossy(&serial.stdout), String::from_utf8_lossy(&parallel.stdout));
+    assert_eq!(String::from_utf8_lossy(&serial.stderr), String::from_utf8_lossy(&parallel.stderr));
+}
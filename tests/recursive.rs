@@ -0,0 +1,82 @@
+//! --recursive扫描目录: 在临时目录中用tests/fixtures中的程序搭建目录树, 包括一个截断在程序头表中间的ELF,
+//! 一个文本文件和一个指向目录树外部的符号链接
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn fixture(name:&str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+/// top/a.elf, top/outside.elf -> hardening/weak.elf, top/readme.txt, top/sub/b.elf, top/sub/truncated.elf
+fn tree(name:&str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("easyreadelf-recursive-{}-{}",std::process::id(),name));
+    let _ = fs::remove_dir_all(&root);
+    let top = root.join("top");
+    fs::create_dir_all(top.join("sub")).unwrap();
+    fs::copy(fixture("compare/libcmp-v1.elf"), top.join("a.elf")).unwrap();
+    fs::copy(fixture("locate/bss.elf"), top.join("sub/b.elf")).unwrap();
+    let data = fs::read(fixture("compare/libcmp-v1.elf")).unwrap();
+    fs::write(top.join("sub/truncated.elf"), &data[..100]).unwrap();
+    fs::write(top.join("readme.txt"), "not an ELF file\n").unwrap();
+    std::os::unix::fs::symlink(fixture("hardening/weak.elf"), top.join("outside.elf")).unwrap();
+    root
+}
+
+fn easyreadelf(args:&[&str], path:&Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .args(args)
+        .arg(path)
+        .output()
+        .expect("Failed to run easyreadelf")
+}
+
+/// 输出中各个文件块的文件名, 相对于top
+fn files(stdout:&[u8], top:&Path) -> Vec<String> {
+    String::from_utf8_lossy(stdout).lines()
+        .filter_map(|line| line.strip_prefix("File: "))
+        .map(|path| Path::new(path).strip_prefix(top).unwrap().display().to_string())
+        .collect()
+}
+
+#[test]
+fn failures_are_summarized() {
+    let root = tree("summary");
+    let top = root.join("top");
+    let output = easyreadelf(&["-l", "-r"], &top);
+    assert_eq!(output.status.code(), Some(1));
+    // 出错的文件之后继续处理, 非ELF文件和符号链接默认静默跳过
+    assert_eq!(files(&output.stdout, &top), ["a.elf", "sub/b.elf", "sub/truncated.elf"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, format!("\n1 of 3 files failed:\n    {}: program header table at 0x40 with size 0xe0 extends past the end of the file (0x64 bytes)\n",
+        top.join("sub/truncated.elf").display()));
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn follow_symlinks_and_report_skipped() {
+    let root = tree("follow");
+    let top = root.join("top");
+    let output = easyreadelf(&["-l", "-r", "-L", "--report-skipped"], &top);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(files(&output.stdout, &top), ["a.elf", "outside.elf", "sub/b.elf", "sub/truncated.elf"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with(&format!("{}: not an ELF file, skipped\n",top.join("readme.txt").display())), "{}", stderr);
+    assert!(stderr.contains("\n1 of 4 files failed:\n"), "{}", stderr);
+
+    let output = easyreadelf(&["-l", "-r", "--report-skipped"], &top);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!("{}: symbolic link, skipped\n",top.join("outside.elf").display())), "{}", stderr);
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn directory_needs_recursive() {
+    let root = tree("directory");
+    let output = easyreadelf(&["-h"], &root.join("top"));
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert_eq!(String::from_utf8_lossy(&output.stderr), format!("\n1 of 1 files failed:\n    {}: is a directory, use --recursive to scan it\n",root.join("top").display()));
+    fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,65 @@
+//! size和size --diff: tests/fixtures/compare中libcmp-v2.elf比libcmp-v1.elf多了函数, 重定位和GOT, table从4个int变为8个
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use easyreadelf::size::{diff_sizes, size_report, DataSource, SizeReport};
+
+fn fixture(name:&str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/compare").join(name)
+}
+
+fn report(name:&str, source:DataSource) -> SizeReport {
+    size_report(&mut File::open(fixture(name)).unwrap(), &source).unwrap()
+}
+
+#[test]
+fn entries_add_up_to_the_totals() {
+    for source in ["segments", "sections"] {
+        let report = report("libcmp-v1.elf", DataSource::from(source).unwrap());
+        assert_eq!(report.file_total, std::fs::metadata(fixture("libcmp-v1.elf")).unwrap().len(), "{}", source);
+        assert_eq!(report.vm_total, 848, "{}", source);
+        assert_eq!(report.entries.iter().map(|entry| entry.file_size).sum::<u64>(), report.file_total, "{}", source);
+        assert_eq!(report.entries.iter().map(|entry| entry.vm_size).sum::<u64>(), report.vm_total, "{}", source);
+    }
+}
+
+#[test]
+fn section_deltas() {
+    let diff = diff_sizes(&report("libcmp-v1.elf", DataSource::SECTIONS), &report("libcmp-v2.elf", DataSource::SECTIONS));
+    let delta = |name:&str| diff.entries.iter().find(|entry| entry.name == name).map(|entry| (entry.old, entry.new));
+    assert_eq!(delta(".text"), Some((Some((10, 10)), Some((85, 85)))));
+    assert_eq!(delta(".data"), Some((Some((16, 16)), Some((32, 32)))));
+    assert_eq!(delta(".got.plt"), Some((None, Some((24, 24)))));
+    // 大小没有变化的项不列出
+    assert_eq!(delta(".eh_frame"), None);
+    assert_eq!(delta("[ELF Header]"), None);
+    let largest:Vec<&str> = diff.entries.iter().take(3).map(|entry| entry.name.as_str()).collect();
+    assert_eq!(largest, ["[Section Headers]", ".text", "[Unmapped]"]);
+    assert_eq!((diff.old_total, diff.new_total), ((1648, 848), (2264, 1232)));
+}
+
+#[test]
+fn removed_sections() {
+    let diff = diff_sizes(&report("libcmp-v2.elf", DataSource::SECTIONS), &report("libcmp-v1.elf", DataSource::SECTIONS));
+    let removed:Vec<&str> = diff.entries.iter().filter(|entry| entry.new.is_none()).map(|entry| entry.name.as_str()).collect();
+    assert_eq!(removed, [".got.plt", ".rela.dyn", ".got"]);
+    assert!(diff.entries.iter().all(|entry| entry.old.is_some()));
+}
+
+#[test]
+fn diff_output() {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .args(["size", "-n", "3", "--diff"])
+        .arg(fixture("libcmp-v1.elf"))
+        .arg(fixture("libcmp-v2.elf"))
+        .output()
+        .expect("Failed to run easyreadelf");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rows:Vec<&str> = stdout.lines().skip(2).collect();
+    assert_eq!(rows.len(), 5, "{}", stdout);
+    assert_eq!(rows[1], " +750.0%     +75  +750.0%     +75    .text");
+    assert!(rows[3].ends_with("[12 Others]"), "{}", stdout);
+    assert_eq!(rows[4], " +37.4%    +616  +45.3%    +384    TOTAL");
+}
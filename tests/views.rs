@@ -0,0 +1,62 @@
+//! 组合多个显示选项: 无论选项的顺序如何, 每个文件都按-h, -s, -l, -d, ...的固定顺序输出各个视图,
+//! 使用tests/fixtures/compare/libcmp-v1.elf和tests/fixtures/locate/bss.elf
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn fixture(name:&str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+fn easyreadelf(args:&[&str], files:&[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_easyreadelf"))
+        .args(args)
+        .args(files.iter().map(|file| fixture(file)))
+        .output()
+        .expect("Failed to run easyreadelf");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// text在输出中第一次出现的位置, 找不到时panic
+fn position(stdout:&str, text:&str) -> usize {
+    stdout.find(text).unwrap_or_else(|| panic!("{:?} not in {}", text, stdout))
+}
+
+#[test]
+fn header_before_segments() {
+    let stdout = easyreadelf(&["-l", "-h"], &["compare/libcmp-v1.elf"]);
+    assert_eq!(stdout, easyreadelf(&["-h", "-l"], &["compare/libcmp-v1.elf"]));
+    let header = position(&stdout, "Magic:");
+    let segments = position(&stdout, "There are 4 program headers, starting at offset 64:\n");
+    assert!(header < segments);
+    assert!(stdout.contains("Number of program headers:              4\n"));
+    assert!(stdout.contains("\nDYNAMIC        0x0000000000000248\t0x0000000000000258\t"), "{}", stdout);
+    // 没有请求节表
+    assert!(!stdout.contains(".gnu.hash"), "{}", stdout);
+}
+
+#[test]
+fn headers_aggregate() {
+    let files = ["compare/libcmp-v1.elf"];
+    assert_eq!(easyreadelf(&["-e"], &files), easyreadelf(&["-l", "-s", "-h"], &files));
+}
+
+#[test]
+fn views_repeat_for_each_file() {
+    let stdout = easyreadelf(&["-l", "-h"], &["compare/libcmp-v1.elf", "locate/bss.elf"]);
+    let first = position(&stdout, &format!("File: {}\n",fixture("compare/libcmp-v1.elf").display()));
+    let second = position(&stdout, &format!("File: {}\n",fixture("locate/bss.elf").display()));
+    assert!(first < second);
+    let (first_block, second_block) = stdout.split_at(second);
+    assert!(first_block.contains("Start of section headers:               944(bytes into file)\n"), "{}", first_block);
+    assert!(second_block.contains("Start of section headers:               1136(bytes into file)\n"), "{}", second_block);
+    assert!(position(second_block, "Magic:") < position(second_block, "There are 4 program headers"));
+}
+
+#[test]
+fn json_keys_follow_view_order() {
+    let stdout = easyreadelf(&["-l", "-h", "--output", "json"], &["compare/libcmp-v1.elf"]);
+    assert!(position(&stdout, "\n  \"header\": {") < position(&stdout, "\n  \"segments\": ["));
+    assert!(!stdout.contains("\"sections\""));
+}